@@ -0,0 +1,116 @@
+struct Rule {
+    text: String,
+    pattern: String,
+    attrs: Vec<String>,
+    modified: bool,
+}
+
+enum Line {
+    Other(String),
+    Rule(Rule),
+}
+
+// Split a line into its pattern (which may be a C-style quoted string) and attributes
+fn parse(line: &str) -> Option<(String, Vec<String>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let end = if line.starts_with('"') {
+        let mut escaped = false;
+        line.char_indices()
+            .skip(1)
+            .find(|&(_, c)| {
+                let end = c == '"' && !escaped;
+                escaped = c == '\\' && !escaped;
+                end
+            })
+            .map_or(line.len(), |(i, _)| i + 1)
+    } else {
+        line.find(char::is_whitespace).unwrap_or(line.len())
+    };
+
+    let (pattern, attrs) = line.split_at(end);
+    Some((
+        pattern.to_string(),
+        attrs.split_whitespace().map(String::from).collect(),
+    ))
+}
+
+// `diff`, `-diff`, `!diff` and `diff=foo` all set the state of the attribute `diff`
+fn name(attr: &str) -> &str {
+    let attr = attr.trim_start_matches(['-', '!']);
+    attr.split('=').next().unwrap_or(attr)
+}
+
+/// Merge the attribute lines in `additions` (such as `*.png binary`) into the gitattributes
+/// contents `existing`, returning the new contents.
+///
+/// Unlike ignore patterns, attribute lines are order-sensitive, so nothing is sorted and comments
+/// are kept. An addition whose pattern already has a line gets its attributes merged into the last
+/// such line: attributes already in the same state are skipped, and those in a different state
+/// (`diff` vs `-diff` vs `diff=foo`, say) are replaced. Other additions are appended.
+pub fn merge_attributes<S: AsRef<str>>(existing: &str, additions: &[S]) -> String {
+    let mut lines: Vec<Line> = existing
+        .lines()
+        .map(|line| match parse(line) {
+            None => Line::Other(line.trim_end().to_string()),
+            Some((pattern, attrs)) => Line::Rule(Rule {
+                text: line.trim_end().to_string(),
+                pattern,
+                attrs,
+                modified: false,
+            }),
+        })
+        .collect();
+
+    for (pattern, attrs) in additions.iter().filter_map(|a| parse(a.as_ref())) {
+        let rule = lines.iter_mut().rev().find_map(|line| match line {
+            Line::Rule(rule) if rule.pattern == pattern => Some(rule),
+            _ => None,
+        });
+
+        match rule {
+            None => lines.push(Line::Rule(Rule {
+                text: String::new(),
+                pattern,
+                attrs,
+                modified: true,
+            })),
+            Some(rule) => {
+                for attr in attrs {
+                    match rule.attrs.iter_mut().find(|a| name(a) == name(&attr)) {
+                        Some(a) if *a == attr => {},
+                        Some(a) => {
+                            *a = attr;
+                            rule.modified = true;
+                        },
+                        None => {
+                            rule.attrs.push(attr);
+                            rule.modified = true;
+                        },
+                    }
+                }
+            },
+        }
+    }
+
+    let mut text = String::new();
+    for line in lines {
+        match line {
+            Line::Other(line) => text.push_str(&line),
+            Line::Rule(rule) if !rule.modified => text.push_str(&rule.text),
+            Line::Rule(rule) => {
+                text.push_str(&rule.pattern);
+                for attr in rule.attrs {
+                    text.push(' ');
+                    text.push_str(&attr);
+                }
+            },
+        }
+        text.push('\n');
+    }
+
+    text
+}
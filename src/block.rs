@@ -0,0 +1,73 @@
+/// Replace the managed block called `name` in `existing` with `lines`.
+///
+/// A block is delimited by `# BEGIN git-ignore NAME` and `# END git-ignore NAME` comment lines;
+/// everything outside it is left alone, so files can be regenerated without losing hand-written
+/// rules. A missing block is appended, and an empty `lines` removes the block.
+pub fn set_block<S: AsRef<str>>(existing: &str, name: &str, lines: &[S]) -> String {
+    let begin = format!("# BEGIN git-ignore {}", name);
+    let end = format!("# END git-ignore {}", name);
+
+    let mut block = vec![];
+    if !lines.is_empty() {
+        block.push(begin.clone());
+        block.extend(lines.iter().map(|line| line.as_ref().to_string()));
+        block.push(end.clone());
+    }
+
+    let existing: Vec<&str> = existing.lines().collect();
+    let start = existing.iter().position(|line| line.trim_end() == begin);
+    let stop = start.and_then(|start| {
+        existing[start..]
+            .iter()
+            .position(|line| line.trim_end() == end)
+            .map(|offset| start + offset)
+    });
+
+    let mut text: Vec<String> = match (start, stop) {
+        (Some(start), Some(stop)) => existing[..start]
+            .iter()
+            .map(|line| line.to_string())
+            .chain(block)
+            .chain(existing[stop + 1..].iter().map(|line| line.to_string()))
+            .collect(),
+        _ => {
+            let mut text: Vec<String> = existing.iter().map(|line| line.to_string()).collect();
+            if !block.is_empty() && text.last().is_some_and(|line| !line.trim().is_empty()) {
+                text.push(String::new());
+            }
+            text.extend(block);
+            text
+        },
+    };
+
+    while text.last().is_some_and(|line| line.trim().is_empty()) {
+        text.pop();
+    }
+    if text.is_empty() {
+        return String::new();
+    }
+    text.push(String::new());
+    text.join("\n")
+}
+
+/// The managed blocks of `text` (see [`set_block`]), in order: the name of each, and the lines
+/// between its markers. A block without its end marker is left out, as `set_block` would
+/// append a new one.
+pub fn blocks(text: &str) -> Vec<(&str, Vec<&str>)> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut blocks = vec![];
+    let mut i = 0;
+    while i < lines.len() {
+        if let Some(name) = lines[i].trim_end().strip_prefix("# BEGIN git-ignore ") {
+            let end = format!("# END git-ignore {}", name);
+            let body = &lines[i + 1..];
+            if let Some(len) = body.iter().position(|line| line.trim_end() == end) {
+                blocks.push((name, body[..len].to_vec()));
+                i += len + 2;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    blocks
+}
@@ -0,0 +1,153 @@
+//! Text that isn't all UTF-8.
+//!
+//! On Unix a file name can be any bytes but `/` and NUL, and git reads patterns as bytes, while
+//! this crate works on `str`s. So each byte that isn't part of valid UTF-8 is carried as a
+//! character of its own, U+10FF00 plus the byte, from the end of a private use area that text
+//! otherwise has no business with, and turned back into the byte on the way out. A character
+//! from that range that is really in the input is carried as its own UTF-8 bytes, so it comes
+//! back out unchanged too.
+
+use std::{
+    borrow::Cow,
+    ffi::{OsStr, OsString},
+    fs, io,
+    path::Path,
+};
+
+// Every byte that isn't valid UTF-8 on its own is at least 0x80
+const BASE: u32 = 0x10FF00;
+
+/// Bytes as text, keeping those that aren't UTF-8.
+pub fn decode(bytes: &[u8]) -> String {
+    as_text(bytes).into_owned()
+}
+
+/// Like [`decode`], but borrowing `bytes` when they are already the text.
+pub fn as_text(bytes: &[u8]) -> Cow<'_, str> {
+    if let Ok(valid) = std::str::from_utf8(bytes) {
+        if !valid.chars().any(is_byte) {
+            return Cow::Borrowed(valid);
+        }
+    }
+
+    let mut text = String::with_capacity(bytes.len());
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let e = match std::str::from_utf8(rest) {
+            Ok(valid) => {
+                push_valid(&mut text, valid);
+                break;
+            },
+            Err(e) => e,
+        };
+        let (valid, invalid) = rest.split_at(e.valid_up_to());
+        push_valid(&mut text, std::str::from_utf8(valid).unwrap_or_default());
+        let (invalid, after) = invalid.split_at(e.error_len().unwrap_or(invalid.len()));
+        push_bytes(&mut text, invalid);
+        rest = after;
+    }
+    Cow::Owned(text)
+}
+
+/// A file's contents as text, keeping the bytes that aren't UTF-8, where [`fs::read_to_string`]
+/// would fail.
+pub fn read<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    fs::read(path).map(|bytes| decode(&bytes))
+}
+
+/// The bytes that [`decode`] made `text` from.
+pub fn encode(text: &str) -> Cow<'_, [u8]> {
+    if !text.chars().any(is_byte) {
+        return Cow::Borrowed(text.as_bytes());
+    }
+
+    let mut bytes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        if is_byte(c) {
+            bytes.push((u32::from(c) - BASE) as u8);
+        } else {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+        }
+    }
+    Cow::Owned(bytes)
+}
+
+/// An argument or file name as text, keeping what isn't UTF-8 (on Windows, as replacement
+/// characters).
+pub fn from_os(s: &OsStr) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        decode(s.as_bytes())
+    }
+    #[cfg(not(unix))]
+    {
+        s.to_string_lossy().into_owned()
+    }
+}
+
+/// The file name that [`from_os`] made `text` from.
+pub fn to_os(text: &str) -> OsString {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        OsString::from_vec(encode(text).into_owned())
+    }
+    #[cfg(not(unix))]
+    {
+        OsString::from(text)
+    }
+}
+
+fn push_valid(text: &mut String, valid: &str) {
+    for c in valid.chars() {
+        if is_byte(c) {
+            let mut buf = [0; 4];
+            push_bytes(text, c.encode_utf8(&mut buf).as_bytes());
+        } else {
+            text.push(c);
+        }
+    }
+}
+
+// Every byte of a character outside ASCII is at least 0x80 as well
+fn push_bytes(text: &mut String, bytes: &[u8]) {
+    for &byte in bytes {
+        text.extend(char::from_u32(BASE + u32::from(byte)));
+    }
+}
+
+fn is_byte(c: char) -> bool {
+    (BASE + 0x80..=BASE + 0xFF).contains(&u32::from(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_utf8() {
+        let bytes = b"caf\xe9/\n\xff\xfe*.log\n";
+        let text = decode(bytes);
+        assert!(text.starts_with("caf"));
+        assert_eq!(&encode(&text)[..], &bytes[..]);
+    }
+
+    #[test]
+    fn private_use() {
+        for text in ["keep\u{10FFA0}\n", "\u{10FF80}\u{10FFFF}", "\u{10FF7F}"] {
+            assert_eq!(&encode(&decode(text.as_bytes()))[..], text.as_bytes());
+        }
+
+        let mut bytes = "\u{10FFC3}".as_bytes().to_vec();
+        bytes.push(0xC3);
+        assert_eq!(&encode(&decode(&bytes))[..], &bytes[..]);
+    }
+
+    #[test]
+    fn borrowed() {
+        assert!(matches!(as_text(b"*.log\n"), Cow::Borrowed("*.log\n")));
+        assert!(matches!(as_text("\u{10FFA0}".as_bytes()), Cow::Owned(_)));
+    }
+}
@@ -0,0 +1,88 @@
+//! The cache of downloaded templates (and anything else worth keeping between runs).
+//!
+//! It lives in the directory named by the `GIT_IGNORE_CACHE_DIR` environment variable or the
+//! global `ignore.cacheDir` config, or else in `git-ignore` under the user's cache directory
+//! (`$XDG_CACHE_HOME` on Linux).
+
+use crate::{repo, Result};
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// A file in the cache.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry {
+    /// The path of the file, relative to the cache directory.
+    pub path: PathBuf,
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// When the file was last written, if known.
+    pub modified: Option<SystemTime>,
+}
+
+/// The cache directory, which might not exist yet.
+pub fn dir() -> Result<PathBuf> {
+    if let Some(dir) = env::var_os("GIT_IGNORE_CACHE_DIR").filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    if let Some(dir) = repo::config_path("ignore.cacheDir")? {
+        return Ok(dir);
+    }
+
+    match dirs::cache_dir() {
+        Some(dir) => Ok(dir.join("git-ignore")),
+        None => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "Could not find the cache directory",
+        )
+        .into()),
+    }
+}
+
+/// The files in the cache, sorted by path.
+pub fn entries() -> Result<Vec<Entry>> {
+    let root = dir()?;
+    let mut entries = vec![];
+    let mut dirs = vec![root.clone()];
+
+    while let Some(dir) = dirs.pop() {
+        let children = match fs::read_dir(&dir) {
+            Ok(children) => children,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for child in children {
+            let child = child?;
+            let metadata = child.metadata()?;
+            if metadata.is_dir() {
+                dirs.push(child.path());
+            } else {
+                entries.push(Entry {
+                    path: relative(&child.path(), &root),
+                    size: metadata.len(),
+                    modified: metadata.modified().ok(),
+                });
+            }
+        }
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(entries)
+}
+
+/// Remove everything in the cache, returning what was removed.
+pub fn clear() -> Result<Vec<Entry>> {
+    let entries = entries()?;
+
+    match fs::remove_dir_all(dir()?) {
+        Ok(()) => Ok(entries),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(entries),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn relative(path: &Path, root: &Path) -> PathBuf {
+    path.strip_prefix(root).unwrap_or(path).to_path_buf()
+}
@@ -0,0 +1,201 @@
+use crate::export::parse_rule;
+
+/// A standard section of an ignore file; see [`group`].
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Category {
+    /// Operating system clutter (`.DS_Store`, `Thumbs.db`).
+    Os,
+    /// Editor and IDE files (`.idea/`, `*.swp`).
+    Editors,
+    /// Build output (`target/`, `*.o`).
+    Build,
+    /// Installed dependencies (`node_modules/`, `.venv/`).
+    Dependencies,
+    /// Logs (`*.log`).
+    Logs,
+    /// Everything else.
+    Custom,
+}
+
+impl Category {
+    /// Every category, in the order [`group`] writes them.
+    pub const ALL: [Self; 6] = [
+        Self::Os,
+        Self::Editors,
+        Self::Build,
+        Self::Dependencies,
+        Self::Logs,
+        Self::Custom,
+    ];
+
+    /// The section header, without the leading `# `.
+    pub fn header(self) -> &'static str {
+        match self {
+            Self::Os => "OS",
+            Self::Editors => "Editors",
+            Self::Build => "Build",
+            Self::Dependencies => "Dependencies",
+            Self::Logs => "Logs",
+            Self::Custom => "Custom",
+        }
+    }
+
+    fn known(self) -> &'static [&'static str] {
+        match self {
+            Self::Os => &[
+                "$RECYCLE.BIN",
+                ".AppleDouble",
+                ".DS_Store",
+                ".LSOverride",
+                ".Spotlight-V100",
+                ".Trashes",
+                ".directory",
+                ".fseventsd",
+                "._*",
+                ".nfs*",
+                "Desktop.ini",
+                "Thumbs.db",
+                "desktop.ini",
+                "ehthumbs.db",
+            ],
+            Self::Editors => &[
+                "*.iml",
+                "*.sublime-project",
+                "*.sublime-workspace",
+                "*.swo",
+                "*.swp",
+                "*~",
+                "#*#",
+                ".#*",
+                ".classpath",
+                ".idea",
+                ".project",
+                ".settings",
+                ".vs",
+                ".vscode",
+                "Session.vim",
+                "tags",
+            ],
+            Self::Build => &[
+                "*.a",
+                "*.class",
+                "*.dll",
+                "*.dylib",
+                "*.egg-info",
+                "*.exe",
+                "*.lib",
+                "*.o",
+                "*.obj",
+                "*.pyc",
+                "*.pyo",
+                "*.so",
+                ".gradle",
+                "CMakeCache.txt",
+                "CMakeFiles",
+                "__pycache__",
+                "_build",
+                "bin",
+                "build",
+                "cmake-build-*",
+                "dist",
+                "obj",
+                "out",
+                "target",
+            ],
+            Self::Dependencies => &[
+                ".bundle",
+                ".pnp.*",
+                ".venv",
+                ".yarn",
+                "Pods",
+                "bower_components",
+                "jspm_packages",
+                "node_modules",
+                "vendor",
+                "venv",
+            ],
+            Self::Logs => &[
+                "*.log",
+                "logs",
+                "npm-debug.log*",
+                "pip-log.txt",
+                "yarn-debug.log*",
+                "yarn-error.log*",
+            ],
+            Self::Custom => &[],
+        }
+    }
+}
+
+/// The category of a gitignore pattern, going by a built-in list of well-known patterns.
+///
+/// Patterns are compared without their anchoring or trailing slash, so `/target/` and `target`
+/// are both build output; `*.log.*` style variants of a known extension count too.
+pub fn classify(pattern: &str) -> Category {
+    let rule = match parse_rule(pattern) {
+        Some(rule) => rule,
+        None => return Category::Custom,
+    };
+    let name = rule.pattern.strip_prefix("**/").unwrap_or(rule.pattern);
+
+    Category::ALL
+        .iter()
+        .copied()
+        .find(|category| {
+            category.known().iter().any(|known| {
+                name == *known
+                    || (known.starts_with("*.") && name.starts_with(known) && {
+                        let rest = &name[known.len()..];
+                        rest == "*" || rest.starts_with('.')
+                    })
+            })
+        })
+        .unwrap_or(Category::Custom)
+}
+
+/// Regroup the patterns of the ignore file contents `text` under a `# HEADER` comment per
+/// [`Category`], each sorted and without duplicates.
+///
+/// Other comments are dropped. Negated patterns go last, under `# Exceptions`, so that they still
+/// override the patterns they used to follow.
+pub fn group(text: &str) -> String {
+    let mut groups: Vec<Vec<&str>> = vec![vec![]; Category::ALL.len() + 1];
+
+    for line in text.lines().map(str::trim) {
+        if let Some(rule) = parse_rule(line) {
+            let index = if rule.negated {
+                Category::ALL.len()
+            } else {
+                classify(line) as usize
+            };
+            if !groups[index].contains(&line) {
+                groups[index].push(line);
+            }
+        }
+    }
+
+    let mut result = String::new();
+    let headers = Category::ALL
+        .iter()
+        .map(|c| c.header())
+        .chain(["Exceptions"]);
+    for (header, mut patterns) in headers.zip(groups) {
+        if patterns.is_empty() {
+            continue;
+        }
+        patterns.sort_unstable();
+
+        if !result.is_empty() {
+            result.push('\n');
+        }
+        result.push_str("# ");
+        result.push_str(header);
+        result.push('\n');
+        for pattern in patterns {
+            result.push_str(pattern);
+            result.push('\n');
+        }
+    }
+
+    result
+}
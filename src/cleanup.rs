@@ -0,0 +1,49 @@
+//! Files to remove if the process is interrupted.
+//!
+//! Destructors don't run when a signal kills the process, so anything that must not outlive it,
+//! such as the lock files of [`IgnoreFile`](crate::IgnoreFile)s, is registered here, and a
+//! signal handler calls [`remove_all`] before exiting.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+static PATHS: Mutex<Vec<PathBuf>> = Mutex::new(vec![]);
+
+/// Remove the file or directory at `path` if the process is interrupted.
+pub fn register<P: AsRef<Path>>(path: P) {
+    if let Ok(mut paths) = PATHS.lock() {
+        paths.push(path.as_ref().to_path_buf());
+    }
+}
+
+/// Forget a path passed to [`register`], once it has been removed normally.
+pub fn unregister<P: AsRef<Path>>(path: P) {
+    if let Ok(mut paths) = PATHS.lock() {
+        if let Some(i) = paths.iter().rposition(|p| p == path.as_ref()) {
+            paths.remove(i);
+        }
+    }
+}
+
+/// Remove every registered path, ignoring errors.
+pub fn remove_all() {
+    let paths = match PATHS.lock() {
+        Ok(mut paths) => std::mem::take(&mut *paths),
+        Err(_) => return,
+    };
+
+    for path in paths.iter().rev() {
+        match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.is_dir() => {
+                let _ = fs::remove_dir_all(path);
+            },
+            Ok(_) => {
+                let _ = fs::remove_file(path);
+            },
+            Err(_) => {},
+        }
+    }
+}
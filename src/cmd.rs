@@ -0,0 +1,803 @@
+pub mod add;
+pub mod audit;
+pub mod cache;
+pub mod compare;
+pub mod compile;
+pub mod completions;
+pub mod dedupe;
+pub mod derive;
+pub mod export;
+pub mod export_effective;
+pub mod flatten;
+pub mod fmt;
+pub mod from_status;
+pub mod import_from;
+pub mod import_hg;
+pub mod import_svn;
+pub mod install_alias;
+pub mod lsp;
+pub mod manpage;
+pub mod merge_file;
+pub mod plugin;
+pub mod rename_prefix;
+pub mod renames;
+pub mod restore;
+pub mod rewrite;
+pub mod scatter;
+pub mod self_update;
+pub mod set;
+pub mod show;
+pub mod sources;
+pub mod template;
+pub mod verify;
+pub mod watch;
+pub mod which;
+
+use getopt::Opt;
+use git_ignore::{
+    bytes, cleanup, has_conflicts, repo, sources, IgnoreFile, IgnoreOp, Newline, Outcome, Repo,
+    Target,
+};
+use std::{
+    error::Error,
+    fs,
+    io::{self, Read, Write},
+    path::Path,
+    process::{self, Command},
+};
+
+// Exit status after an interrupt, as shells report a process killed by SIGINT
+const INTERRUPTED: i32 = 130;
+
+// Exit statuses for the failures with a known cause, from sysexits.h; other failures exit with 1
+pub const EXIT_INVALID_PATTERN: i32 = 65;
+pub const EXIT_NO_REPO: i32 = 66;
+pub const EXIT_NETWORK: i32 = 69;
+pub const EXIT_PERMISSION: i32 = 77;
+pub const EXIT_CONFIG: i32 = 78;
+
+const PERMISSION_HINT: &str =
+    "-i adds to _/.git/info/exclude instead, which only this clone reads, and -r to _/.gitignore";
+
+// On Ctrl-C, remove lock and temporary files, end any half-written prompt line, and exit; the
+// destructors that normally clean up don't run when the process is interrupted
+pub fn handle_interrupts() {
+    let _ = ctrlc::set_handler(|| {
+        cleanup::remove_all();
+        eprintln!();
+        process::exit(INTERRUPTED);
+    });
+}
+
+// Report a failure with a known cause and exit with its status, leaving the others be
+pub fn exit_status(name: &str, result: program::Result) -> program::Result {
+    let e = match result {
+        Err(e) => e,
+        result => return result,
+    };
+    // With what to try instead, where that isn't obvious
+    let (status, hint) = match git_ignore::Error::find(&*e) {
+        Some(git_ignore::Error::InvalidPattern { .. }) => (EXIT_INVALID_PATTERN, None),
+        Some(git_ignore::Error::RepoNotFound(_)) => (
+            EXIT_NO_REPO,
+            Some("outside a repository, -g adds to the global ignore file, and -f to any file"),
+        ),
+        Some(git_ignore::Error::BareRepo(_)) => (EXIT_NO_REPO, None),
+        Some(git_ignore::Error::InGitDir(_)) => (1, Some("-i adds to info/exclude")),
+        Some(git_ignore::Error::Network { .. }) => (EXIT_NETWORK, None),
+        Some(git_ignore::Error::Unverified { .. }) => (1, Some("-k (--insecure) uses it anyway")),
+        Some(git_ignore::Error::BadConfig { .. }) => (EXIT_CONFIG, None),
+        Some(git_ignore::Error::PermissionDenied(_)) => (EXIT_PERMISSION, Some(PERMISSION_HINT)),
+        _ => match e.downcast_ref::<io::Error>() {
+            Some(io) if io.kind() == io::ErrorKind::PermissionDenied => {
+                (EXIT_PERMISSION, Some(PERMISSION_HINT))
+            },
+            _ => return Err(e),
+        },
+    };
+
+    eprintln!("{}: {}", name, e);
+    if let Some(hint) = hint {
+        eprintln!("({})", hint);
+    }
+    Ok(status)
+}
+
+// The subcommands, with their options (as given to getopt) and a summary for the help
+pub const COMMANDS: &[(&str, &str, &str)] = &[
+    (
+        "audit",
+        "h",
+        "list the ignore files with their size, time, rules and status",
+    ),
+    (
+        "cache",
+        "h",
+        "show or clear the cache of downloaded templates",
+    ),
+    ("compare", "h", "compare two ignore files or repositories"),
+    (
+        "compile",
+        "D:hknOo:R:",
+        "compile '.gitignore.in' (with @include and @template)",
+    ),
+    ("completions", "h", "print a shell completion script"),
+    (
+        "dedupe",
+        "f:ghinrU:",
+        "remove duplicate patterns, keeping the order and comments",
+    ),
+    (
+        "derive",
+        "hnr",
+        "generate '.eslintignore' or '.prettierignore'",
+    ),
+    (
+        "export",
+        "F:ho:",
+        "print the effective rules in another format",
+    ),
+    (
+        "export-effective",
+        "ho:",
+        "print the effective rules as a single '.gitignore'",
+    ),
+    (
+        "flatten",
+        "Dhn",
+        "fold nested '.gitignore' files into the root one",
+    ),
+    (
+        "fmt",
+        "cf:ghiMnr",
+        "tidy an ignore file, optionally grouping it into sections",
+    ),
+    (
+        "from-status",
+        "df:ghilm:nr",
+        "ignore the files git shows as untracked",
+    ),
+    (
+        "import-from",
+        "af:ghinr",
+        "merge the ignore rules of another repository",
+    ),
+    (
+        "import-hg",
+        "f:ghinr",
+        "import the rules of a Mercurial '.hgignore'",
+    ),
+    (
+        "import-svn",
+        "f:G:ghI:inr",
+        "import Subversion ignore properties",
+    ),
+    (
+        "install-alias",
+        "hnw",
+        "check that 'git ignore' works, or set up an alias",
+    ),
+    ("lsp", "h", "run a language server for ignore files"),
+    ("manpage", "h", "print the manual page, in roff"),
+    (
+        "merge-file",
+        "f:ghinr",
+        "merge a whole ignore file, comments and all",
+    ),
+    (
+        "rename-prefix",
+        "f:ghinr",
+        "rewrite patterns under a moved directory",
+    ),
+    (
+        "renames",
+        "ahnR:",
+        "suggest pattern rewrites for renamed directories",
+    ),
+    (
+        "restore",
+        "f:ghinrt:",
+        "list the backups of an ignore file, or restore one",
+    ),
+    (
+        "rewrite",
+        "f:ghinr",
+        "apply a sed-style substitution to every pattern",
+    ),
+    (
+        "scatter",
+        "hn",
+        "move directory-specific rules into nested files",
+    ),
+    (
+        "self-update",
+        "hnu:",
+        "replace this executable with the newest release",
+    ),
+    ("set", "ho:", "combine the patterns of two ignore files"),
+    (
+        "show",
+        "C:f:ghir",
+        "print an ignore file, highlighted in a terminal",
+    ),
+    (
+        "sources",
+        "h",
+        "list the ignore files of the repository in precedence order",
+    ),
+    (
+        "template",
+        "f:ghiknR:ry",
+        "list templates, or add or update them in blocks of their own",
+    ),
+    (
+        "verify",
+        "d:h",
+        "check that two ignore files ignore the same paths",
+    ),
+    (
+        "watch",
+        "f:ghinr",
+        "offer to ignore new untracked files as they appear",
+    ),
+    (
+        "which",
+        "af:ghirs",
+        "print the path of the file patterns would be added to",
+    ),
+];
+
+// Long names for the options that mean the same to every command that has them
+pub const LONG_OPTS: &[(&str, char)] = &[
+    ("dry-run", 'n'),
+    ("file", 'f'),
+    ("global", 'g'),
+    ("help", 'h'),
+    ("internal", 'i'),
+    ("root", 'r'),
+];
+
+// The commands that take options after their operands too, by way of `permute`
+const PERMUTED: &[&str] = &["merge-file", "rename-prefix", "rewrite"];
+
+// Replace the long options of a command (or of adding patterns) by the short ones they stand
+// for, which is all getopt knows, as '--name VALUE' or '--name=VALUE', up to '--' for the
+// commands in PERMUTED and up to the first operand for the others; the arguments of plugins are
+// left as they are
+pub fn long_options(args: Vec<String>) -> Result<Vec<String>, Box<dyn Error>> {
+    let (start, optstring, longs, permuted) = match args.get(1) {
+        Some(arg) if arg.starts_with('-') => (1, add::OPTS, long_names(""), false),
+        Some(arg) => match COMMANDS.iter().find(|&&(command, _, _)| command == arg) {
+            Some(&(command, optstring, _)) => (
+                2,
+                optstring,
+                long_names(command),
+                PERMUTED.contains(&command),
+            ),
+            None => return Ok(args),
+        },
+        None => return Ok(args),
+    };
+    let takes_value = |opt: char| optstring.contains(&format!("{}:", opt));
+
+    let mut args = args.into_iter();
+    let mut translated: Vec<String> = args.by_ref().take(start).collect();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            translated.push(arg);
+            break;
+        }
+        if arg == "-" || !arg.starts_with('-') {
+            translated.push(arg);
+            if permuted {
+                continue;
+            }
+            break;
+        }
+        let long = match arg.strip_prefix("--") {
+            Some(long) => long,
+            None => {
+                // Short options, of which the last may take the next argument as its value
+                let opts = &arg[1..];
+                let value = opts.char_indices().find(|&(_, opt)| takes_value(opt));
+                let needs_next = value.is_some_and(|(i, opt)| i + opt.len_utf8() == opts.len());
+                translated.push(arg);
+                if needs_next {
+                    translated.extend(args.next());
+                }
+                continue;
+            },
+        };
+
+        let (long, value) = match long.split_once('=') {
+            Some((long, value)) => (long, Some(value.to_string())),
+            None => (long, None),
+        };
+        let opt = longs
+            .iter()
+            .find(|&&(name, _)| name == long)
+            .map(|&(_, opt)| opt)
+            .ok_or_else(|| format!("Unknown option '--{}'", long))?;
+        translated.push(format!("-{}", opt));
+        match value {
+            Some(_) if !takes_value(opt) => {
+                return Err(format!("Option '--{}' doesn't take a value", long).into())
+            },
+            Some(value) => translated.push(value),
+            None if takes_value(opt) => match args.next() {
+                Some(value) => translated.push(value),
+                None => return Err(format!("Option '--{}' needs a value", long).into()),
+            },
+            None => {},
+        }
+    }
+
+    translated.extend(args);
+    Ok(translated)
+}
+
+// Long names for the options of one command, besides those every command has
+fn command_long_options(command: &str) -> &'static [(&'static str, char)] {
+    match command {
+        "compile" => compile::LONG_OPTS,
+        "dedupe" => dedupe::LONG_OPTS,
+        "export" => export::LONG_OPTS,
+        "export-effective" => export_effective::LONG_OPTS,
+        "flatten" => flatten::LONG_OPTS,
+        "fmt" => fmt::LONG_OPTS,
+        "from-status" => from_status::LONG_OPTS,
+        "import-from" => import_from::LONG_OPTS,
+        "import-svn" => import_svn::LONG_OPTS,
+        "install-alias" => install_alias::LONG_OPTS,
+        "renames" => renames::LONG_OPTS,
+        "restore" => restore::LONG_OPTS,
+        "self-update" => self_update::LONG_OPTS,
+        "set" => set::LONG_OPTS,
+        "show" => show::LONG_OPTS,
+        "template" => template::LONG_OPTS,
+        "verify" => verify::LONG_OPTS,
+        "which" => which::LONG_OPTS,
+        _ => &[],
+    }
+}
+
+// The long names of the options a command has (the one adding patterns has no name), sorted
+pub fn long_names(command: &str) -> Vec<(&'static str, char)> {
+    let (optstring, longs) = match COMMANDS.iter().find(|&&(name, _, _)| name == command) {
+        Some(&(_, optstring, _)) => (optstring, command_long_options(command)),
+        None => (add::OPTS, add::LONG_OPTS),
+    };
+    let mut names: Vec<(&str, char)> = LONG_OPTS
+        .iter()
+        .chain(longs)
+        .copied()
+        .filter(|&(_, opt)| optstring.contains(opt))
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+// The paragraph of a command's help listing the long names of its options, if it has more than
+// --help
+pub fn print_long_names(command: &str) {
+    let names: Vec<String> = long_names(command)
+        .iter()
+        // Kept on one line
+        .map(|(name, opt)| format!("--{}\0(-{})", name, opt))
+        .collect();
+    let text = match names.split_last() {
+        Some((last, rest)) if !rest.is_empty() => {
+            format!(
+                "The options also have long names: {} and {}.",
+                rest.join(", "),
+                last
+            )
+        },
+        _ => return,
+    };
+
+    println!();
+    let mut line = String::new();
+    for word in text.split(' ') {
+        if !line.is_empty() && line.len() + 1 + word.len() > 80 {
+            println!("{}", line.replace('\0', " "));
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    println!("{}", line.replace('\0', " "));
+}
+
+// Options selecting the file to write, shared by every command that writes one
+pub const TARGET_OPTS: &str = "f:gir";
+
+pub const TARGET_HELP: &str = "  -f FILE  write to FILE
+  -g       write to the global ignore file (core.excludesFile)
+  -i       write to the internal repository ignore file (_/.git/info/exclude)
+  -r       write to the root-level repository ignore file (_/.gitignore)
+           (without any of these, the file the 'ignore.target' config names, as for
+           adding patterns, or '.gitignore')
+  -n       show what would be done without writing anything";
+
+pub fn target_opt(opt: &Opt, target: &mut Target) -> bool {
+    match opt {
+        Opt('f', Some(arg)) => *target = Target::File(bytes::to_os(arg).into()),
+        Opt('g', None) => *target = Target::Global,
+        Opt('i', None) => *target = Target::Internal,
+        Opt('r', None) => *target = Target::Root,
+        _ => return false,
+    }
+
+    true
+}
+
+// Safety checks, which refuse to go ahead unless overridden with -F
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Check {
+    // A new pattern that ignores everything
+    Catastrophic,
+    // Sorting and deduplicating changes what is ignored, beyond what the new patterns do
+    Reorder,
+    // The file has merge conflicts, whose markers would stay
+    Conflicts,
+    // The file is in a git directory, where git doesn't read it
+    GitDir,
+}
+
+impl Check {
+    pub const ALL: &'static [Self] = &[
+        Self::Catastrophic,
+        Self::Reorder,
+        Self::Conflicts,
+        Self::GitDir,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Catastrophic => "catastrophic",
+            Self::Reorder => "reorder",
+            Self::Conflicts => "conflicts",
+            Self::GitDir => "git-dir",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Self::Catastrophic => "a new pattern ignores everything (such as '*')",
+            Self::Reorder => "with -v, tidying the file changes what is ignored",
+            Self::Conflicts => "the file has merge conflicts (which -M resolves)",
+            Self::GitDir => "-f FILE is inside a git directory, unread by git",
+        }
+    }
+}
+
+pub fn print_force_help() {
+    println!("  -F CHECK");
+    println!("           go ahead even if the safety check CHECK fails ('all' for every one):");
+    for check in Check::ALL {
+        println!("             {:<12}  {}", check.name(), check.description());
+    }
+}
+
+// The safety checks overridden with -F
+#[derive(Debug, Default)]
+pub struct Force(Vec<Check>);
+
+impl Force {
+    pub fn add(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        if name == "all" {
+            self.0.extend(Check::ALL);
+            return Ok(());
+        }
+
+        match Check::ALL.iter().find(|check| check.name() == name) {
+            Some(check) => {
+                self.0.push(*check);
+                Ok(())
+            },
+            None => {
+                let names: Vec<&str> = Check::ALL.iter().map(|check| check.name()).collect();
+                Err(format!(
+                    "Unknown check '{}' (expected all, {})",
+                    name,
+                    names.join(", ")
+                )
+                .into())
+            },
+        }
+    }
+
+    // Fail with `message` unless `check` is overridden, in which case only warn
+    pub fn check(&self, check: Check, message: &str) -> Result<(), CheckFailed> {
+        if self.0.contains(&check) {
+            eprintln!("Warning: {} (forced)", message);
+            Ok(())
+        } else {
+            Err(CheckFailed(format!(
+                "{} (use -F {} to go ahead anyway)",
+                message,
+                check.name()
+            )))
+        }
+    }
+}
+
+// The error of a failed safety check, which -x exits with a status of its own for
+#[derive(Debug)]
+pub struct CheckFailed(String);
+
+impl std::fmt::Display for CheckFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for CheckFailed {}
+
+pub fn update(op: IgnoreOp) -> Result<Outcome, Box<dyn Error>> {
+    let outcome = op.newline(newline()?).execute()?;
+    report(&outcome);
+    link_dotfiles(&outcome)?;
+    Ok(outcome)
+}
+
+pub fn report(outcome: &Outcome) {
+    eprint!("Updating {}... ", outcome.path.to_string_lossy());
+    if !outcome.changed() {
+        eprintln!("Nothing to do!");
+    } else if outcome.written {
+        eprintln!("Done!");
+    } else {
+        eprintln!("Skipped (dry run)");
+    }
+    if let Some(backup) = &outcome.backup {
+        eprintln!("Backed up the old contents to {}", backup.to_string_lossy());
+    }
+}
+
+// The final newline policy, from the 'ignore.finalNewline' config
+fn newline() -> Result<Newline, Box<dyn Error>> {
+    match Repo::discover()
+        .ok()
+        .map(|repo| repo.config("ignore.finalNewline"))
+    {
+        Some(Ok(Some(value))) => Ok(value.parse()?),
+        Some(Err(e)) => Err(e),
+        _ => Ok(Newline::default()),
+    }
+}
+
+// With 'ignore.dotfiles' set, the global ignore file lives in a dotfiles repository: once it
+// changes, make sure git reads it, by pointing core.excludesFile at it or, if that names another
+// file, linking it there
+fn link_dotfiles(outcome: &Outcome) -> Result<(), Box<dyn Error>> {
+    let dotfiles = match repo::config_path("ignore.dotfiles")? {
+        Some(path) if outcome.changed() && same_file(&outcome.path, &path) => path,
+        _ => return Ok(()),
+    };
+    let dry_run = !outcome.written;
+
+    match repo::config_path("core.excludesFile")? {
+        None => {
+            eprint!(
+                "Setting core.excludesFile to {}... ",
+                dotfiles.to_string_lossy()
+            );
+            if dry_run {
+                eprintln!("Skipped (dry run)");
+                return Ok(());
+            }
+            let status = Command::new("git")
+                .args(["config", "--global", "core.excludesFile"])
+                .arg(&dotfiles)
+                .status()?;
+            if !status.success() {
+                return Err(format!("git config failed ({})", status).into());
+            }
+            eprintln!("Done!");
+        },
+        Some(path) if same_file(&path, &dotfiles) => {},
+        Some(path) if path.symlink_metadata().is_err() => {
+            eprint!(
+                "Linking {} to {}... ",
+                path.to_string_lossy(),
+                dotfiles.to_string_lossy()
+            );
+            if dry_run {
+                eprintln!("Skipped (dry run)");
+                return Ok(());
+            }
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            symlink(&dotfiles, &path)?;
+            eprintln!("Done!");
+        },
+        Some(path) => {
+            eprintln!(
+                "Warning: core.excludesFile is {}, so git does not read {}",
+                path.to_string_lossy(),
+                dotfiles.to_string_lossy()
+            );
+            eprintln!("(remove the former, or point core.excludesFile at the latter)");
+        },
+    }
+
+    Ok(())
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (repo::canonicalize(a), repo::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+#[cfg(unix)]
+fn symlink(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn symlink(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(original, link)
+}
+
+// Replace the contents of a loaded file with `f` of them, reporting like `update`; the result must
+// not have merge conflicts
+pub fn rewrite<F>(file: &mut IgnoreFile, dry_run: bool, f: F) -> Result<(), Box<dyn Error>>
+where
+    F: FnOnce(&str) -> String,
+{
+    let before = file.text().to_string();
+    file.set_newline(newline()?);
+    file.set_text(f(&before));
+    if has_conflicts(file.text()) {
+        return Err(format!(
+            "{} has unresolved merge conflicts (resolve them, or run '{} fmt -M' to keep both sides)",
+            file.path().to_string_lossy(),
+            env!("CARGO_PKG_NAME")
+        )
+        .into());
+    }
+    let written = !dry_run && file.save()?;
+
+    let outcome = Outcome {
+        path: file.path().to_path_buf(),
+        before,
+        after: file.text().to_string(),
+        written,
+        backup: None,
+    };
+    report(&outcome);
+    link_dotfiles(&outcome)
+}
+
+// Ask a question on the terminal; `None` at the end of the input
+pub fn prompt(question: &str) -> io::Result<Option<String>> {
+    eprint!("{} ", question);
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer)? == 0 {
+        eprintln!();
+        return Ok(None);
+    }
+    Ok(Some(answer.trim().to_string()))
+}
+
+// Read a file, or standard input for '-'
+pub fn read_input(input: &str) -> io::Result<String> {
+    if input == "-" {
+        let mut text = vec![];
+        io::stdin().read_to_end(&mut text)?;
+        Ok(bytes::decode(&text))
+    } else {
+        bytes::read(bytes::to_os(input))
+    }
+}
+
+// The contents of every existing ignore file of a repository, with the directory each applies
+// to, in increasing order of precedence
+pub fn effective_rules(repo: &Repo) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut rules = vec![];
+
+    for source in sources(repo)? {
+        match bytes::read(&source.path) {
+            Ok(text) => rules.push((source.base, text)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(rules)
+}
+
+// Move the options that follow the operands to the front, as GNU getopt does; `optstring` says
+// which options take an argument
+pub fn permute(args: &[String], optstring: &str) -> Vec<String> {
+    let takes_argument = |c: char| optstring.contains(&format!("{}:", c));
+    let mut options = args[..1].to_vec();
+    let mut operands = vec![];
+    let mut args = args[1..].iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            operands.extend(args.by_ref().cloned());
+        } else if arg.len() > 1 && arg.starts_with('-') {
+            options.push(arg.clone());
+            let cluster = &arg[1..];
+            let needs_value = cluster
+                .char_indices()
+                .find(|&(_, c)| takes_argument(c))
+                .is_some_and(|(i, c)| i + c.len_utf8() == cluster.len());
+            if needs_value {
+                options.extend(args.next().cloned());
+            }
+        } else {
+            operands.push(arg.clone());
+        }
+    }
+
+    options.push(String::from("--"));
+    options.extend(operands);
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn long() {
+        let translate = |line| long_options(args(line)).unwrap().join(" ");
+        assert_eq!(translate("gi --root --dry-run foo"), "gi -r -n foo");
+        assert_eq!(translate("gi -rn --file x foo"), "gi -rn -f x foo");
+        assert_eq!(
+            translate("gi -f --root --file=y foo"),
+            "gi -f --root -f y foo"
+        );
+        assert_eq!(
+            translate("gi which --file=.ignore -s"),
+            "gi which -f .ignore -s"
+        );
+        assert_eq!(translate("gi merge-file - --dry-run"), "gi merge-file - -n");
+        assert_eq!(translate("gi merge-file a --root"), "gi merge-file a -r");
+        assert_eq!(
+            translate("gi rename-prefix a/ --file x b/ --root"),
+            "gi rename-prefix a/ -f x b/ -r"
+        );
+        assert_eq!(
+            translate("gi rewrite s/a/b/ -- --root"),
+            "gi rewrite s/a/b/ -- --root"
+        );
+        assert_eq!(translate("gi foo --root"), "gi foo --root");
+        assert_eq!(translate("gi -- --root"), "gi -- --root");
+        assert_eq!(
+            translate("gi no-such-command --root"),
+            "gi no-such-command --root"
+        );
+
+        assert!(long_options(args("gi --no-such-option")).is_err());
+        assert!(long_options(args("gi --root=yes")).is_err());
+        assert!(long_options(args("gi merge-file a --file")).is_err());
+    }
+
+    #[test]
+    fn permuted() {
+        let permuted = |line| permute(&args(line), "f:hnr").join(" ");
+        assert_eq!(permuted("rewrite a -r b"), "rewrite -r -- a b");
+        assert_eq!(permuted("rewrite -f x a -n"), "rewrite -f x -n -- a");
+        assert_eq!(permuted("rewrite a -rf x b"), "rewrite -rf x -- a b");
+        assert_eq!(permuted("rewrite a -fx"), "rewrite -fx -- a");
+        assert_eq!(permuted("rewrite - a -- -r"), "rewrite -- - a -r");
+        assert_eq!(permuted("rewrite"), "rewrite --");
+    }
+}
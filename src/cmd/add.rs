@@ -0,0 +1,1446 @@
+use super::{
+    print_force_help, prompt, update,
+    verify::{divergent, relative_dir},
+    watch::suggest,
+    Check, CheckFailed, Force, COMMANDS, EXIT_CONFIG, EXIT_INVALID_PATTERN, EXIT_NETWORK,
+    EXIT_NO_REPO, EXIT_PERMISSION,
+};
+use getopt::Opt;
+use git_ignore::{
+    bytes, date::DateTime, has_conflicts, has_lines, is_ignored, missing_rules, repo,
+    to_dockerignore, to_dockerignore_rules, unified_diff, IgnoreOp, Index, Kind, MergeOptions,
+    Normalization, Outcome, Pattern, Repo, Rules, Target,
+};
+use is_terminal::IsTerminal;
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    error::Error,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+// Also listed by the completion scripts
+pub const OPTS: &str = "AabdDF:f:ghiklMnNPp:rsS:Tt:U:uvwxz";
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[
+    ("annotate", 'A'),
+    ("ask", 'P'),
+    ("attributes", 'a'),
+    ("backup", 'b'),
+    ("diff", 'D'),
+    ("docker", 'd'),
+    ("force", 'F'),
+    ("keep", 'k'),
+    ("list", 'l'),
+    ("normalize", 'U'),
+    ("npm", 'N'),
+    ("null", 'z'),
+    ("print-path", 'w'),
+    ("protect-tracked", 'T'),
+    ("resolve", 'M'),
+    ("section", 'S'),
+    ("separator", 'p'),
+    ("sparse", 's'),
+    ("strict", 'x'),
+    ("tool", 't'),
+    ("untracked", 'u'),
+    ("verify", 'v'),
+];
+
+// The exit statuses with -x, which stay the same from one version to the next
+const STRICT_CHANGED: i32 = 0;
+const STRICT_USAGE: i32 = 1;
+const STRICT_UNCHANGED: i32 = 2;
+const STRICT_CHECK_FAILED: i32 = 3;
+const STRICT_NO_REPO: i32 = 4;
+const STRICT_FAILED: i32 = 5;
+
+fn usage_line(program_name: &str) -> String {
+    format!(
+        "Usage: {} [-h] [-AabDdgiklnMNPrsTuvwxz] [-F CHECK] [-f FILE] [-p SEP] [-S SECTION] [-t TOOL] [-U FORM] [--] pattern [pattern ...]",
+        program_name
+    )
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -f FILE  add patterns to FILE, warning if it is in another repository than the");
+    println!("           current directory (or only one of them is in any); in a git directory,");
+    println!("           FILE can only be one of the files in 'info' that git reads");
+    println!("  -g       add patterns to global ignore file (core.excludesFile); with the");
+    println!("           'ignore.dotfiles' config set, that file instead, which");
+    println!("           core.excludesFile is then set to (or linked to, if it names another,");
+    println!("           missing file)");
+    println!("  -i       add patterns to internal repository ignore file (_/.git/info/exclude)");
+    println!("           which linked worktrees share");
+    println!("  -r       add patterns to root-level repository ignore file (_/.gitignore)");
+    println!("  -s       add patterns to sparse-checkout file (_/.git/info/sparse-checkout);");
+    println!("           in cone mode, patterns are directories");
+    println!();
+    println!(
+        "  -a       edit attributes instead of ignore patterns: each argument is a line such as"
+    );
+    println!("           '*.png binary', and -g, -i and -r select core.attributesFile,");
+    println!("           _/.git/info/attributes and _/.gitattributes");
+    println!("  -d       also add the patterns, translated, to the '.dockerignore' of the build");
+    println!("           context (the repository root, or the current directory outside one)");
+    println!(
+        "  -N       edit '.npmignore' instead of '.gitignore'; since npm disregards '.gitignore'"
+    );
+    println!("           once '.npmignore' exists, the rules of the '.gitignore' next to it are");
+    println!("           copied over when it is created, or when no patterns are given");
+    println!("  -t TOOL  edit the search tool ignore file instead of '.gitignore': '.ignore' for");
+    println!("           'any', '.rgignore' for 'ripgrep', '.fdignore' for 'fd' (-g selects");
+    println!("           fd's global ignore file)");
+    println!("  -A       put a comment such as '# added 2024-05-01 by git-ignore (user)' above");
+    println!("           the new patterns, and keep the file's other comments; the text comes");
+    println!("           from the 'ignore.annotation' config, where {{date}} and {{user}} expand");
+    println!("  -S SECTION");
+    println!("           add the patterns under the comment header '# SECTION', creating it at");
+    println!("           the end if needed, and keep the file's other comments");
+    println!("  -p SEP   split each argument into patterns at SEP (such as ','), or at any");
+    println!("           whitespace if SEP is ' '; a separator escaped with '\\' is kept");
+    println!("  -U FORM  write the patterns, and those already in the file, in the Unicode");
+    println!("           normalization form FORM: 'nfc' (composed, as typed) or 'nfd'");
+    println!("           (decomposed, as macOS returns file names); by default, as they are,");
+    println!("           but for an argument naming a file, which is spelled as git sees its");
+    println!("           name (with a warning if that is different)");
+    println!("  -T       skip the patterns matching tracked files, which they would not");
+    println!("           ignore, and say which they are; the default with the");
+    println!("           'ignore.protectTracked' config set");
+    println!("  -P       ask about each pattern the file doesn't have yet, showing what it would");
+    println!("           ignore (or re-include) in the working tree: 'y' to add it, 'n' to skip");
+    println!("           it, 'a' to add it and the rest, 'q' to skip the rest");
+    println!("  -b       copy the file to 'NAME.bak-TIMESTAMP' before changing it, next to it or");
+    println!("           in the directory the global 'ignore.backupDir' config names, so that");
+    println!("           files git doesn't track (such as _/.git/info/exclude) can be recovered;");
+    println!("           the default with the 'ignore.backup' config set");
+    println!("  -M       if the file has merge conflicts, resolve them by keeping both sides");
+    println!("           instead of failing");
+    println!("  -v       check that sorting and deduplicating the file doesn't change what its");
+    println!("           patterns ignore in the working tree (as moving a pattern past a");
+    println!("           negation would), beyond what adding the new ones at the end would do,");
+    println!("           and fail if so, listing the paths; the default with the");
+    println!("           'ignore.verifyMerge' config set");
+    print_force_help();
+    println!("  -k       if the file already has every pattern, leave it exactly as it is");
+    println!("           (unsorted, say), only looking through it; much faster for huge files");
+    println!("  -l       list the patterns on standard output, a line each: 'added PATTERN' for");
+    println!("           those added (or that would be, in a dry run), and 'present PATTERN'");
+    println!("           for those the file already had");
+    println!("  -D       print a unified diff of the changes on standard output");
+    println!("  -w       print the absolute path of the file on standard output, after the");
+    println!("           lines of -l, so that a script can open it afterwards");
+    println!("  -u       also add a pattern for each untracked file or directory in the output");
+    println!("           of 'git status --porcelain' (with or without -z) on standard input;");
+    println!("           well-known clutter is ignored wherever it is, and anything else just");
+    println!("           where it is");
+    println!("  -z       also read patterns from standard input, each ended by a NUL byte, and");
+    println!("           end the lines of -l and -w with a NUL byte instead of a newline; with");
+    println!("           -u, only the latter");
+    println!("  -n       show what would be done without writing anything");
+    println!(
+        "  -x       exit with a status saying what happened, for scripts: {} if the file",
+        STRICT_CHANGED
+    );
+    println!(
+        "           changed (or would, in a dry run), {} for a usage error, {} if there was",
+        STRICT_USAGE, STRICT_UNCHANGED
+    );
+    println!(
+        "           nothing to do, {} if a safety check failed, {} if a repository was needed",
+        STRICT_CHECK_FAILED, STRICT_NO_REPO
+    );
+    println!(
+        "           but not found, and {} for any other failure",
+        STRICT_FAILED
+    );
+    println!();
+    println!("  -h       display this help");
+    println!();
+    println!("By default, patterns are added to the file '.gitignore' (or '.gitattributes') in");
+    println!("the current directory, or where the 'ignore.target' config says: 'root',");
+    println!("'internal' or 'global' for the file -r, -i or -g selects (so that after 'git");
+    println!("config --global ignore.target root', patterns go to the root '.gitignore' from");
+    println!("any subdirectory), or 'local'. '-f .gitignore' then names the file here. Set");
+    println!("in a repository, as by 'git config ignore.target internal' where its tracked");
+    println!("'.gitignore' is not to be touched, it takes precedence there, for every command.");
+    println!("The specified file is created if it does not exist. A pattern starting with '#'");
+    println!("or '!' that names an existing file is escaped with '\\', so that it ignores the");
+    println!("file. A pattern starting with '-', or named like a command, goes after '--', as");
+    println!(
+        "in '{} -- -output/'; after the first pattern, arguments are all patterns.",
+        program_name
+    );
+    println!("Files keep ending with a newline or not, as they did; the 'ignore.finalNewline'");
+    println!("config can instead be 'always' or 'never'.");
+    println!("With the 'ignore.askTarget' config set, when no file is given in a repository");
+    println!("and the input is a terminal, asks whether to add the patterns to the root");
+    println!("'.gitignore', the nearest one, the internal file, or the global one, showing");
+    println!("where each is.");
+    println!();
+    println!("Each option also has a long name, given as '--name VALUE' or '--name=VALUE' if");
+    println!("it takes a value: --annotate (-A), --ask (-P), --attributes (-a), --backup (-b),");
+    println!("--diff (-D), --docker (-d), --dry-run (-n), --file (-f), --force (-F),");
+    println!("--global (-g), --help (-h), --internal (-i), --keep (-k), --list (-l),");
+    println!("--normalize (-U), --npm (-N), --null (-z), --print-path (-w),");
+    println!("--protect-tracked (-T), --resolve (-M), --root (-r), --section (-S),");
+    println!("--separator (-p), --sparse (-s), --strict (-x), --tool (-t), --untracked (-u)");
+    println!("and --verify (-v). The options of the other commands have long names too, which");
+    println!("their help lists.");
+    println!();
+    println!("Commands (see '{} COMMAND -h'):", program_name);
+    for (command, _, summary) in COMMANDS {
+        if command.len() > 13 {
+            println!("  {}", command);
+            println!("  {:<13}  {}", "", summary);
+        } else {
+            println!("  {:<13}  {}", command, summary);
+        }
+    }
+    println!();
+    println!("If the first argument names an executable 'git-ignore-NAME' on the PATH, it is run");
+    println!("with the remaining arguments instead.");
+    println!();
+    println!(
+        "Failures exit with 1 or, for some causes (unless -x is given), with {} for a",
+        EXIT_INVALID_PATTERN
+    );
+    println!(
+        "pattern an ignore file can't hold, {} if there is no repository or working tree,",
+        EXIT_NO_REPO
+    );
+    println!(
+        "{} if a download failed, {} for lack of permission, and {} for a broken config",
+        EXIT_NETWORK, EXIT_PERMISSION, EXIT_CONFIG
+    );
+    println!("file.");
+}
+
+// What the options ask for
+#[derive(Debug, Default)]
+struct Options {
+    target: Target,
+    kind: Kind,
+    docker: bool,
+    diff: bool,
+    section: Option<String>,
+    annotate: bool,
+    resolve: bool,
+    force: Force,
+    normalize: Normalization,
+    protect: bool,
+    ask: bool,
+    backup: bool,
+    verify: bool,
+    keep: bool,
+    list: bool,
+    print_path: bool,
+    porcelain: bool,
+    nul: bool,
+    dry_run: bool,
+}
+
+pub fn run(name: &str, args: Vec<String>) -> program::Result {
+    // Looked for before anything can fail, since every failure then exits as it says
+    let strict = is_strict(&args);
+
+    match run_as_asked(name, args, strict) {
+        Ok(Done::Help) => Ok(0),
+        Ok(Done::Usage) => Ok(STRICT_USAGE),
+        Ok(Done::Added(_)) if !strict => Ok(0),
+        Ok(Done::Added(true)) => Ok(STRICT_CHANGED),
+        Ok(Done::Added(false)) => Ok(STRICT_UNCHANGED),
+        Err(e) if !strict => Err(e),
+        Err(e) => {
+            eprintln!("{}: {}", name, e);
+            Ok(strict_status(&*e))
+        },
+    }
+}
+
+// What came of running, short of failing
+enum Done {
+    Help,
+    Usage,
+    // Whether the file changed (or would have)
+    Added(bool),
+}
+
+// Whether -x is among the options, which may not parse
+fn is_strict(args: &[String]) -> bool {
+    let takes_value = |opt: char| OPTS.contains(&format!("{}:", opt));
+    let mut args = args.iter().skip(1);
+
+    while let Some(arg) = args.next() {
+        if arg == "--" || !arg.starts_with('-') || arg.len() == 1 {
+            return false;
+        }
+        let opts = &arg[1..];
+        for (i, opt) in opts.char_indices() {
+            if opt == 'x' {
+                return true;
+            }
+            // The rest of the argument, or the next one, is its value
+            if takes_value(opt) {
+                if i + opt.len_utf8() == opts.len() {
+                    args.next();
+                }
+                break;
+            }
+        }
+    }
+    false
+}
+
+// The exit status with -x for a failure
+fn strict_status(e: &(dyn Error + 'static)) -> i32 {
+    if e.is::<UsageError>() {
+        STRICT_USAGE
+    } else if e.is::<CheckFailed>() {
+        STRICT_CHECK_FAILED
+    } else if let Some(git_ignore::Error::RepoNotFound(_)) = git_ignore::Error::find(e) {
+        STRICT_NO_REPO
+    } else {
+        STRICT_FAILED
+    }
+}
+
+// A bad option, or option value, which -x exits with the status of a usage error for
+#[derive(Debug)]
+struct UsageError(Box<dyn Error>);
+
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Error for UsageError {}
+
+fn usage<E: Into<Box<dyn Error>>>(e: E) -> Box<dyn Error> {
+    Box::new(UsageError(e.into()))
+}
+
+// Add the patterns the arguments give, as their options say; usage errors are `UsageError`s
+fn run_as_asked(name: &str, mut args: Vec<String>, strict: bool) -> Result<Done, Box<dyn Error>> {
+    let mut opts = getopt::Parser::new(&args, OPTS);
+    let mut options = Options::default();
+    let mut separator = None;
+
+    loop {
+        match opts.next().transpose().map_err(usage)? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('A', None) => options.annotate = true,
+                Opt('a', None) => options.kind = Kind::Attributes,
+                Opt('b', None) => options.backup = true,
+                Opt('D', None) => options.diff = true,
+                Opt('d', None) => options.docker = true,
+                Opt('F', Some(arg)) => options.force.add(&arg).map_err(usage)?,
+                Opt('f', Some(arg)) => options.target = Target::File(bytes::to_os(&arg).into()),
+                Opt('g', None) => options.target = Target::Global,
+                Opt('i', None) => options.target = Target::Internal,
+                Opt('k', None) => options.keep = true,
+                Opt('l', None) => options.list = true,
+                Opt('M', None) => options.resolve = true,
+                Opt('n', None) => options.dry_run = true,
+                Opt('N', None) => options.kind = Kind::Npm,
+                Opt('P', None) => options.ask = true,
+                Opt('p', Some(arg)) if arg.is_empty() => return Err(usage("Empty separator")),
+                Opt('p', Some(arg)) => separator = Some(arg),
+                Opt('r', None) => options.target = Target::Root,
+                Opt('s', None) => options.target = Target::Sparse,
+                Opt('S', Some(arg)) => options.section = Some(arg),
+                Opt('T', None) => options.protect = true,
+                Opt('t', Some(arg)) => options.kind = Kind::Tool(arg.parse().map_err(usage)?),
+                Opt('U', Some(arg)) => options.normalize = arg.parse().map_err(usage)?,
+                Opt('u', None) => options.porcelain = true,
+                Opt('v', None) => options.verify = true,
+                Opt('w', None) => options.print_path = true,
+                // Looked for already
+                Opt('x', None) => {},
+                Opt('z', None) => options.nul = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(Done::Help);
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if options.target == Target::Local {
+        options.target = Target::configured(options.kind)?;
+    }
+
+    let mut args = args.split_off(opts.index());
+    if options.ask && (options.nul || options.porcelain) {
+        return Err(usage(
+            "-P needs standard input for the answers, which -u and -z read from",
+        ));
+    }
+    if options.porcelain {
+        let mut input = vec![];
+        io::stdin().read_to_end(&mut input)?;
+        let untracked = untracked(&bytes::decode(&input));
+        let untracked = untracked_patterns(&options.target, options.kind, &untracked)?;
+        for path in untracked.outside {
+            eprintln!(
+                "Skipping '{}', which is outside the directory of the file",
+                path
+            );
+        }
+        args.extend(untracked.patterns.into_iter().map(|(pattern, _)| pattern));
+    } else if options.nul {
+        args.extend(read_nul()?);
+    }
+    if let Some(separator) = separator {
+        args = args.iter().flat_map(|arg| split(arg, &separator)).collect();
+    }
+    if (args.is_empty() && options.kind != Kind::Npm && !options.porcelain)
+        || (options.docker && options.kind != Kind::Ignore)
+    {
+        eprintln!("{}", usage_line(name));
+        return Ok(Done::Usage);
+    }
+
+    // Before anything is looked at, so that it fails the same way whatever the options
+    let needs_repo = matches!(
+        options.target,
+        Target::Internal | Target::Root | Target::Sparse
+    );
+    if strict && needs_repo {
+        Repo::discover()?;
+    }
+    Ok(Done::Added(add(args, options)?))
+}
+
+// Add the patterns `args` as the options say; whether the file changed (or would have)
+fn add(mut args: Vec<String>, options: Options) -> Result<bool, Box<dyn Error>> {
+    let Options {
+        mut target,
+        kind,
+        force,
+        ..
+    } = options;
+
+    if target == Target::Local && kind == Kind::Ignore && io::stdin().is_terminal() {
+        if let Ok(repo) = Repo::discover() {
+            if repo.config_bool("ignore.askTarget")? {
+                target = match choose_target(&repo)? {
+                    Some(target) => target,
+                    None => return Err("No file chosen".into()),
+                };
+            }
+        }
+    }
+    // Whether a file named with -f may be in a git directory, once forced
+    let mut git_dir = false;
+    if let Target::File(_) = target {
+        let path = match target.resolve(kind) {
+            Err(e)
+                if matches!(
+                    git_ignore::Error::find(&*e),
+                    Some(git_ignore::Error::InGitDir(_))
+                ) =>
+            {
+                force.check(Check::GitDir, &e.to_string())?;
+                git_dir = true;
+                target.resolve_allowing_git_dir(kind)?
+            },
+            path => path?,
+        };
+        warn_other_repo(&path);
+    }
+    let locate = |kind| match git_dir {
+        true => target.locate_allowing_git_dir(kind),
+        false => target.locate(kind),
+    };
+    // The markers would stay, as more patterns
+    let mut keep_conflicts = false;
+    if !options.resolve {
+        if let Ok(path) = locate(kind) {
+            if bytes::read(&path).is_ok_and(|text| has_conflicts(&text)) {
+                force.check(
+                    Check::Conflicts,
+                    &format!("{} has unresolved merge conflicts", path.to_string_lossy()),
+                )?;
+                keep_conflicts = true;
+            }
+        }
+    }
+
+    if kind != Kind::Attributes {
+        // git reads names as macOS gives them, decomposed, unless it composes them
+        let precompose = cfg!(target_os = "macos") && repo::config_bool("core.precomposeUnicode")?;
+        for arg in &mut args {
+            escape(arg);
+            // With -U, the patterns are rewritten in the form asked for anyway
+            if options.normalize != Normalization::None {
+                continue;
+            }
+            if let Some(seen) = as_git_sees(arg, precompose) {
+                let form = if Normalization::Nfc.apply(&seen) == seen {
+                    "composed"
+                } else {
+                    "decomposed"
+                };
+                eprintln!(
+                    "Warning: git sees the name of the file '{}' with its characters {}, \
+                     which it compares byte for byte; adding it as '{}'",
+                    arg, form, seen
+                );
+                *arg = seen;
+            }
+        }
+    }
+    if kind == Kind::Npm {
+        args.extend(npm_rules(&locate(kind)?, args.is_empty())?);
+    }
+
+    if kind != Kind::Attributes && target != Target::Sparse {
+        for pattern in args.iter().filter(|p| catastrophic(p)) {
+            force.check(
+                Check::Catastrophic,
+                &format!("'{}' would ignore everything", pattern),
+            )?;
+        }
+    }
+    if kind == Kind::Ignore && target != Target::Sparse {
+        let file = locate(kind)?;
+        warn_self_ignore(&target, &file, &args)?;
+        if options.protect || repo::config_bool("ignore.protectTracked")? {
+            args = skip_tracked(&target, &file, args)?;
+        }
+    }
+    if options.ask {
+        args = ask_patterns(&target, &locate(kind)?, kind, args)?;
+    }
+
+    let merge_options = MergeOptions {
+        normalize: options.normalize,
+        ..MergeOptions::default()
+    };
+    let mut op = IgnoreOp::new()
+        .target(target.clone())
+        .allow_git_dir(git_dir)
+        .kind(kind)
+        .add(&args)
+        .options(merge_options)
+        .resolve_conflicts(options.resolve)
+        .keep_conflicts(keep_conflicts)
+        .skip_present(options.keep)
+        .dry_run(options.dry_run);
+    if options.backup || repo::config_bool("ignore.backup")? {
+        op = op.backup(true);
+        if let Some(dir) = repo::config_path("ignore.backupDir")? {
+            op = op.backup_dir(dir);
+        }
+    }
+    if let Some(section) = options.section {
+        op = op.section(section);
+    }
+    if options.annotate {
+        op = op.annotate(annotation()?);
+    }
+    if kind == Kind::Ignore
+        && target != Target::Sparse
+        && (options.verify || repo::config_bool("ignore.verifyMerge")?)
+    {
+        verify_merge(&op, &target, &args, &force)?;
+    }
+    let outcome = update(op)?;
+    if options.diff {
+        print_diff(&outcome)?;
+    }
+    if options.list {
+        for arg in &args {
+            let state = if outcome.adds(arg, merge_options) {
+                "added"
+            } else {
+                "present"
+            };
+            let end = if options.nul { '\0' } else { '\n' };
+            let line = format!("{} {}{}", state, arg, end);
+            io::stdout().write_all(&bytes::encode(&line))?;
+        }
+    }
+    if options.print_path {
+        let end = if options.nul { '\0' } else { '\n' };
+        let line = format!("{}{}", bytes::from_os(outcome.path.as_os_str()), end);
+        io::stdout().write_all(&bytes::encode(&line))?;
+    }
+    if target == Target::Sparse && outcome.written {
+        eprintln!("Run 'git sparse-checkout reapply' to update the working tree.");
+    }
+
+    if options.docker {
+        let (context, base) = docker_context(&target, &outcome.path)?;
+        let patterns = docker_patterns(&args, &base, &outcome.after);
+        let outcome = update(
+            IgnoreOp::new()
+                .target(Target::File(context.join(Kind::Docker.file_name())))
+                .kind(Kind::Docker)
+                .add(patterns)
+                .dry_run(options.dry_run),
+        )?;
+        if options.diff {
+            print_diff(&outcome)?;
+        }
+    }
+
+    Ok(outcome.changed())
+}
+
+fn print_diff(outcome: &Outcome) -> io::Result<()> {
+    let path = bytes::from_os(outcome.path.as_os_str());
+    let diff = unified_diff(&outcome.before, &outcome.after, &path, &path, 3);
+    io::stdout().write_all(&bytes::encode(&diff))
+}
+
+// The patterns on standard input, each ended by a NUL (the last one maybe not)
+fn read_nul() -> Result<Vec<String>, Box<dyn Error>> {
+    let mut input = vec![];
+    io::stdin().read_to_end(&mut input)?;
+
+    let mut patterns = vec![];
+    for piece in input.split(|&byte| byte == 0).filter(|p| !p.is_empty()) {
+        let pattern = bytes::decode(piece);
+        if pattern.contains('\n') {
+            return Err(git_ignore::Error::InvalidPattern {
+                pattern,
+                reason: String::from("spans lines, which an ignore file can't hold"),
+            }
+            .into());
+        }
+        patterns.push(pattern);
+    }
+    Ok(patterns)
+}
+
+// The untracked paths in the output of 'git status --porcelain', relative to the root of the
+// working tree, a directory with a trailing '/'; records end with NUL bytes (-z) or newlines, in
+// which case the special ones are quoted
+pub fn untracked(status: &str) -> Vec<String> {
+    let mut paths = vec![];
+    if status.contains('\0') {
+        let mut records = status.split('\0');
+        while let Some(record) = records.next() {
+            match record.split_at(record.len().min(3)) {
+                ("?? ", path) => paths.push(path.to_string()),
+                // The original path of a rename or copy follows in a record of its own
+                (xy, _) if xy.starts_with(['R', 'C']) => {
+                    records.next();
+                },
+                _ => {},
+            }
+        }
+    } else {
+        for line in status.lines() {
+            if let Some(path) = line.strip_prefix("?? ") {
+                paths.push(unquote(path));
+            }
+        }
+    }
+    paths.retain(|path| !path.is_empty());
+    paths
+}
+
+// A path as git quotes it, with C escapes
+fn unquote(path: &str) -> String {
+    let inner = match path.strip_prefix('"').and_then(|p| p.strip_suffix('"')) {
+        Some(inner) => inner,
+        None => return path.to_string(),
+    };
+
+    let mut raw = vec![];
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            raw.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('a') => raw.push(7),
+            Some('b') => raw.push(8),
+            Some('t') => raw.push(b'\t'),
+            Some('n') => raw.push(b'\n'),
+            Some('v') => raw.push(11),
+            Some('f') => raw.push(12),
+            Some('r') => raw.push(b'\r'),
+            Some(d @ '0'..='7') => {
+                let mut byte = d as u32 - '0' as u32;
+                for _ in 0..2 {
+                    match chars.clone().next().and_then(|d| d.to_digit(8)) {
+                        Some(digit) => {
+                            byte = byte * 8 + digit;
+                            chars.next();
+                        },
+                        None => break,
+                    }
+                }
+                raw.push(byte as u8);
+            },
+            Some(c) => raw.push(c as u8),
+            None => {},
+        }
+    }
+    bytes::decode(&raw)
+}
+
+// Patterns for untracked paths, with how many of the paths each is for, and the paths outside
+// the directory of the file, which are skipped
+pub struct Untracked<'a> {
+    pub patterns: Vec<(String, usize)>,
+    pub outside: Vec<&'a str>,
+}
+
+// Patterns for the untracked `paths` (relative to the root of the working tree) in the ignore
+// file `target`, but for ignore files
+pub fn untracked_patterns<'a>(
+    target: &Target,
+    kind: Kind,
+    paths: &'a [String],
+) -> Result<Untracked<'a>, Box<dyn Error>> {
+    let repo = Repo::discover()?;
+    let dir = match target {
+        Target::Global | Target::Internal => Some(String::new()),
+        _ => match target.locate(kind)?.parent() {
+            Some(base) => relative_dir(&repo, base)?,
+            None => None,
+        },
+    };
+    let dir = match dir {
+        Some(dir) => dir,
+        None => return Err("The file is outside the working tree".into()),
+    };
+
+    let mut patterns: Vec<(String, usize)> = vec![];
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut outside = vec![];
+    for path in paths {
+        let relative = if dir.is_empty() {
+            Some(path.as_str())
+        } else {
+            path.strip_prefix(&dir).and_then(|p| p.strip_prefix('/'))
+        };
+        let relative = match relative {
+            Some(relative) if !relative.is_empty() => relative,
+            _ => {
+                outside.push(path.as_str());
+                continue;
+            },
+        };
+
+        // Ignore files are never junk
+        let name = relative.rsplit('/').next();
+        if name == Some(Kind::Ignore.file_name()) || name == Some(kind.file_name()) {
+            continue;
+        }
+
+        let is_dir = relative.ends_with('/');
+        let pattern = suggest(&escape_glob(relative.trim_end_matches('/')), is_dir);
+        match seen.get(&pattern) {
+            Some(&i) => patterns[i].1 += 1,
+            None => {
+                seen.insert(pattern.clone(), patterns.len());
+                patterns.push((pattern, 1));
+            },
+        }
+    }
+    Ok(Untracked { patterns, outside })
+}
+
+// A name as a pattern matching just it
+fn escape_glob(name: &str) -> String {
+    let mut escaped = String::new();
+    for c in name.chars() {
+        // Nothing matches a newline but a wildcard
+        if c == '\n' {
+            escaped.push('?');
+            continue;
+        }
+        if matches!(c, '*' | '?' | '[' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    // Trailing spaces would be dropped
+    let trimmed = escaped.trim_end_matches(' ').len();
+    if trimmed < escaped.len() {
+        escaped.insert(trimmed, '\\');
+    }
+    escaped
+}
+
+// Ask which ignore file to add the patterns to, showing where each is; `None` if told to stop
+fn choose_target(repo: &Repo) -> Result<Option<Target>, Box<dyn Error>> {
+    let choices = [
+        ("root .gitignore", Target::Root),
+        (
+            "nearest .gitignore",
+            Target::File(nearest_ignore_file(repo)?),
+        ),
+        ("info/exclude", Target::Internal),
+        ("global", Target::Global),
+    ];
+
+    eprintln!("Add the patterns to:");
+    for (i, (label, target)) in choices.iter().enumerate() {
+        let path = target.locate(Kind::Ignore)?;
+        eprintln!("  {}) {:<18}  {}", i + 1, label, path.to_string_lossy());
+    }
+
+    loop {
+        let choice = match prompt("Which one? [1-4, default 2, or q]")?.as_deref() {
+            None | Some("q") => return Ok(None),
+            Some("") => Some(1),
+            Some(answer) => answer.parse::<usize>().ok().and_then(|n| n.checked_sub(1)),
+        };
+        if let Some((_, target)) = choice.and_then(|i| choices.get(i)) {
+            return Ok(Some(target.clone()));
+        }
+    }
+}
+
+// The closest '.gitignore' in the current directory or above it, up to the working tree root;
+// that of the current directory if there is none
+fn nearest_ignore_file(repo: &Repo) -> io::Result<PathBuf> {
+    let cwd = env::current_dir()?;
+    if let Some(root) = repo
+        .workdir()
+        .and_then(|root| repo::canonicalize(root).ok())
+    {
+        let dirs = repo::canonicalize(&cwd)?;
+        for dir in dirs.ancestors().take_while(|dir| dir.starts_with(&root)) {
+            let file = dir.join(Kind::Ignore.file_name());
+            if file.is_file() {
+                return Ok(file);
+            }
+        }
+    }
+
+    Ok(cwd.join(Kind::Ignore.file_name()))
+}
+
+// The pieces of `arg` between the separators `sep` (or runs of whitespace, if it is ' ') that are
+// not escaped with '\', which stay escaped
+fn split(arg: &str, sep: &str) -> Vec<String> {
+    let mut pieces = vec![String::new()];
+    let mut rest = arg;
+
+    while let Some(c) = rest.chars().next() {
+        let separator = if sep == " " && c.is_whitespace() {
+            c.len_utf8()
+        } else if rest.starts_with(sep) {
+            sep.len()
+        } else {
+            0
+        };
+        if separator > 0 {
+            pieces.push(String::new());
+            rest = &rest[separator..];
+            continue;
+        }
+
+        let len = match rest[c.len_utf8()..].chars().next() {
+            Some(next) if c == '\\' => 1 + next.len_utf8(),
+            _ => c.len_utf8(),
+        };
+        if let Some(piece) = pieces.last_mut() {
+            piece.push_str(&rest[..len]);
+        }
+        rest = &rest[len..];
+    }
+
+    pieces.retain(|piece| !piece.is_empty());
+    pieces
+}
+
+// Escape a leading '#' or '!' when the argument names an existing path, since it would otherwise be
+// a comment or a negation
+fn escape(arg: &mut String) {
+    if !arg.starts_with(['#', '!']) {
+        return;
+    }
+
+    if Path::new(&bytes::to_os(arg)).symlink_metadata().is_ok() {
+        arg.insert(0, '\\');
+    } else if arg.starts_with('#') {
+        eprintln!(
+            "Warning: '{}' is a comment, not a pattern; use '\\{}' for a name starting with '#'",
+            arg, arg
+        );
+    }
+}
+
+// The argument spelled as git sees the file it names (from the current directory, as for
+// `escape`), if that is different: a name typed with 'é' composed may be decomposed on disk, and
+// patterns are compared byte for byte
+fn as_git_sees(arg: &str, precompose: bool) -> Option<String> {
+    if arg.is_ascii() {
+        return None;
+    }
+
+    let path = arg.trim_end_matches('/');
+    let mut dir = PathBuf::from(".");
+    let mut seen = vec![];
+    for component in path.split('/') {
+        // A glob names no file of its own
+        if component.contains(['*', '?', '[', '\\']) {
+            return None;
+        }
+        if component.is_ascii() {
+            dir.push(component);
+            seen.push(component.to_string());
+            continue;
+        }
+
+        let names: Vec<String> = fs::read_dir(&dir)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| bytes::from_os(&entry.file_name()))
+            .collect();
+        let wanted = Normalization::Nfc.apply(component);
+        let name = names.iter().find(|name| *name == component).or_else(|| {
+            names
+                .iter()
+                .find(|name| Normalization::Nfc.apply(name) == wanted)
+        })?;
+        dir.push(bytes::to_os(name));
+        seen.push(if precompose {
+            Normalization::Nfc.apply(name).into_owned()
+        } else {
+            name.clone()
+        });
+    }
+
+    let seen = format!("{}{}", seen.join("/"), &arg[path.len()..]);
+    Some(seen).filter(|seen| seen != arg)
+}
+
+// Whether a pattern matches any file or directory at all
+fn catastrophic(pattern: &str) -> bool {
+    let probes = [
+        ("a", false),
+        ("README.md", false),
+        (".env", false),
+        ("src", true),
+    ];
+    match Pattern::parse(pattern) {
+        Some(pattern) if !pattern.is_negated() => probes
+            .iter()
+            .all(|&(path, is_dir)| pattern.matches(path, is_dir)),
+        _ => false,
+    }
+}
+
+// Warn, naming both, when `file` belongs to another repository than the current directory does,
+// or one of them to none, as when a path given to -f leads into the wrong project
+fn warn_other_repo(file: &Path) {
+    // The file, and even its directory, may not exist yet
+    let dir = file.ancestors().skip(1).find(|dir| dir.is_dir());
+    let there = dir.and_then(|dir| Repo::discover_from(dir).ok());
+    let here = Repo::discover().ok();
+    if common_dir(&there) == common_dir(&here) {
+        return;
+    }
+
+    eprintln!(
+        "Warning: {} is not in the repository of the current directory",
+        file.to_string_lossy()
+    );
+    eprintln!(
+        "  repository of the file:              {}",
+        describe(&there)
+    );
+    eprintln!("  repository of the current directory: {}", describe(&here));
+}
+
+// What identifies a repository, whichever of its worktrees or its git directory it was found from
+fn common_dir(repo: &Option<Repo>) -> Option<PathBuf> {
+    repo.as_ref().map(|repo| {
+        git_ignore::repo::canonicalize(repo.common_dir()).unwrap_or_else(|_| repo.common_dir())
+    })
+}
+
+fn describe(repo: &Option<Repo>) -> String {
+    match repo {
+        Some(repo) => repo
+            .workdir()
+            .unwrap_or_else(|| repo.git_dir())
+            .to_string_lossy()
+            .into_owned(),
+        None => "none".to_string(),
+    }
+}
+
+// Warn about new patterns that would ignore the ignore file `file` itself, or '.git'; the global
+// and internal files apply everywhere, so for them, about '.gitignore'
+fn warn_self_ignore(
+    target: &Target,
+    file: &Path,
+    patterns: &[String],
+) -> Result<(), Box<dyn Error>> {
+    let name = match target {
+        Target::Global | Target::Internal => Some(Kind::Ignore.file_name().to_string()),
+        _ => file
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned()),
+    };
+    let patterns: Vec<(&String, Pattern)> = patterns
+        .iter()
+        .filter_map(|p| Pattern::parse(p).map(|pattern| (p, pattern)))
+        .collect();
+    // Only read when needed, as the file may be huge
+    let mut texts = None;
+
+    let paths = name
+        .iter()
+        .map(|name| (name.as_str(), false))
+        .chain([(".git", true)]);
+    for (path, is_dir) in paths {
+        // Without a new pattern matching the path, whether it is ignored stays the same
+        if !patterns.iter().any(|(_, p)| p.matches(path, is_dir)) {
+            continue;
+        }
+
+        if texts.is_none() {
+            let before = match bytes::read(file) {
+                Ok(text) => text,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+                Err(e) => return Err(e.into()),
+            };
+            let mut after = before.clone();
+            for (pattern, _) in &patterns {
+                after.push('\n');
+                after.push_str(pattern);
+            }
+            texts = Some((before, after));
+        }
+        let (before, after) = texts.as_ref().unwrap();
+        if is_ignored(before, path, is_dir) || !is_ignored(after, path, is_dir) {
+            continue;
+        }
+
+        let culprits = patterns
+            .iter()
+            .filter(|(_, p)| !p.is_negated() && p.matches(path, is_dir))
+            .map(|(p, _)| format!("'{}'", p))
+            .collect::<Vec<_>>();
+        eprintln!(
+            "Warning: {} would ignore '{}' itself",
+            culprits.join(", "),
+            path
+        );
+    }
+
+    Ok(())
+}
+
+// Ask about each pattern that the file doesn't have, saying what it would change in the working
+// tree, and keep those accepted
+fn ask_patterns(
+    target: &Target,
+    file: &Path,
+    kind: Kind,
+    patterns: Vec<String>,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let present = has_lines(file, &patterns)?;
+
+    // The file's rules, and the working tree they apply to, for ignore files in a repository
+    let mut tree = None;
+    if kind == Kind::Ignore && *target != Target::Sparse {
+        if let Ok(repo) = Repo::discover() {
+            let base = match target {
+                Target::Global | Target::Internal => repo.workdir(),
+                _ => file.parent(),
+            };
+            if let Some(dir) = base.and_then(|base| relative_dir(&repo, base).ok().flatten()) {
+                let before = match bytes::read(file) {
+                    Ok(text) => text,
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+                    Err(e) => return Err(e.into()),
+                };
+                tree = Some((Index::load(&repo)?, dir, before));
+            }
+        }
+    }
+
+    let mut chosen = vec![];
+    let mut all = false;
+    for (pattern, present) in patterns.into_iter().zip(present) {
+        if present || all {
+            chosen.push(pattern);
+            continue;
+        }
+
+        if let Some((index, dir, before)) = &tree {
+            let after = format!("{}\n{}", before, pattern);
+            let (ignored, included) =
+                divergent(index, dir, &Rules::parse(&after), &Rules::parse(before));
+            if ignored.is_empty() && included.is_empty() {
+                eprintln!("'{}' changes nothing in the working tree yet", pattern);
+            }
+            if !ignored.is_empty() {
+                eprintln!("'{}' would ignore {}", pattern, some_of(&ignored));
+            }
+            if !included.is_empty() {
+                eprintln!("'{}' would re-include {}", pattern, some_of(&included));
+            }
+        }
+
+        loop {
+            match prompt(&format!("Add '{}'? [y/N/a/q]", pattern))?.as_deref() {
+                Some("y") | Some("Y") => chosen.push(pattern),
+                Some("a") | Some("A") => {
+                    all = true;
+                    chosen.push(pattern);
+                },
+                None | Some("q") | Some("Q") => return Ok(chosen),
+                Some("") | Some("n") | Some("N") => {},
+                Some(_) => continue,
+            }
+            break;
+        }
+    }
+
+    Ok(chosen)
+}
+
+// Check that the file `op` writes ignores the same paths of the working tree as it would with
+// the patterns added at its end, untidied
+fn verify_merge(
+    op: &IgnoreOp,
+    target: &Target,
+    patterns: &[String],
+    force: &Force,
+) -> Result<(), Box<dyn Error>> {
+    let repo = match Repo::discover() {
+        Ok(repo) => repo,
+        Err(_) => return Ok(()),
+    };
+    let outcome = op.clone().dry_run(true).execute()?;
+    if !outcome.changed() {
+        return Ok(());
+    }
+    // The directory the patterns are relative to, which has to be in the working tree
+    let base = match target {
+        Target::Global | Target::Internal => repo.workdir(),
+        _ => outcome.path.parent(),
+    };
+    let dir = match base.and_then(|base| relative_dir(&repo, base).ok().flatten()) {
+        Some(dir) => dir,
+        None => return Ok(()),
+    };
+
+    let mut appended = outcome.before.clone();
+    for pattern in patterns {
+        if !appended.is_empty() && !appended.ends_with('\n') {
+            appended.push('\n');
+        }
+        appended.push_str(pattern);
+    }
+    let (only_merged, only_appended) = divergent(
+        &Index::load(&repo)?,
+        &dir,
+        &Rules::parse(&outcome.after),
+        &Rules::parse(&appended),
+    );
+    if only_merged.is_empty() && only_appended.is_empty() {
+        return Ok(());
+    }
+
+    let mut changes = vec![];
+    if !only_merged.is_empty() {
+        changes.push(format!("also ignore {}", some_of(&only_merged)));
+    }
+    if !only_appended.is_empty() {
+        changes.push(format!("stop ignoring {}", some_of(&only_appended)));
+    }
+    force.check(
+        Check::Reorder,
+        &format!(
+            "Tidying {} would {}",
+            outcome.path.to_string_lossy(),
+            changes.join(", and ")
+        ),
+    )?;
+    Ok(())
+}
+
+// A few of `paths`, quoted, and how many others there are
+fn some_of(paths: &[String]) -> String {
+    let mut list: Vec<String> = paths.iter().take(5).map(|p| format!("'{}'", p)).collect();
+    if paths.len() > 5 {
+        list.push(format!("{} more", paths.len() - 5));
+    }
+    list.join(", ")
+}
+
+// The patterns that don't match any file git tracks below the directory of the ignore file
+// `file`, saying which the others match
+fn skip_tracked(
+    target: &Target,
+    file: &Path,
+    patterns: Vec<String>,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let repo = match Repo::discover() {
+        Ok(repo) => repo,
+        Err(_) => return Ok(patterns),
+    };
+    let root = match repo.workdir() {
+        Some(root) => repo::canonicalize(root)?,
+        None => return Ok(patterns),
+    };
+    let base = match target {
+        Target::Global | Target::Internal | Target::Root => root.clone(),
+        _ => match file.parent() {
+            Some(dir) => repo::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf()),
+            None => root.clone(),
+        },
+    };
+    if !base.starts_with(&root) || !base.is_dir() {
+        return Ok(patterns);
+    }
+
+    // The tracked files, relative to the directory of the ignore file
+    let dir = bytes::from_os(base.strip_prefix(&root)?.as_os_str()).replace('\\', "/");
+    let tracked = repo.tracked()?;
+    let tracked: Vec<&str> = tracked
+        .iter()
+        .filter_map(|path| match dir.as_str() {
+            "" => Some(path.as_str()),
+            dir => path.strip_prefix(dir)?.strip_prefix('/'),
+        })
+        .collect();
+
+    // A file is ignored by a pattern matching it, or any directory it is in
+    let matches = |pattern: &Pattern, path: &str| {
+        path.match_indices('/')
+            .any(|(end, _)| pattern.matches(&path[..end], true))
+            || pattern.matches(path, false)
+    };
+
+    Ok(patterns
+        .into_iter()
+        .filter(|arg| {
+            let pattern = match Pattern::parse(arg) {
+                Some(pattern) if !pattern.is_negated() => pattern,
+                _ => return true,
+            };
+            match tracked.iter().find(|path| matches(&pattern, path)) {
+                Some(path) => {
+                    eprintln!("Skipping '{}', which matches tracked file '{}'", arg, path);
+                    false
+                },
+                None => true,
+            }
+        })
+        .collect())
+}
+
+// The annotation comment for new patterns
+fn annotation() -> Result<String, Box<dyn Error>> {
+    let repo = Repo::discover().ok();
+    let config = |key| match &repo {
+        Some(repo) => repo.config(key),
+        None => Ok(None),
+    };
+
+    let user = match config("user.name")? {
+        Some(user) => user,
+        None => env::var("USER")
+            .or_else(|_| env::var("USERNAME"))
+            .unwrap_or_else(|_| String::from("unknown")),
+    };
+    let format = config("ignore.annotation")?
+        .unwrap_or_else(|| String::from("added {date} by git-ignore ({user})"));
+
+    Ok(format.replace("{date}", &today()).replace("{user}", &user))
+}
+
+// The current UTC date, as YYYY-MM-DD
+fn today() -> String {
+    let now = DateTime::now();
+    format!("{:04}-{:02}-{:02}", now.year, now.month, now.day)
+}
+
+// The patterns translated for the '.dockerignore', in the context of the rules of the ignore file
+// (`rules`), warning about the negated ones that would re-include more for Docker than for git
+fn docker_patterns(patterns: &[String], base: &str, rules: &str) -> Vec<String> {
+    let (text, untranslated) = to_dockerignore_rules(&[(base, rules)]);
+    let kept: HashSet<&str> = text.lines().collect();
+
+    let mut translated = vec![];
+    for pattern in patterns {
+        let docker = to_dockerignore(pattern, base);
+        if pattern.trim_start().starts_with('!') && !kept.contains(docker.as_str()) {
+            eprintln!(
+                "Leaving '{}' out of the .dockerignore: it is in a directory git excludes, \
+                 where nothing can be re-included",
+                pattern
+            );
+            continue;
+        }
+        if untranslated
+            .iter()
+            .any(|rule| rule.trim() == pattern.trim())
+        {
+            eprintln!(
+                "Warning: '{}' may re-include files in excluded directories for Docker, \
+                 but not for git",
+                pattern
+            );
+        }
+        translated.push(docker);
+    }
+    translated
+}
+
+// The build context root, and the directory of the ignore file relative to it
+fn docker_context(target: &Target, file: &Path) -> Result<(PathBuf, String), Box<dyn Error>> {
+    let context = match Repo::discover()
+        .ok()
+        .and_then(|r| r.workdir().map(PathBuf::from))
+    {
+        Some(root) => root,
+        None => env::current_dir()?,
+    };
+
+    let base = match target {
+        Target::Global | Target::Internal | Target::Sparse => None,
+        _ => file
+            .parent()
+            .and_then(|dir| dir.strip_prefix(&context).ok())
+            .map(|dir| dir.to_string_lossy().replace('\\', "/")),
+    };
+
+    Ok((context, base.unwrap_or_default()))
+}
+
+// The sibling '.gitignore' rules to mirror into the '.npmignore' `file`: all of them when syncing
+// or when creating the file, otherwise none, but warn about the ones that are missing
+fn npm_rules(file: &Path, sync: bool) -> Result<Vec<String>, Box<dyn Error>> {
+    let gitignore = match file.parent().map(|dir| dir.join(Kind::Ignore.file_name())) {
+        Some(path) if path.is_file() => bytes::read(path)?,
+        _ => return Ok(vec![]),
+    };
+    let exists = file.exists();
+    let npmignore = if exists {
+        bytes::read(file)?
+    } else {
+        String::new()
+    };
+
+    let missing = missing_rules(&gitignore, &npmignore);
+    if missing.is_empty() {
+        return Ok(missing);
+    }
+
+    if sync || !exists {
+        eprintln!(
+            "npm disregards '.gitignore' once '.npmignore' exists; copying {} rule(s) from it",
+            missing.len()
+        );
+        Ok(missing)
+    } else {
+        eprintln!("Warning: npm disregards '.gitignore' once '.npmignore' exists, and these");
+        eprintln!("'.gitignore' rules are missing from it (run with -N alone to copy them):");
+        for rule in &missing {
+            eprintln!("  {}", rule);
+        }
+        Ok(vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn strict() {
+        assert!(is_strict(&args("gi -x foo")));
+        assert!(is_strict(&args("gi -nxz foo")));
+        assert!(is_strict(&args("gi -S section -x foo")));
+        assert!(!is_strict(&args("gi -Sx foo")));
+        assert!(!is_strict(&args("gi -S -x foo")));
+        assert!(!is_strict(&args("gi foo -x")));
+        assert!(!is_strict(&args("gi -- -x")));
+    }
+
+    #[test]
+    fn strict_statuses() {
+        let status = |line| run("gi", args(line)).ok();
+        assert_eq!(status("gi -x -Q foo"), Some(STRICT_USAGE));
+        assert_eq!(status("gi -x -S"), Some(STRICT_USAGE));
+        assert_eq!(status("gi -x -F no-such-check foo"), Some(STRICT_USAGE));
+        assert_eq!(status("gi -x -t no-such-tool foo"), Some(STRICT_USAGE));
+        assert_eq!(status("gi -x -U nfkc foo"), Some(STRICT_USAGE));
+        assert_eq!(status("gi -x -Pz foo"), Some(STRICT_USAGE));
+        assert_eq!(status("gi -x"), Some(STRICT_USAGE));
+        assert!(status("gi -Q foo").is_none());
+
+        let check: Box<dyn Error> = Box::new(CheckFailed(String::from("unsafe")));
+        assert_eq!(strict_status(&*check), STRICT_CHECK_FAILED);
+        let no_repo: Box<dyn Error> = git_ignore::Error::RepoNotFound(PathBuf::from("/")).into();
+        assert_eq!(strict_status(&*no_repo), STRICT_NO_REPO);
+        let config: Box<dyn Error> = io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Unknown ignore.target 'nowhere' (expected local, root, internal or global)",
+        )
+        .into();
+        assert_eq!(strict_status(&*config), STRICT_FAILED);
+        let bad_config: Box<dyn Error> = git_ignore::Error::BadConfig {
+            file: PathBuf::from("config"),
+            line: 1,
+            reason: String::from("bad section"),
+        }
+        .into();
+        assert_eq!(strict_status(&*bad_config), STRICT_FAILED);
+    }
+
+    #[test]
+    fn porcelain() {
+        let status =
+            "?? a.txt\n M tracked\n?? \"sp ace\\tx\"\n?? \"caf\\303\\251/\"\n?? \"\\377\"\n\
+                      ?? \"q\\\"uote\\\\d\"\nR  old -> new\n?? \n";
+        assert_eq!(
+            untracked(status),
+            [
+                String::from("a.txt"),
+                String::from("sp ace\tx"),
+                String::from("café/"),
+                bytes::decode(&[0xff]),
+                String::from("q\"uote\\d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn porcelain_z() {
+        let status = "?? a b\0R  new\0?? odd\0 M x\0?? \"q\"\0?? d/\0";
+        assert_eq!(untracked(status), ["a b", "\"q\"", "d/"]);
+    }
+
+    #[test]
+    fn quoted() {
+        assert_eq!(unquote("plain"), "plain");
+        assert_eq!(unquote("\"half"), "\"half");
+        assert_eq!(
+            unquote("\"\\a\\b\\t\\n\\v\\f\\r\""),
+            "\x07\x08\t\n\x0b\x0c\r"
+        );
+        assert_eq!(unquote("\"\\1\\12x\\0123\""), "\x01\nx\n3");
+        assert_eq!(unquote("\"\\342\\202\\254\""), "€");
+        assert_eq!(unquote("\"é\""), "é");
+    }
+}
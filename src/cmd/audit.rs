@@ -0,0 +1,199 @@
+use super::cache::size;
+use getopt::Opt;
+use git_ignore::{bytes, case_collisions, date::DateTime, sources, Level, Pattern, Repo, Source};
+use std::{
+    collections::{BTreeMap, HashSet},
+    error::Error,
+    fs,
+    io::{self, Write},
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+    time::SystemTime,
+};
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -h       display this help");
+    println!();
+    println!("Lists the ignore files of the current repository in the order git reads them, as");
+    println!("'sources' does, with the size of each, when it was last modified (in UTC), and");
+    println!("how many rules it has, noting those that are missing, symbolic links, tracked or");
+    println!("not, and ignored themselves: an inventory to compare between machines where git");
+    println!("ignores different files. (Since 2.32, git does not read a '.gitignore' that is a");
+    println!("symbolic link.)");
+    println!();
+    println!("Where git ignores case (core.ignoreCase, as on the usual file systems of Windows");
+    println!("and macOS), the patterns that differ from an earlier one only in case, and the");
+    println!("tracked files whose names do, are listed after them: where git does not, they");
+    println!("are different, so the repository behaves differently on different machines.");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "h");
+
+    if let Some(Opt('h', None)) = opts.next().transpose()? {
+        print_usage(name);
+        return Ok(0);
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let repo = Repo::discover()?;
+    let sources = sources(&repo)?;
+    let tree: Vec<String> = sources.iter().filter_map(git_path).collect();
+    let (tracked, ignored) = match repo.workdir() {
+        Some(root) => (
+            repo.tracked()?.into_iter().collect::<HashSet<_>>(),
+            ignored(root, &tree)?,
+        ),
+        None => Default::default(),
+    };
+
+    for source in &sources {
+        let level = match source.level {
+            Level::Global => "global",
+            Level::Internal => "internal",
+            Level::Tree => "tree",
+        };
+        let mut notes = vec![];
+        match fs::symlink_metadata(&source.path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => notes.push("symlink"),
+            Ok(_) => {},
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+            Err(e) => return Err(e.into()),
+        }
+        if let Some(file) = git_path(source) {
+            notes.push(if tracked.contains(&file) {
+                "tracked"
+            } else {
+                "untracked"
+            });
+            if ignored.contains(&file) {
+                notes.push("ignored");
+            }
+        }
+
+        let (size, modified, rules) = match fs::metadata(&source.path) {
+            Ok(metadata) => {
+                let text = bytes::decode(&fs::read(&source.path)?);
+                let rules = text.lines().filter_map(Pattern::parse).count();
+                let modified = metadata.modified().ok().map_or(String::from("?"), date);
+                (size(metadata.len()), modified, rules.to_string())
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                notes.push("missing");
+                (String::from("-"), String::from("-"), String::from("-"))
+            },
+            Err(e) => return Err(e.into()),
+        };
+        let notes = if notes.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", notes.join(", "))
+        };
+        println!(
+            "{:<8}  {:>10}  {:<16}  {:>5}  {}{}",
+            level,
+            size,
+            modified,
+            rules,
+            source.path.to_string_lossy(),
+            notes
+        );
+    }
+
+    if repo.config_bool("core.ignoreCase")? {
+        for source in &sources {
+            let text = match fs::read(&source.path) {
+                Ok(text) => bytes::decode(&text),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            for lint in case_collisions(&text) {
+                println!(
+                    "{}:{}: {}",
+                    source.path.to_string_lossy(),
+                    lint.line + 1,
+                    lint.message
+                );
+            }
+        }
+
+        let mut folded: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+        for file in &tracked {
+            folded.entry(file.to_lowercase()).or_default().push(file);
+        }
+        for mut files in folded.into_values().filter(|files| files.len() > 1) {
+            files.sort_unstable();
+            println!("Tracked files differing only in case: {}", files.join(", "));
+        }
+    }
+
+    Ok(0)
+}
+
+// A '.gitignore' in the working tree, as git names it
+fn git_path(source: &Source) -> Option<String> {
+    match (source.level, source.base.as_str()) {
+        (Level::Tree, "") => Some(String::from(".gitignore")),
+        (Level::Tree, base) => Some(format!("{}/.gitignore", base)),
+        _ => None,
+    }
+}
+
+// Which of `files` the rules ignore, whether they are tracked or not
+fn ignored(root: &Path, files: &[String]) -> Result<HashSet<String>, Box<dyn Error>> {
+    let mut git = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["check-ignore", "-z", "--no-index", "--stdin"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut input = vec![];
+    for file in files {
+        input.extend_from_slice(&bytes::encode(file));
+        input.push(0);
+    }
+    // Written meanwhile, so that neither side waits for the other to read
+    let writer = git
+        .stdin
+        .take()
+        .map(|mut stdin| thread::spawn(move || stdin.write_all(&input)));
+    let output = git.wait_with_output()?;
+    if let Some(writer) = writer {
+        writer
+            .join()
+            .map_err(|_| "could not write to git check-ignore")??;
+    }
+    // 1 means none of them is ignored
+    if !matches!(output.status.code(), Some(0 | 1)) {
+        return Err(format!("git check-ignore failed ({})", output.status).into());
+    }
+    Ok(split(&output.stdout))
+}
+
+fn split(output: &[u8]) -> HashSet<String> {
+    bytes::decode(output)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// The time in UTC, as YYYY-MM-DD HH:MM
+fn date(time: SystemTime) -> String {
+    let date = DateTime::utc(time);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        date.year, date.month, date.day, date.hour, date.minute
+    )
+}
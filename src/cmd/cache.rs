@@ -0,0 +1,68 @@
+use getopt::Opt;
+use git_ignore::cache;
+use std::time::SystemTime;
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] info|clear", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -h       display this help");
+    println!();
+    println!("Shows the cache of downloaded templates (info), with the size and age of each");
+    println!("file, or empties it (clear). The cache is in the directory named by");
+    println!("GIT_IGNORE_CACHE_DIR or the 'ignore.cacheDir' config, or else in 'git-ignore'");
+    println!("under the user's cache directory.");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "h");
+
+    if let Some(Opt('h', None)) = opts.next().transpose()? {
+        print_usage(name);
+        return Ok(0);
+    }
+
+    match &args[opts.index()..] {
+        [command] if command == "info" => {
+            let entries = cache::entries()?;
+            println!("{}", cache::dir()?.to_string_lossy());
+            for entry in &entries {
+                let age = entry
+                    .modified
+                    .and_then(|time| SystemTime::now().duration_since(time).ok())
+                    .map_or(String::from("?"), |age| {
+                        format!("{}d", age.as_secs() / (24 * 60 * 60))
+                    });
+                println!(
+                    "  {:>10}  {:>5}  {}",
+                    size(entry.size),
+                    age,
+                    entry.path.to_string_lossy()
+                );
+            }
+            let total: u64 = entries.iter().map(|entry| entry.size).sum();
+            println!("{} file(s), {}", entries.len(), size(total));
+        },
+        [command] if command == "clear" => {
+            let entries = cache::clear()?;
+            let total: u64 = entries.iter().map(|entry| entry.size).sum();
+            eprintln!("Removed {} file(s), {}", entries.len(), size(total));
+        },
+        _ => {
+            eprintln!("{}", usage_line(name));
+            return Ok(1);
+        },
+    }
+
+    Ok(0)
+}
+
+pub fn size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1048575 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1048576.0),
+    }
+}
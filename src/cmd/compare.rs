@@ -0,0 +1,68 @@
+use super::effective_rules;
+use getopt::Opt;
+use git_ignore::{bytes, compare, export, Repo};
+use std::{error::Error, path::Path};
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] A B", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -h       display this help");
+    println!();
+    println!("Compares the patterns of A and B, each an ignore file or a directory in a");
+    println!("repository (whose effective rules are used), listing the patterns unique to each");
+    println!("and those that are written differently but match the same paths. Exits with 1 if");
+    println!("any pattern is unique to one side.");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "h");
+
+    // -h is the only option
+    if let Some(Opt('h', None)) = opts.next().transpose()? {
+        print_usage(name);
+        return Ok(0);
+    }
+
+    let (a, b) = match &args[opts.index()..] {
+        [a, b] => (a, b),
+        _ => {
+            eprintln!("{}", usage_line(name));
+            return Ok(1);
+        },
+    };
+
+    let comparison = compare(&read_rules(a)?, &read_rules(b)?);
+    for (title, patterns) in [
+        (format!("Only in {}:", a), &comparison.only_a),
+        (format!("Only in {}:", b), &comparison.only_b),
+    ] {
+        if !patterns.is_empty() {
+            println!("{}", title);
+            for pattern in patterns {
+                println!("  {}", pattern);
+            }
+        }
+    }
+    if !comparison.equivalent.is_empty() {
+        println!("Equivalent:");
+        for (a, b) in &comparison.equivalent {
+            println!("  {}  =  {}", a, b);
+        }
+    }
+
+    Ok(if comparison.is_same() { 0 } else { 1 })
+}
+
+// A directory stands for the effective rules of its repository, flattened into one file
+fn read_rules(path: &str) -> Result<String, Box<dyn Error>> {
+    if Path::new(path).is_dir() {
+        Ok(export::to_gitignore(&effective_rules(
+            &Repo::discover_from(path)?,
+        )?))
+    } else {
+        Ok(bytes::read(path)?)
+    }
+}
@@ -0,0 +1,145 @@
+use super::rewrite;
+use getopt::Opt;
+#[cfg(feature = "net")]
+use git_ignore::template::{set_offline, set_reference};
+use git_ignore::{compile, compile_allowing_unverified, IgnoreFile, Repo};
+use std::{collections::HashMap, env, path::PathBuf};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[
+    ("define", 'D'),
+    ("insecure", 'k'),
+    ("offline", 'O'),
+    ("output", 'o'),
+    ("template-ref", 'R'),
+];
+
+fn usage_line(program_name: &str) -> String {
+    format!(
+        "Usage: {} [-hknO] [-D NAME=VALUE]... [-o FILE] [-R REF] [MANIFEST]",
+        program_name
+    )
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -D NAME=VALUE");
+    println!("           set the template variable NAME");
+    println!("  -k       use templates from the registry even if they can't be verified");
+    println!("  -o FILE  write to FILE instead of MANIFEST without its '.in' extension");
+    println!("  -O       never download templates: only use the embedded and cached ones (also");
+    println!("           with the 'ignore.offline' config, or GIT_IGNORE_OFFLINE set)");
+    println!("  -R REF   take the templates from the commit, tag or branch REF of");
+    println!("           github/gitignore instead of the embedded or current ones (also with");
+    println!("           the 'ignore.templateRef' config), so that they are the same wherever");
+    println!("           and whenever the manifest is compiled; the comment above each says so");
+    println!("  -n       show what would be done without writing anything");
+    println!("  -h       display this help");
+    println!();
+    println!("Compiles MANIFEST ('.gitignore.in' by default) into an ignore file. Its lines are");
+    println!("copied as they are, except for these directives:");
+    println!();
+    println!("  @include PATH    insert another file, relative to the manifest's directory");
+    println!("  @template NAME   insert a template from github/gitignore (such as 'Rust')");
+    println!();
+    println!("Placeholders such as '{{{{build_dir}}}}' are replaced by the value of the variable,");
+    println!("from -D or the 'ignore.vars.NAME' config (with '-' for '_'); 'project_name'");
+    println!("defaults to the name of the repository (or current) directory.");
+    super::print_long_names("compile");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "D:hknOo:R:");
+    let mut vars = HashMap::new();
+    let mut output = None;
+    let mut dry_run = false;
+    let mut unverified = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('D', Some(arg)) => match arg.split_once('=') {
+                    Some((name, value)) => {
+                        vars.insert(name.to_string(), value.to_string());
+                    },
+                    None => {
+                        eprintln!("{}", usage_line(name));
+                        return Ok(1);
+                    },
+                },
+                Opt('o', Some(arg)) => output = Some(PathBuf::from(arg)),
+                Opt('n', None) => dry_run = true,
+                Opt('k', None) => unverified = true,
+                // Without the `net` feature, nothing is ever downloaded
+                Opt('O', None) => {
+                    #[cfg(feature = "net")]
+                    set_offline(true);
+                },
+                Opt('R', Some(arg)) => {
+                    #[cfg(feature = "net")]
+                    set_reference(Some(&arg))?;
+                    #[cfg(not(feature = "net"))]
+                    return Err(format!(
+                        "Can't download templates from {}: this build has no network support",
+                        arg
+                    )
+                    .into());
+                },
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    let manifest = match &args[opts.index()..] {
+        [] => PathBuf::from(".gitignore.in"),
+        [manifest] => PathBuf::from(manifest),
+        _ => {
+            eprintln!("{}", usage_line(name));
+            return Ok(1);
+        },
+    };
+    let output = match output {
+        Some(output) => output,
+        None if manifest.extension().is_some_and(|ext| ext == "in") => manifest.with_extension(""),
+        None => {
+            eprintln!("{}: MANIFEST has no '.in' extension; use -o", name);
+            return Ok(1);
+        },
+    };
+
+    let repo = Repo::discover().ok();
+    let project = match repo.as_ref().and_then(Repo::workdir) {
+        Some(root) => root.to_path_buf(),
+        None => env::current_dir()?,
+    };
+    let value = |var: &str| {
+        if let Some(value) = vars.get(var) {
+            return Some(value.clone());
+        }
+        let key = format!("ignore.vars.{}", var.replace('_', "-"));
+        if let Some(value) = repo
+            .as_ref()
+            .and_then(|repo| repo.config(&key).ok().flatten())
+        {
+            return Some(value);
+        }
+        match var {
+            "project_name" => project
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+            _ => None,
+        }
+    };
+    let text = match unverified {
+        true => compile_allowing_unverified(&manifest, value)?,
+        false => compile(&manifest, value)?,
+    };
+    rewrite(&mut IgnoreFile::load(output)?, dry_run, |_| text)?;
+
+    Ok(0)
+}
@@ -0,0 +1,612 @@
+use super::{add, long_names, watch, Check, COMMANDS};
+use getopt::Opt;
+use git_ignore::{bytes, format::FORMATS, repo, template, Pattern, Repo};
+use std::{
+    collections::HashSet,
+    env,
+    error::Error,
+    io::{self, Write},
+    slice,
+};
+
+const SHELLS: &[&str] = &["bash", "zsh", "fish", "powershell"];
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] bash|zsh|fish|powershell", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -h       display this help");
+    println!();
+    println!("Prints a script completing the options and commands of git-ignore, and the");
+    println!("values they take, for the given shell; it works for 'git ignore' as well. The");
+    println!("patterns offered are those for the untracked files under the current directory,");
+    println!("and those of the embedded templates; the templates offered are all but those");
+    println!("only upstream, as 'template list' lists them. For example:");
+    println!();
+    println!("  bash:       source <(git-ignore completions bash)");
+    println!("  zsh:        git-ignore completions zsh > \"${{fpath[1]}}/_git-ignore\"");
+    println!("  fish:       git-ignore completions fish | source");
+    println!("  powershell: git-ignore completions powershell | Out-String | Invoke-Expression");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "h");
+
+    if let Some(Opt('h', None)) = opts.next().transpose()? {
+        print_usage(name);
+        return Ok(0);
+    }
+
+    let specs = specs();
+    match &args[opts.index()..] {
+        [shell] if shell == "bash" => bash(&specs),
+        [shell] if shell == "zsh" => zsh(&specs),
+        [shell] if shell == "fish" => fish(&specs),
+        [shell] if shell == "powershell" => powershell(&specs),
+        _ => {
+            eprintln!("{}", usage_line(name));
+            return Ok(1);
+        },
+    }
+
+    Ok(0)
+}
+
+// The hidden command the scripts run for the patterns to offer as arguments: those for the
+// untracked files under the current directory (as 'watch' suggests them), and the patterns of
+// the embedded templates matching any of those files or starting with the word being completed;
+// with -t, the names of the templates instead
+pub fn complete(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "t");
+    let mut templates = false;
+    while let Some(opt) = opts.next().transpose()? {
+        if let Opt('t', None) = opt {
+            templates = true;
+        }
+    }
+
+    let word = match &args[opts.index()..] {
+        [] => "",
+        [word] => word.as_str(),
+        _ => {
+            eprintln!("Usage: {} [-t] [WORD]", name);
+            return Ok(1);
+        },
+    };
+
+    if templates {
+        // Those of an unreachable registry that was never reached are left out
+        for template in template::list().unwrap_or_default() {
+            if template.to_lowercase().starts_with(&word.to_lowercase()) {
+                println!("{}", template);
+            }
+        }
+        return Ok(0);
+    }
+
+    // Outside a repository nothing is untracked, but the templates still are there
+    let untracked = untracked().unwrap_or_default();
+    let matches = |pattern: &Pattern| {
+        untracked
+            .iter()
+            .any(|path| pattern.matches(path.trim_end_matches('/'), path.ends_with('/')))
+    };
+
+    let mut patterns: Vec<String> = untracked
+        .iter()
+        .map(|path| watch::suggest(path.trim_end_matches('/'), path.ends_with('/')))
+        .collect();
+    for line in template::names()
+        .filter_map(template::embedded)
+        .flat_map(str::lines)
+    {
+        match Pattern::parse(line) {
+            Some(pattern) if pattern.is_negated() => {},
+            Some(pattern) if (!word.is_empty() && line.starts_with(word)) || matches(&pattern) => {
+                patterns.push(line.trim_end().to_string())
+            },
+            _ => {},
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for pattern in patterns {
+        if pattern.starts_with(word) && seen.insert(pattern.clone()) {
+            io::stdout().write_all(&bytes::encode(&format!("{}\n", pattern)))?;
+        }
+    }
+
+    Ok(0)
+}
+
+// The untracked files and wholly untracked directories (with a trailing '/') that git doesn't
+// ignore, under and relative to the current directory
+fn untracked() -> Result<Vec<String>, Box<dyn Error>> {
+    let repo = Repo::discover()?;
+    let root = match repo.workdir() {
+        Some(root) => repo::canonicalize(root)?,
+        None => return Ok(vec![]),
+    };
+    let cwd = repo::canonicalize(env::current_dir()?)?;
+    let dir = match cwd.strip_prefix(&root) {
+        Ok(dir) => bytes::from_os(dir.as_os_str()).replace('\\', "/"),
+        Err(_) => return Ok(vec![]),
+    };
+
+    if dir.is_empty() {
+        return repo.untracked(&[]);
+    }
+    Ok(repo
+        .untracked(slice::from_ref(&dir))?
+        .into_iter()
+        .filter_map(|path| Some(path.strip_prefix(&dir)?.strip_prefix('/')?.to_string()))
+        .collect())
+}
+
+// What the scripts know about a command; the one adding patterns has no name
+struct Spec {
+    command: &'static str,
+    summary: &'static str,
+    // Each option, whether it takes a value, and its long name
+    opts: Vec<(char, bool, Option<&'static str>)>,
+}
+
+fn specs() -> Vec<Spec> {
+    let spec = |command, opts: &str, summary| {
+        let longs = long_names(command);
+        Spec {
+            command,
+            summary,
+            opts: opts
+                .char_indices()
+                .filter(|&(_, c)| c != ':')
+                .map(|(i, c)| {
+                    let long = longs.iter().find(|&&(_, opt)| opt == c);
+                    (
+                        c,
+                        opts[i + 1..].starts_with(':'),
+                        long.map(|&(name, _)| name),
+                    )
+                })
+                .collect(),
+        }
+    };
+
+    let mut specs = vec![spec("", add::OPTS, "")];
+    specs.extend(
+        COMMANDS
+            .iter()
+            .map(|&(command, opts, summary)| spec(command, opts, summary)),
+    );
+    specs
+}
+
+// The values of an option; an option taking a value without any completes file names
+fn values(command: &str, opt: char) -> Vec<&'static str> {
+    match (command, opt) {
+        ("", 'F') => {
+            let mut checks = vec!["all"];
+            checks.extend(Check::ALL.iter().map(|check| check.name()));
+            checks
+        },
+        ("", 't') => vec!["any", "ripgrep", "fd"],
+        ("" | "dedupe", 'U') => vec!["nfc", "nfd", "none"],
+        ("export", 'F') => FORMATS.iter().map(|format| format.name()).collect(),
+        ("show", 'C') => vec!["always", "auto", "never"],
+        _ => vec![],
+    }
+}
+
+// The values of the first argument, and whether they are those of every argument rather than
+// just the first; arguments without any complete file names
+fn arguments(command: &str) -> (Vec<&'static str>, bool) {
+    match command {
+        "cache" => (vec!["info", "clear"], true),
+        "completions" => (SHELLS.to_vec(), true),
+        "derive" => (vec!["eslint", "prettier"], true),
+        "set" => (vec!["union", "intersect", "subtract"], false),
+        // The other arguments are templates, which `complete -t` lists
+        "template" => (vec!["add", "list", "update"], false),
+        _ => (vec![], false),
+    }
+}
+
+// The options, short and long
+fn flags(spec: &Spec, value: bool) -> Vec<String> {
+    let opts = spec.opts.iter().filter(|&&(_, takes, _)| takes || !value);
+    let mut flags: Vec<String> = opts
+        .clone()
+        .map(|(opt, _, _)| format!("-{}", opt))
+        .collect();
+    flags.extend(opts.filter_map(|(_, _, long)| long.map(|long| format!("--{}", long))));
+    flags
+}
+
+// The keys of an option's values in the scripts: 'COMMAND:-o', and 'COMMAND:--name' if it has
+// a long name
+fn keys(command: &str, opt: char, long: Option<&str>) -> Vec<String> {
+    let mut keys = vec![format!("{}:-{}", command, opt)];
+    keys.extend(long.map(|long| format!("{}:--{}", command, long)));
+    keys
+}
+
+fn names() -> Vec<&'static str> {
+    COMMANDS.iter().map(|&(command, _, _)| command).collect()
+}
+
+// Quote `s` for the shell, as 'S', where a quote is written as `quote`
+fn quote(s: &str, quote: &str) -> String {
+    format!("'{}'", s.replace('\'', quote))
+}
+
+fn bash(specs: &[Spec]) {
+    println!("# bash completion for git-ignore, also used by git's completion for 'git ignore'");
+    println!("_git_ignore() {{");
+    println!("    local cur=${{COMP_WORDS[COMP_CWORD]}} prev=${{COMP_WORDS[COMP_CWORD - 1]}}");
+    println!("    local i n=0 start=1 command= flags= takes= word= words= every= opt=");
+    println!();
+    println!("    for ((i = 0; i < COMP_CWORD; i++)); do");
+    println!("        case ${{COMP_WORDS[i]}} in");
+    println!("        git-ignore | */git-ignore | ignore)");
+    println!("            start=$((i + 1))");
+    println!("            break");
+    println!("            ;;");
+    println!("        esac");
+    println!("    done");
+    println!("    if ((COMP_CWORD > start)); then");
+    println!("        case ${{COMP_WORDS[start]}} in");
+    println!(
+        "        {}) command=${{COMP_WORDS[start]}} ;;",
+        names().join(" | ")
+    );
+    println!("        esac");
+    println!("    fi");
+    println!();
+    println!("    case $command in");
+    for spec in specs {
+        println!(
+            "    {}) flags='{}' takes='{}' ;;",
+            quote(spec.command, ""),
+            flags(spec, false).join(" "),
+            flags(spec, true).join(" ")
+        );
+    }
+    println!("    esac");
+    println!();
+    println!("    case $prev in");
+    println!("    --?*) [[ \" $takes \" == *\" $prev \"* ]] && opt=$prev ;;");
+    println!("    -?*) [[ \" $takes \" == *\" -${{prev: -1}} \"* ]] && opt=-${{prev: -1}} ;;");
+    println!("    esac");
+    println!("    if [[ -n $opt ]]; then");
+    println!("        case $command:$opt in");
+    for spec in specs {
+        for &(opt, _, long) in spec.opts.iter().filter(|&&(_, takes, _)| takes) {
+            let values = values(spec.command, opt);
+            if !values.is_empty() {
+                println!(
+                    "        {}) words='{}' ;;",
+                    keys(spec.command, opt, long).join(" | "),
+                    values.join(" ")
+                );
+            }
+        }
+    }
+    println!("        esac");
+    println!("        [[ -n $words ]] && COMPREPLY=($(compgen -W \"$words\" -- \"$cur\"))");
+    println!("        return");
+    println!("    fi");
+    println!("    if [[ $cur == -* ]]; then");
+    println!("        COMPREPLY=($(compgen -W \"$flags\" -- \"$cur\"))");
+    println!("        return");
+    println!("    fi");
+    println!();
+    println!("    # Count the arguments before the cursor, skipping options and their values");
+    println!("    i=$start");
+    println!("    [[ -n $command ]] && ((i++))");
+    println!("    for ((; i < COMP_CWORD; i++)); do");
+    println!("        case ${{COMP_WORDS[i]}} in");
+    println!("        --?*) [[ \" $takes \" == *\" ${{COMP_WORDS[i]}} \"* ]] && ((i++)) ;;");
+    println!("        -?*) [[ \" $takes \" == *\" -${{COMP_WORDS[i]: -1}} \"* ]] && ((i++)) ;;");
+    println!("        *) ((n++)) ;;");
+    println!("        esac");
+    println!("    done");
+    println!();
+    println!("    case $command in");
+    for spec in specs {
+        let (words, every) = arguments(spec.command);
+        if !words.is_empty() {
+            println!(
+                "    {}) words='{}' every={} ;;",
+                spec.command,
+                words.join(" "),
+                if every { "1" } else { "" }
+            );
+        }
+    }
+    println!("    esac");
+    println!("    if [[ -z $command ]]; then");
+    println!("        compopt -o filenames 2>/dev/null");
+    println!("        COMPREPLY=()");
+    println!(
+        "        ((n == 0)) && COMPREPLY=($(compgen -W '{}' -- \"$cur\"))",
+        names().join(" ")
+    );
+    println!("        # Read line by line, as the patterns must not be split or expanded");
+    println!("        while IFS= read -r word; do");
+    println!("            COMPREPLY+=(\"$word\")");
+    println!(
+        "        done < <(git-ignore __complete -- \"$cur\" 2>/dev/null; compgen -f -- \"$cur\")"
+    );
+    println!("    elif [[ -n $words ]] && ((n == 0 || every)); then");
+    println!("        COMPREPLY=($(compgen -W \"$words\" -- \"$cur\"))");
+    println!("    elif [[ $command == template ]]; then");
+    println!(
+        "        COMPREPLY=($(compgen -W \"$(git-ignore __complete -t 2>/dev/null)\" -- \"$cur\"))"
+    );
+    println!("    fi");
+    println!("}}");
+    println!();
+    println!("complete -o bashdefault -o default -F _git_ignore git-ignore");
+}
+
+fn zsh(specs: &[Spec]) {
+    let arguments = |spec: &Spec| {
+        let mut lines = vec![];
+        for &(opt, takes, long) in &spec.opts {
+            let values = values(spec.command, opt);
+            // '+' takes the value in the same word or the next, as '=' does for a long name
+            let value = match (takes, values.is_empty()) {
+                (false, _) => String::new(),
+                (true, true) => String::from(":file:_files"),
+                (true, false) => format!(":value:({})", values.join(" ")),
+            };
+            let (plus, equals) = if takes { ("+", "=") } else { ("", "") };
+            lines.push(format!("'*-{}{}{}'", opt, plus, value));
+            if let Some(long) = long {
+                lines.push(format!("'*--{}{}{}'", long, equals, value));
+            }
+        }
+        match arguments(spec.command) {
+            (words, _) if words.is_empty() => lines.push(String::from("'*:file:_files'")),
+            (words, true) => lines.push(format!("'*:argument:({})'", words.join(" "))),
+            (words, false) => {
+                lines.push(format!("'1:argument:({})'", words.join(" ")));
+                lines.push(String::from(if spec.command == "template" {
+                    "'*:template:{compadd -- ${(f)\"$(git-ignore __complete -t 2>/dev/null)\"}}'"
+                } else {
+                    "'*:file:_files'"
+                }));
+            },
+        }
+        lines
+    };
+
+    println!("#compdef git-ignore");
+    println!();
+    println!("_git-ignore() {{");
+    println!("    local context state state_descr line");
+    println!("    typeset -A opt_args");
+    println!("    local -a commands");
+    println!("    commands=(");
+    for spec in specs.iter().filter(|spec| !spec.command.is_empty()) {
+        println!(
+            "        {}",
+            quote(&format!("{}:{}", spec.command, spec.summary), "'\\''")
+        );
+    }
+    println!("    )");
+    println!();
+    println!("    if ((CURRENT > 2)) && ((${{commands[(I)${{(b)words[2]}}:*]}})); then");
+    println!("        local command=$words[2]");
+    println!("        shift words");
+    println!("        ((CURRENT--))");
+    println!("        case $command in");
+    for spec in specs.iter().filter(|spec| !spec.command.is_empty()) {
+        println!("        {})", spec.command);
+        println!("            _arguments -s : \\");
+        println!(
+            "                {}",
+            arguments(spec).join(" \\\n                ")
+        );
+        println!("            ;;");
+    }
+    println!("        esac");
+    println!("    else");
+    let mut lines = arguments(&specs[0]);
+    lines.pop();
+    lines.push(String::from("'1: :->command'"));
+    lines.push(String::from("'*: :->pattern'"));
+    println!("        _arguments -s : \\");
+    println!("            {}", lines.join(" \\\n            "));
+    println!("        if [[ -n $state ]]; then");
+    println!("            local -a patterns");
+    println!(
+        "            patterns=(${{(f)\"$(git-ignore __complete -- \"$PREFIX\" 2>/dev/null)\"}})"
+    );
+    println!("            [[ $state == command ]] && _describe command commands");
+    println!("            compadd -a patterns");
+    println!("            _files");
+    println!("        fi");
+    println!("    fi");
+    println!("}}");
+    println!();
+    println!("if [ \"$funcstack[1]\" = _git-ignore ]; then");
+    println!("    _git-ignore \"$@\"");
+    println!("else");
+    println!("    compdef _git-ignore git-ignore");
+    println!("fi");
+}
+
+fn fish(specs: &[Spec]) {
+    let names = names().join(" ");
+
+    println!("# fish completion for git-ignore");
+    for spec in specs.iter().filter(|spec| !spec.command.is_empty()) {
+        println!(
+            "complete -c git-ignore -n __fish_use_subcommand -a {} -d {}",
+            spec.command,
+            quote(spec.summary, "\\'")
+        );
+    }
+    println!(
+        "complete -c git-ignore -n 'not __fish_seen_subcommand_from {}' -a '(git-ignore __complete -- (commandline -ct) 2>/dev/null)'",
+        names
+    );
+    for spec in specs {
+        let condition = if spec.command.is_empty() {
+            format!("'not __fish_seen_subcommand_from {}'", names)
+        } else {
+            format!("'__fish_seen_subcommand_from {}'", spec.command)
+        };
+        for &(opt, takes, long) in &spec.opts {
+            let values = values(spec.command, opt);
+            let long = long.map_or(String::new(), |long| format!(" -l {}", long));
+            match (takes, values.is_empty()) {
+                (false, _) => {
+                    println!("complete -c git-ignore -n {} -s {}{}", condition, opt, long)
+                },
+                (true, true) => println!(
+                    "complete -c git-ignore -n {} -s {}{} -r",
+                    condition, opt, long
+                ),
+                (true, false) => println!(
+                    "complete -c git-ignore -n {} -s {}{} -x -a '{}'",
+                    condition,
+                    opt,
+                    long,
+                    values.join(" ")
+                ),
+            }
+        }
+        match arguments(spec.command) {
+            (words, _) if words.is_empty() => {},
+            (words, every) => println!(
+                "complete -c git-ignore -n {}{} -a '{}'",
+                condition,
+                if every { " -f" } else { "" },
+                words.join(" ")
+            ),
+        }
+    }
+    println!(
+        "complete -c git-ignore -n '__fish_seen_subcommand_from template; and __fish_seen_subcommand_from add update' -f -a '(git-ignore __complete -t 2>/dev/null)'"
+    );
+}
+
+fn powershell(specs: &[Spec]) {
+    let list = |words: Vec<String>| {
+        let words: Vec<String> = words.iter().map(|word| quote(word, "''")).collect();
+        format!("@({})", words.join(", "))
+    };
+    let strings = |words: Vec<&str>| list(words.into_iter().map(String::from).collect());
+
+    println!("# PowerShell completion for git-ignore");
+    println!("Register-ArgumentCompleter -Native -CommandName git-ignore, git-ignore.exe -ScriptBlock {{");
+    println!("    param($wordToComplete, $commandAst, $cursorPosition)");
+    println!();
+    println!("    $commands = [ordered]@{{");
+    for spec in specs.iter().filter(|spec| !spec.command.is_empty()) {
+        println!(
+            "        {} = {}",
+            quote(spec.command, "''"),
+            quote(spec.summary, "''")
+        );
+    }
+    println!("    }}");
+    println!("    $flags = @{{");
+    for spec in specs {
+        println!(
+            "        {} = {}",
+            quote(spec.command, "''"),
+            list(flags(spec, false))
+        );
+    }
+    println!("    }}");
+    println!("    $takes = @{{");
+    for spec in specs {
+        println!(
+            "        {} = {}",
+            quote(spec.command, "''"),
+            list(flags(spec, true))
+        );
+    }
+    println!("    }}");
+    println!("    $values = @{{");
+    for spec in specs {
+        for &(opt, _, long) in spec.opts.iter().filter(|&&(_, takes, _)| takes) {
+            let values = values(spec.command, opt);
+            if !values.is_empty() {
+                let values = strings(values);
+                for key in keys(spec.command, opt, long) {
+                    println!("        '{}' = {}", key, values);
+                }
+            }
+        }
+    }
+    println!("    }}");
+    println!("    $arguments = @{{");
+    for spec in specs {
+        let (words, _) = arguments(spec.command);
+        if !words.is_empty() {
+            println!("        {} = {}", quote(spec.command, "''"), strings(words));
+        }
+    }
+    println!("    }}");
+    let every: Vec<&str> = specs
+        .iter()
+        .filter(|spec| arguments(spec.command).1)
+        .map(|spec| spec.command)
+        .collect();
+    println!("    $every = {}", strings(every));
+    println!();
+    println!("    $words = @($commandAst.CommandElements | Select-Object -Skip 1 | ForEach-Object {{ \"$_\" }})");
+    println!("    if ($wordToComplete) {{");
+    println!("        $words = @($words | Select-Object -SkipLast 1)");
+    println!("    }}");
+    println!("    $command = ''");
+    println!("    if ($words.Count -gt 0 -and $commands.Contains($words[0])) {{");
+    println!("        $command = $words[0]");
+    println!("        $words = @($words | Select-Object -Skip 1)");
+    println!("    }}");
+    println!();
+    println!("    $prev = if ($words.Count -gt 0) {{ $words[-1] }} else {{ '' }}");
+    println!("    $opt = if ($prev -like '--?*') {{ $prev }} elseif ($prev -like '-?*') {{ \"-$($prev[-1])\" }}");
+    println!("    if ($opt -and $takes[$command] -contains $opt) {{");
+    println!("        $candidates = $values[\"${{command}}:$opt\"]");
+    println!("    }} elseif ($wordToComplete -like '-*') {{");
+    println!("        $candidates = $flags[$command]");
+    println!("    }} else {{");
+    println!("        # Count the arguments before the cursor, skipping options and their values");
+    println!("        $n = 0");
+    println!("        for ($i = 0; $i -lt $words.Count; $i++) {{");
+    println!("            if ($words[$i] -notlike '-?*') {{");
+    println!("                $n++");
+    println!("            }} elseif ($words[$i] -like '--?*') {{");
+    println!("                if ($takes[$command] -contains $words[$i]) {{");
+    println!("                    $i++");
+    println!("                }}");
+    println!("            }} elseif ($takes[$command] -contains \"-$($words[$i][-1])\") {{");
+    println!("                $i++");
+    println!("            }}");
+    println!("        }}");
+    println!("        if (-not $command) {{");
+    println!("            $patterns = @(git-ignore __complete -- $wordToComplete 2>$null)");
+    println!("            $candidates = if ($n -eq 0) {{ @($commands.Keys) + $patterns }} else {{ $patterns }}");
+    println!("        }} elseif ($n -eq 0 -or $every -contains $command) {{");
+    println!("            $candidates = $arguments[$command]");
+    println!("        }} elseif ($command -eq 'template') {{");
+    println!("            $candidates = @(git-ignore __complete -t 2>$null)");
+    println!("        }}");
+    println!("    }}");
+    println!();
+    println!("    # Without candidates, PowerShell completes file names");
+    println!(
+        "    $candidates | Where-Object {{ $_ -like \"$wordToComplete*\" }} | ForEach-Object {{"
+    );
+    println!("        $tip = if ($commands.Contains($_)) {{ $commands[$_] }} else {{ $_ }}");
+    println!("        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $tip)");
+    println!("    }}");
+    println!("}}");
+}
@@ -0,0 +1,70 @@
+use super::{rewrite, target_opt, TARGET_HELP, TARGET_OPTS};
+use getopt::Opt;
+use git_ignore::{dedupe, IgnoreFile, Kind, Normalization, Target};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("normalize", 'U')];
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] [-ginr] [-f FILE] [-U FORM]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("{}", TARGET_HELP);
+    println!("  -U FORM  rewrite the patterns in the Unicode normalization form FORM (nfc or");
+    println!("           nfd) first, so that differently encoded duplicates are found too");
+    println!();
+    println!("  -h       display this help");
+    println!();
+    println!("Removes the duplicate patterns of the target ('.gitignore' by default), whether");
+    println!("repeated exactly or written differently with the same meaning (such as 'foo' and");
+    println!("'**/foo'). The last occurrence of each is kept, since git uses the last matching");
+    println!("pattern; the order of the others, comments and blank lines are left alone.");
+    super::print_long_names("dedupe");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, &format!("hnU:{}", TARGET_OPTS));
+    let mut target = Target::configured(Kind::Ignore)?;
+    let mut normalize = Normalization::None;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('n', None) => dry_run = true,
+                Opt('U', Some(arg)) => normalize = arg.parse()?,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                opt if target_opt(&opt, &mut target) => {},
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let mut removed = vec![];
+    rewrite(&mut IgnoreFile::open(&target)?, dry_run, |text| {
+        let (text, lines) = dedupe(text, normalize);
+        removed = lines;
+        text
+    })?;
+    for line in &removed {
+        eprintln!("  {}", line);
+    }
+    if dry_run {
+        eprintln!("{} duplicate(s) would be removed", removed.len());
+    } else {
+        eprintln!("{} duplicate(s) removed", removed.len());
+    }
+
+    Ok(0)
+}
@@ -0,0 +1,73 @@
+use super::update;
+use getopt::Opt;
+use git_ignore::{bytes, derive, derive_rules, Error, IgnoreOp, Kind, Repo, Target};
+use std::env;
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] [-nr] TOOL...", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -r       use the repository root instead of the current directory");
+    println!("  -n       show what would be done without writing anything");
+    println!("  -h       display this help");
+    println!();
+    println!("Writes the rules of '.gitignore' that are relevant to each TOOL (eslint or");
+    println!("prettier) into its ignore file next to it, skipping rules for file extensions");
+    println!("the tool does not process. The rules go in a managed block, which is replaced");
+    println!("when run again; anything outside it is kept.");
+    super::print_long_names("derive");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "hnr");
+    let mut root = false;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('n', None) => dry_run = true,
+                Opt('r', None) => root = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    let tools = args[opts.index()..]
+        .iter()
+        .map(|tool| tool.parse())
+        .collect::<Result<Vec<derive::Tool>, _>>()?;
+    if tools.is_empty() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let dir = if root {
+        let repo = Repo::discover()?;
+        repo.workdir()
+            .map(|dir| dir.to_path_buf())
+            .ok_or_else(|| Error::BareRepo(repo.git_dir().to_path_buf()))?
+    } else {
+        env::current_dir()?
+    };
+    let gitignore = bytes::read(dir.join(Kind::Ignore.file_name()))?;
+
+    for tool in tools {
+        update(
+            IgnoreOp::new()
+                .target(Target::File(dir.join(tool.file_name())))
+                .add(derive_rules(&gitignore, tool))
+                .block(format!("derive {}", tool.name()))
+                .dry_run(dry_run),
+        )?;
+    }
+
+    Ok(0)
+}
@@ -0,0 +1,80 @@
+use super::effective_rules;
+use getopt::Opt;
+use git_ignore::{bytes, format, Repo};
+use std::{
+    fs,
+    io::{self, Write},
+};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("format", 'F'), ("output", 'o')];
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] [-F FORMAT] [-o FILE]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -F FORMAT  output format (rsync by default)");
+    println!("  -o FILE    write to FILE instead of standard output");
+    println!("  -h         display this help");
+    println!();
+    println!("Collects the effective ignore rules of the current repository (the global and");
+    println!("internal ignore files and every '.gitignore' in the working tree) and prints them");
+    println!("in another format, reporting any rules it cannot express. Formats:");
+    println!();
+    for format in format::FORMATS {
+        println!("  {:<12}  {}", format.name(), format.description());
+        for note in format.notes() {
+            println!("  {:<12}    - {}", "", note);
+        }
+    }
+    super::print_long_names("export");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "F:ho:");
+    let mut format = String::from("rsync");
+    let mut output = None;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('F', Some(arg)) => format = arg,
+                Opt('o', Some(arg)) => output = Some(arg),
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let rules = effective_rules(&Repo::discover()?)?;
+
+    let format = match format::find(&format) {
+        Some(format) => format,
+        None => {
+            eprintln!("{}: unknown format '{}'", name, format);
+            return Ok(1);
+        },
+    };
+    let (text, untranslated) = format.serialize(&rules)?;
+    for rule in &untranslated {
+        eprintln!("{}: cannot express '{}' for {}", name, rule, format.name());
+    }
+
+    match output {
+        Some(file) => fs::write(file, bytes::encode(&text))?,
+        None => io::stdout().write_all(&bytes::encode(&text))?,
+    }
+
+    Ok(if untranslated.is_empty() { 0 } else { 2 })
+}
@@ -0,0 +1,73 @@
+use getopt::Opt;
+use git_ignore::{bytes, export, sources, Level, Repo};
+use std::{
+    fs,
+    io::{self, Write},
+};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("output", 'o')];
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] [-o FILE]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -o FILE  write to FILE instead of standard output");
+    println!("  -h       display this help");
+    println!();
+    println!("Prints a single '.gitignore', for the root of the current repository, with the");
+    println!("effective rules of the global and internal ignore files and of every '.gitignore'");
+    println!("in the working tree, in order of precedence. Each file's rules are under a");
+    println!("'# From FILE' comment; other comments and blank lines are dropped, and the rules of");
+    println!("nested files are anchored below their directory.");
+    super::print_long_names("export-effective");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "ho:");
+    let mut output = None;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('o', Some(arg)) => output = Some(arg),
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let mut rules = vec![];
+    for source in sources(&Repo::discover()?)? {
+        let text = match bytes::read(&source.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let file = match source.level {
+            Level::Tree if source.base.is_empty() => String::from(".gitignore"),
+            Level::Tree => format!("{}/.gitignore", source.base),
+            Level::Global | Level::Internal => source.path.to_string_lossy().into_owned(),
+        };
+        rules.push((file, source.base, text));
+    }
+    let text = export::to_annotated_gitignore(&rules);
+
+    match output {
+        Some(file) => fs::write(file, bytes::encode(&text))?,
+        None => io::stdout().write_all(&bytes::encode(&text))?,
+    }
+
+    Ok(0)
+}
@@ -0,0 +1,83 @@
+use super::update;
+use getopt::Opt;
+use git_ignore::{bytes, flatten, sources, IgnoreOp, Level, MergeOptions, Repo, Target};
+use std::fs;
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("delete", 'D')];
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] [-Dn]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -D       delete the nested files afterwards");
+    println!("  -n       show what would be done without writing anything");
+    println!("  -h       display this help");
+    println!();
+    println!("Moves the rules of every nested '.gitignore' in the working tree into the root");
+    println!("one, anchored below their directory so that the same paths stay ignored. Each");
+    println!("file's rules are appended under a '# From DIR/.gitignore' comment.");
+    super::print_long_names("flatten");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "Dhn");
+    let mut delete = false;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('D', None) => delete = true,
+                Opt('n', None) => dry_run = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let nested: Vec<_> = sources(&Repo::discover()?)?
+        .into_iter()
+        .filter(|source| source.level == Level::Tree && !source.base.is_empty())
+        .collect();
+    let mut rules = vec![];
+    for source in &nested {
+        rules.push((source.base.clone(), bytes::read(&source.path)?));
+    }
+
+    update(
+        IgnoreOp::new()
+            .target(Target::Root)
+            .merge_file(flatten(&rules))
+            .options(MergeOptions {
+                sort: false,
+                ..MergeOptions::default()
+            })
+            .dry_run(dry_run),
+    )?;
+
+    if delete {
+        for source in &nested {
+            eprint!("Deleting {}... ", source.path.to_string_lossy());
+            if dry_run {
+                eprintln!("Skipped (dry run)");
+            } else {
+                fs::remove_file(&source.path)?;
+                eprintln!("Done!");
+            }
+        }
+    }
+
+    Ok(0)
+}
@@ -0,0 +1,78 @@
+use super::{rewrite, target_opt, TARGET_HELP, TARGET_OPTS};
+use getopt::Opt;
+use git_ignore::{
+    group, merge, resolve_conflicts, Comments, IgnoreFile, Kind, MergeOptions, Target,
+};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("group", 'c'), ("resolve", 'M')];
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] [-cginMr] [-f FILE]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("{}", TARGET_HELP);
+    println!();
+    println!("  -c       group the patterns into standard sections (OS, Editors, Build,");
+    println!("           Dependencies, Logs, Custom), dropping other comments");
+    println!("  -M       resolve merge conflicts by keeping the patterns of both sides");
+    println!("  -h       display this help");
+    println!();
+    println!("Tidies the target ('.gitignore' by default): patterns are trimmed, duplicates");
+    println!("removed, and each run of patterns between comments sorted, with negated patterns");
+    println!("last. Comments and blank lines are kept.");
+    super::print_long_names("fmt");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, &format!("chMn{}", TARGET_OPTS));
+    let mut target = Target::configured(Kind::Ignore)?;
+    let mut categorize = false;
+    let mut resolve = false;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('c', None) => categorize = true,
+                Opt('M', None) => resolve = true,
+                Opt('n', None) => dry_run = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                opt if target_opt(&opt, &mut target) => {},
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    rewrite(&mut IgnoreFile::open(&target)?, dry_run, |text| {
+        let resolved;
+        let text = if resolve {
+            resolved = resolve_conflicts(text);
+            &resolved
+        } else {
+            text
+        };
+        if categorize {
+            group(text)
+        } else {
+            let options = MergeOptions {
+                comments: Comments::Keep,
+                ..MergeOptions::default()
+            };
+            merge(text, &[] as &[&str], options)
+        }
+    })?;
+
+    Ok(0)
+}
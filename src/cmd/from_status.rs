@@ -0,0 +1,95 @@
+use super::{add::untracked_patterns, target_opt, update, TARGET_HELP, TARGET_OPTS};
+use getopt::Opt;
+use git_ignore::{IgnoreOp, Kind, Repo, Target};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("directories", 'd'), ("list", 'l'), ("min-count", 'm')];
+
+fn usage_line(program_name: &str) -> String {
+    format!(
+        "Usage: {} [-h] [-dginlr] [-f FILE] [-m COUNT]",
+        program_name
+    )
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("{}", TARGET_HELP);
+    println!("  -d       only consider untracked directories");
+    println!("  -m COUNT only use the patterns covering at least COUNT untracked files or");
+    println!("           directories");
+    println!("  -l       list the patterns, with how many untracked files or directories each");
+    println!("           covers, instead of adding them");
+    println!();
+    println!("  -h       display this help");
+    println!();
+    println!("Adds a pattern for each file or directory that git shows as untracked, but for");
+    println!("ignore files, below the directory of the target ('.gitignore' by default) to it.");
+    println!("Well-known clutter (such as 'node_modules/' or '*.log') is ignored wherever it");
+    println!("is, and anything else just where it is; a wholly untracked directory is ignored");
+    println!("as a whole.");
+    super::print_long_names("from-status");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, &format!("dhlm:n{}", TARGET_OPTS));
+    let mut target = Target::configured(Kind::Ignore)?;
+    let mut dirs_only = false;
+    let mut min_count = 1;
+    let mut list = false;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('d', None) => dirs_only = true,
+                Opt('l', None) => list = true,
+                Opt('m', Some(arg)) => min_count = arg.parse()?,
+                Opt('n', None) => dry_run = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                opt if target_opt(&opt, &mut target) => {},
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let mut paths = Repo::discover()?.untracked(&[])?;
+    if dirs_only {
+        paths.retain(|path| path.ends_with('/'));
+    }
+    // What is outside the directory of the file is no concern of it
+    let patterns: Vec<(String, usize)> = untracked_patterns(&target, Kind::Ignore, &paths)?
+        .patterns
+        .into_iter()
+        .filter(|&(_, count)| count >= min_count)
+        .collect();
+
+    if list {
+        for (pattern, count) in &patterns {
+            println!("{:>5}  {}", count, pattern);
+        }
+        return Ok(0);
+    }
+    if patterns.is_empty() {
+        eprintln!("Nothing to ignore");
+        return Ok(0);
+    }
+
+    update(
+        IgnoreOp::new()
+            .target(target)
+            .add(patterns.into_iter().map(|(pattern, _)| pattern))
+            .dry_run(dry_run),
+    )?;
+
+    Ok(0)
+}
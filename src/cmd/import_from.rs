@@ -0,0 +1,187 @@
+use super::{target_opt, update, TARGET_HELP, TARGET_OPTS};
+use getopt::Opt;
+use git_ignore::{
+    bytes, cleanup, flatten, sources, IgnoreOp, Kind, Level, MergeOptions, Repo, Target,
+};
+use std::{
+    env,
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("all", 'a')];
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] [-aginr] [-f FILE] REPO", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -a       also import the nested '.gitignore' files, anchored below their");
+    println!("           directory (see 'flatten')");
+    println!("{}", TARGET_HELP);
+    println!();
+    println!("  -h       display this help");
+    println!();
+    println!("Merges the root '.gitignore' of another repository into the target, as with");
+    println!("'merge-file'. REPO is either the path of a local working tree, whose files are");
+    println!("read as they are, or a URL that git can clone, whose files are read from HEAD.");
+    super::print_long_names("import-from");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, &format!("ahn{}", TARGET_OPTS));
+    let mut target = Target::configured(Kind::Ignore)?;
+    let mut all = false;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('a', None) => all = true,
+                Opt('n', None) => dry_run = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                opt if target_opt(&opt, &mut target) => {},
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    let repo = match &args[opts.index()..] {
+        [repo] => repo,
+        _ => {
+            eprintln!("{}", usage_line(name));
+            return Ok(1);
+        },
+    };
+
+    let files = if Path::new(repo).is_dir() {
+        local_files(Path::new(repo), all)?
+    } else {
+        remote_files(repo, all)?
+    };
+
+    let mut text = String::new();
+    let mut nested = vec![];
+    for (base, rules) in files {
+        if base.is_empty() {
+            text = rules;
+        } else {
+            nested.push((base, rules));
+        }
+    }
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(&flatten(&nested));
+
+    // The order of the nested rules decides which one wins
+    let options = MergeOptions {
+        sort: nested.is_empty(),
+        ..MergeOptions::default()
+    };
+    update(
+        IgnoreOp::new()
+            .target(target)
+            .merge_file(text)
+            .options(options)
+            .dry_run(dry_run),
+    )?;
+
+    Ok(0)
+}
+
+// The `(base, text)` of the ignore files of a local working tree
+fn local_files(dir: &Path, all: bool) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut files = vec![];
+
+    for source in sources(&Repo::discover_from(dir)?)? {
+        if source.level == Level::Tree && (all || source.base.is_empty()) {
+            files.push((source.base, bytes::read(&source.path)?));
+        }
+    }
+
+    Ok(files)
+}
+
+// The `(base, text)` of the ignore files at HEAD of a remote repository, fetched into a
+// temporary shallow clone without a working tree
+fn remote_files(url: &str, all: bool) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let clone = TempDir::new(env::temp_dir().join(format!("git-ignore-{}", process::id())));
+    git(
+        None,
+        &[
+            "clone",
+            "--quiet",
+            "--depth=1",
+            "--no-checkout",
+            url,
+            &clone.0.to_string_lossy(),
+        ],
+    )?;
+
+    let mut files = vec![];
+    for path in git(
+        Some(&clone.0),
+        &["ls-tree", "-r", "-z", "--name-only", "HEAD"],
+    )?
+    .split('\0')
+    {
+        let base = match path.strip_suffix(".gitignore") {
+            Some("") => "",
+            Some(dir) if all => match dir.strip_suffix('/') {
+                Some(base) => base,
+                None => continue,
+            },
+            _ => continue,
+        };
+        let text = git(Some(&clone.0), &["show", &format!("HEAD:{}", path)])?;
+        files.push((base.to_string(), text));
+    }
+    // Parents come before their children
+    files.sort();
+
+    Ok(files)
+}
+
+fn git(dir: Option<&Path>, args: &[&str]) -> Result<String, Box<dyn Error>> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+
+    let output = command.args(args).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr)
+                .trim_end()
+                .to_string(),
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(path: PathBuf) -> Self {
+        cleanup::register(&path);
+        Self(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+        cleanup::unregister(&self.0);
+    }
+}
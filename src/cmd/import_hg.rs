@@ -0,0 +1,67 @@
+use super::{target_opt, update, TARGET_HELP, TARGET_OPTS};
+use getopt::Opt;
+use git_ignore::{bytes, import, IgnoreOp, Kind, Target};
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] [-ginr] [-f FILE] [HGIGNORE]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("{}", TARGET_HELP);
+    println!();
+    println!("  -h       display this help");
+    println!();
+    println!("Converts the rules of HGIGNORE (by default, '.hgignore' in the current directory)");
+    println!("into gitignore patterns and adds them to the target ('.gitignore' by default).");
+    println!("Rules that cannot be expressed as gitignore patterns are reported.");
+    super::print_long_names("import-hg");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, &format!("hn{}", TARGET_OPTS));
+    let mut target = Target::configured(Kind::Ignore)?;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('n', None) => dry_run = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                opt if target_opt(&opt, &mut target) => {},
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    let file = match &args[opts.index()..] {
+        [] => ".hgignore",
+        [file] => file,
+        _ => {
+            eprintln!("{}", usage_line(name));
+            return Ok(1);
+        },
+    };
+
+    let conversion = import::from_hgignore(&bytes::read(file)?);
+    for (line, rule) in &conversion.untranslated {
+        eprintln!("{}:{}: cannot translate '{}'", file, line, rule);
+    }
+
+    update(
+        IgnoreOp::new()
+            .target(target)
+            .add(conversion.patterns)
+            .dry_run(dry_run),
+    )?;
+
+    Ok(if conversion.untranslated.is_empty() {
+        0
+    } else {
+        2
+    })
+}
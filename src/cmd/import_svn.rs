@@ -0,0 +1,111 @@
+use super::{read_input, target_opt, update, TARGET_HELP, TARGET_OPTS};
+use getopt::Opt;
+use git_ignore::{import, IgnoreOp, Kind, Target};
+use std::{error::Error, process::Command};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("global-ignores", 'G'), ("ignores", 'I')];
+
+fn usage_line(program_name: &str) -> String {
+    format!(
+        "Usage: {} [-h] [-ginr] [-f FILE] [-I INPUT] [-G INPUT]",
+        program_name
+    )
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -I INPUT read 'svn propget -R svn:ignore' output from INPUT ('-' for stdin)");
+    println!("  -G INPUT read 'svn propget -R svn:global-ignores' output from INPUT");
+    println!("{}", TARGET_HELP);
+    println!();
+    println!("  -h       display this help");
+    println!();
+    println!("Converts Subversion ignore properties into anchored gitignore patterns and adds");
+    println!("them to the target ('.gitignore' by default). Without -I or -G, 'svn propget' is");
+    println!("run in the current directory for both properties.");
+    super::print_long_names("import-svn");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, &format!("G:hI:n{}", TARGET_OPTS));
+    let mut target = Target::configured(Kind::Ignore)?;
+    let mut ignore = None;
+    let mut global_ignores = None;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('G', Some(arg)) => global_ignores = Some(arg),
+                Opt('I', Some(arg)) => ignore = Some(arg),
+                Opt('n', None) => dry_run = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                opt if target_opt(&opt, &mut target) => {},
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let inputs = if ignore.is_none() && global_ignores.is_none() {
+        vec![
+            (propget("svn:ignore")?, false),
+            // Older servers and clients do not know about inherited properties
+            (propget("svn:global-ignores").unwrap_or_default(), true),
+        ]
+    } else {
+        let mut inputs = vec![];
+        if let Some(input) = ignore {
+            inputs.push((read_input(&input)?, false));
+        }
+        if let Some(input) = global_ignores {
+            inputs.push((read_input(&input)?, true));
+        }
+        inputs
+    };
+
+    let mut patterns = vec![];
+    for (output, inherited) in inputs {
+        let conversion = import::from_svn(&output, inherited);
+        for (line, rule) in &conversion.untranslated {
+            eprintln!("line {}: cannot translate '{}'", line, rule);
+        }
+        patterns.extend(conversion.patterns);
+    }
+
+    update(
+        IgnoreOp::new()
+            .target(target)
+            .add(patterns)
+            .dry_run(dry_run),
+    )?;
+
+    Ok(0)
+}
+
+fn propget(property: &str) -> Result<String, Box<dyn Error>> {
+    let output = Command::new("svn")
+        .args(["propget", "-R", property, "."])
+        .output()
+        .map_err(|e| format!("Unable to run svn: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "svn propget {} failed: {}",
+            property,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
@@ -0,0 +1,127 @@
+use super::plugin::is_executable;
+use getopt::Opt;
+use git_ignore::repo;
+use std::{
+    env,
+    error::Error,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("write", 'w')];
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-hnw]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -w       if git cannot find git-ignore, set 'alias.ignore' in the global config");
+    println!("           to run this executable");
+    println!("  -n       show what would be done without writing anything");
+    println!();
+    println!("  -h       display this help");
+    println!();
+    println!("Checks that 'git ignore' works: git runs the alias 'alias.ignore' if it is set,");
+    println!("and otherwise looks for git-ignore on the PATH and in its exec path");
+    println!("('git --exec-path'). Exits with 1 if it would find neither and nothing is set.");
+    super::print_long_names("install-alias");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "hnw");
+    let mut write = false;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('w', None) => write = true,
+                Opt('n', None) => dry_run = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let exe = env::current_exe()?;
+
+    if let Some(alias) = repo::config("alias.ignore")? {
+        println!("'git ignore' runs the alias 'alias.ignore': {}", alias);
+        return Ok(0);
+    }
+    if let Some(path) = find()? {
+        if same_file(&path, &exe) {
+            println!("'git ignore' runs {}", path.to_string_lossy());
+        } else {
+            println!(
+                "'git ignore' runs {} (not this executable, {})",
+                path.to_string_lossy(),
+                exe.to_string_lossy()
+            );
+        }
+        return Ok(0);
+    }
+
+    if !write {
+        eprintln!("'git ignore' does not work: git-ignore is neither on the PATH nor in git's");
+        eprintln!("exec path, and 'alias.ignore' is not set (use -w to set it)");
+        return Ok(1);
+    }
+
+    // Git runs the alias with the shell, where a single-quoted path is taken as it is
+    let alias = format!("!'{}'", exe.to_string_lossy().replace('\'', "'\\''"));
+    eprint!("Setting alias.ignore to {}... ", alias);
+    if dry_run {
+        eprintln!("Skipped (dry run)");
+        return Ok(0);
+    }
+    let status = Command::new("git")
+        .args(["config", "--global", "alias.ignore"])
+        .arg(&alias)
+        .status()?;
+    if !status.success() {
+        return Err(format!("git config failed ({})", status).into());
+    }
+    eprintln!("Done!");
+
+    Ok(0)
+}
+
+// Where git looks for the git-ignore program: its exec path, then the PATH
+fn find() -> Result<Option<PathBuf>, Box<dyn Error>> {
+    let output = Command::new("git").arg("--exec-path").output()?;
+    if !output.status.success() {
+        return Err(format!("git --exec-path failed ({})", output.status).into());
+    }
+
+    let mut dirs = vec![PathBuf::from(
+        String::from_utf8_lossy(&output.stdout).trim_end(),
+    )];
+    if let Some(path) = env::var_os("PATH") {
+        dirs.extend(env::split_paths(&path));
+    }
+
+    let file = format!("git-ignore{}", env::consts::EXE_SUFFIX);
+    Ok(dirs
+        .into_iter()
+        .map(|dir| dir.join(&file))
+        .find(|path| is_executable(path)))
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (repo::canonicalize(a), repo::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
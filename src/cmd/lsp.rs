@@ -0,0 +1,422 @@
+use getopt::Opt;
+use git_ignore::{
+    case_collisions, dedupe, explain, lint, merge, walk::walk, Comments, Index, MergeOptions,
+    Normalization, Pattern, Repo,
+};
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+// How many matching paths a hover lists, and how many entries it looks at to find them
+const HOVER_MATCHES: usize = 20;
+const HOVER_ENTRIES: usize = 10_000;
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -h       display this help");
+    println!();
+    println!("Runs a language server for ignore files on standard input and output, for");
+    println!("editors speaking the Language Server Protocol. It reports the problems the");
+    println!("lines of a file have (such as redundant patterns or unescaped trailing spaces),");
+    println!("explains a pattern and lists what it matches on hover, and offers to remove");
+    println!("duplicates or sort the patterns.");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "h");
+
+    if let Some(Opt('h', None)) = opts.next().transpose()? {
+        print_usage(name);
+        return Ok(0);
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut server = Server {
+        output: io::stdout(),
+        documents: HashMap::new(),
+        shutdown: false,
+    };
+
+    while let Some(message) = receive(&mut input)? {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+
+        match method {
+            "exit" => return Ok(if server.shutdown { 0 } else { 1 }),
+            "textDocument/didOpen" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                server.open(uri, text.to_string())?;
+            },
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let changes = params["contentChanges"].as_array();
+                // Only whole documents are synchronized
+                if let Some(text) = changes
+                    .and_then(|c| c.last())
+                    .and_then(|c| c["text"].as_str())
+                {
+                    server.open(uri, text.to_string())?;
+                }
+            },
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                server.documents.remove(uri);
+                server.notify(
+                    "textDocument/publishDiagnostics",
+                    json!({"uri": uri, "diagnostics": []}),
+                )?;
+            },
+            // Requests need an answer; other notifications are of no interest
+            _ => {
+                if let Some(id) = message.get("id") {
+                    let result = match method {
+                        "initialize" => Ok(json!({
+                            "capabilities": {
+                                "textDocumentSync": 1,
+                                "hoverProvider": true,
+                                "codeActionProvider": true,
+                            },
+                            "serverInfo": {
+                                "name": "git-ignore",
+                                "version": env!("CARGO_PKG_VERSION"),
+                            },
+                        })),
+                        "shutdown" => {
+                            server.shutdown = true;
+                            Ok(Value::Null)
+                        },
+                        "textDocument/hover" => Ok(server.hover(params)),
+                        "textDocument/codeAction" => Ok(server.code_actions(params)),
+                        _ => Err(format!("Unknown method '{}'", method)),
+                    };
+                    server.respond(id, result)?;
+                }
+            },
+        }
+    }
+
+    Ok(1)
+}
+
+struct Server {
+    output: io::Stdout,
+    documents: HashMap<String, String>,
+    shutdown: bool,
+}
+
+impl Server {
+    fn open(&mut self, uri: &str, text: String) -> io::Result<()> {
+        let mut lints = lint(&text);
+        if file_path(uri).is_some_and(|path| ignores_case(&path)) {
+            lints.extend(case_collisions(&text));
+            lints.sort_by_key(|lint| lint.line);
+        }
+        let diagnostics: Vec<Value> = lints
+            .into_iter()
+            .map(|lint| {
+                let line = text.lines().nth(lint.line).unwrap_or_default();
+                json!({
+                    "range": {
+                        "start": {"line": lint.line, "character": 0},
+                        "end": {"line": lint.line, "character": width(line)},
+                    },
+                    "severity": 2,
+                    "source": "git-ignore",
+                    "message": lint.message,
+                })
+            })
+            .collect();
+        self.documents.insert(uri.to_string(), text);
+
+        self.notify(
+            "textDocument/publishDiagnostics",
+            json!({"uri": uri, "diagnostics": diagnostics}),
+        )
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let line = params["position"]["line"].as_u64().unwrap_or_default() as usize;
+        let line = match self.documents.get(uri).and_then(|t| t.lines().nth(line)) {
+            Some(line) => line,
+            None => return Value::Null,
+        };
+        let mut text = match explain(line) {
+            Some(text) => format!("{}.", text),
+            None => return Value::Null,
+        };
+
+        if let (Some(pattern), Some(base)) = (Pattern::parse(line), file_path(uri).and_then(base)) {
+            let (matches, more) = matching(&base, &pattern);
+            if matches.is_empty() {
+                text.push_str("\n\nNothing matches it yet.");
+            } else {
+                text.push_str("\n\nMatches:\n");
+                for path in matches {
+                    text.push_str(&format!("\n- `{}`", path));
+                }
+                if more {
+                    text.push_str("\n- ...");
+                }
+            }
+        }
+
+        json!({"contents": {"kind": "markdown", "value": text}})
+    }
+
+    fn code_actions(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let text = match self.documents.get(uri) {
+            Some(text) => text,
+            None => return json!([]),
+        };
+
+        let (deduped, removed) = dedupe(text, Normalization::None);
+        let options = MergeOptions {
+            comments: Comments::Keep,
+            ..MergeOptions::default()
+        };
+        let sorted = merge(text, &[] as &[&str], options);
+
+        let mut actions = vec![];
+        if !removed.is_empty() {
+            actions.push(edit(uri, text, "Remove duplicate patterns", deduped));
+        }
+        if sorted != *text {
+            actions.push(edit(uri, text, "Sort patterns", sorted));
+        }
+        Value::Array(actions)
+    }
+
+    fn respond(&mut self, id: &Value, result: Result<Value, String>) -> io::Result<()> {
+        let message = match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err(error) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": -32601, "message": error},
+            }),
+        };
+        send(&mut self.output, &message)
+    }
+
+    fn notify(&mut self, method: &str, params: Value) -> io::Result<()> {
+        let message = json!({"jsonrpc": "2.0", "method": method, "params": params});
+        send(&mut self.output, &message)
+    }
+}
+
+// A code action replacing the whole document `text` with `new`
+fn edit(uri: &str, text: &str, title: &str, new: String) -> Value {
+    let lines = text.split('\n').count() - 1;
+    let last = text.rsplit('\n').next().unwrap_or_default();
+
+    json!({
+        "title": title,
+        "kind": "source",
+        "edit": {
+            "changes": {
+                uri: [{
+                    "range": {
+                        "start": {"line": 0, "character": 0},
+                        "end": {"line": lines, "character": width(last)},
+                    },
+                    "newText": new,
+                }],
+            },
+        },
+    })
+}
+
+// Positions count UTF-16 code units
+fn width(line: &str) -> usize {
+    line.encode_utf16().count()
+}
+
+// Whether git ignores case in the repository of the file at `path`
+fn ignores_case(path: &Path) -> bool {
+    let repo = path.parent().and_then(|dir| Repo::discover_from(dir).ok());
+    repo.is_some_and(|repo| repo.config_bool("core.ignoreCase").unwrap_or(false))
+}
+
+// The directory the patterns of the ignore file at `path` are relative to
+fn base(path: PathBuf) -> Option<PathBuf> {
+    let dir = path.parent()?;
+    if path.ends_with("info/exclude") {
+        let repo = Repo::discover_from(dir).ok()?;
+        return repo.workdir().map(Path::to_path_buf);
+    }
+    Some(dir.to_path_buf())
+}
+
+// Up to `HOVER_MATCHES` paths below `base` that `pattern` matches, and whether there are more;
+// nothing below a matching directory is listed, nor anything in '.git'
+fn matching(base: &Path, pattern: &Pattern) -> (Vec<String>, bool) {
+    let (mut matches, more) = match indexed(base) {
+        Some(entries) => {
+            let mut matches = vec![];
+            let mut dirs = HashSet::new();
+            let mut looked = 0;
+            for (path, is_dir) in entries {
+                // Parents come first, so a matching one is already known
+                let mut parents = path.match_indices('/').map(|(i, _)| &path[..i]);
+                if parents.any(|parent| dirs.contains(parent)) {
+                    continue;
+                }
+                looked += 1;
+                if looked > HOVER_ENTRIES {
+                    break;
+                }
+                if pattern.matches(&path, is_dir) {
+                    matches.push(if is_dir {
+                        format!("{}/", path)
+                    } else {
+                        path.clone()
+                    });
+                    if is_dir {
+                        dirs.insert(path);
+                    }
+                }
+            }
+            (matches, looked > HOVER_ENTRIES)
+        },
+        None => walked(base, pattern),
+    };
+
+    matches.sort();
+    let more = more || matches.len() > HOVER_MATCHES;
+    matches.truncate(HOVER_MATCHES);
+    (matches, more)
+}
+
+// The entries below `base` from the index of its repository, relative to `base`
+fn indexed(base: &Path) -> Option<Vec<(String, bool)>> {
+    let repo = Repo::discover_from(base).ok()?;
+    let root = git_ignore::repo::canonicalize(repo.workdir()?).ok()?;
+    let dir = git_ignore::repo::canonicalize(base).ok()?;
+    let dir = dir
+        .strip_prefix(root)
+        .ok()?
+        .to_string_lossy()
+        .replace('\\', "/");
+    let index = Index::load(&repo).ok()?;
+    Some(index.entries(&dir).collect())
+}
+
+// The paths below `base` outside of a repository that `pattern` matches, walking the tree, and
+// whether there might be more
+fn walked(base: &Path, pattern: &Pattern) -> (Vec<String>, bool) {
+    let entries = AtomicUsize::new(0);
+    let found = walk(base, |dir, listing| {
+        // Past the limit, nothing more is looked at
+        if entries.fetch_add(listing.len(), Ordering::Relaxed) > HOVER_ENTRIES {
+            return (None, vec![]);
+        }
+        let prefix = match dir.strip_prefix(base) {
+            Ok(dir) if dir.as_os_str().is_empty() => String::new(),
+            Ok(dir) => format!("{}/", dir.to_string_lossy().replace('\\', "/")),
+            Err(_) => return (None, vec![]),
+        };
+
+        let mut matches = vec![];
+        let mut subdirs = vec![];
+        for entry in listing {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name == ".git" {
+                continue;
+            }
+            let path = format!("{}{}", prefix, name);
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+
+            if pattern.matches(&path, is_dir) {
+                matches.push(if is_dir { format!("{}/", path) } else { path });
+            } else if is_dir {
+                subdirs.push(entry.path());
+            }
+        }
+        (Some(matches), subdirs)
+    });
+
+    let matches = match found {
+        Ok(found) => found.into_iter().flat_map(|(_, matches)| matches).collect(),
+        Err(_) => vec![],
+    };
+    (matches, entries.into_inner() > HOVER_ENTRIES)
+}
+
+// The local path of a 'file:' URI
+fn file_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let path = path.strip_prefix("localhost").unwrap_or(path);
+
+    let mut bytes = vec![];
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        match (byte, tail) {
+            (b'%', [high, low, tail @ ..]) => {
+                let hex = std::str::from_utf8(&[*high, *low]).ok()?.to_string();
+                bytes.push(u8::from_str_radix(&hex, 16).ok()?);
+                rest = tail;
+            },
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            },
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+
+    // On Windows, 'file:///C:/dir' is 'C:/dir'
+    if cfg!(windows) && path.as_bytes().get(2) == Some(&b':') {
+        return Some(PathBuf::from(&path[1..]));
+    }
+    Some(PathBuf::from(path))
+}
+
+// The next message, or `None` at the end of the input
+fn receive(input: &mut impl BufRead) -> Result<Option<Value>, Box<dyn Error>> {
+    let mut length = None;
+
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = Some(value.trim().parse::<usize>()?);
+            }
+        }
+    }
+
+    let length = length.ok_or("Message without a Content-Length header")?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+fn send(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
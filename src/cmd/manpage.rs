@@ -0,0 +1,181 @@
+use super::COMMANDS;
+use getopt::Opt;
+use std::{env, error::Error, process::Command};
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -h       display this help");
+    println!();
+    println!("Prints the manual page of git-ignore, in roff, made from the help of the program");
+    println!("and of each command, so the two always agree. Installed as 'git-ignore.1' in a");
+    println!("'man1' directory of the MANPATH, it is also what 'git ignore --help' shows:");
+    println!();
+    println!("  git-ignore manpage > /usr/local/share/man/man1/git-ignore.1");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "h");
+
+    if let Some(Opt('h', None)) = opts.next().transpose()? {
+        print_usage(name);
+        return Ok(0);
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let help = help(&[])?;
+    let mut page = format!(
+        ".TH GIT-IGNORE 1 \"\" \"git-ignore {}\" \"Git Manual\"\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    page.push_str(".SH NAME\n");
+    page.push_str(&format!(
+        "git-ignore \\- {}\n",
+        escape(&env!("CARGO_PKG_DESCRIPTION").to_lowercase())
+    ));
+
+    page.push_str(".SH SYNOPSIS\n.nf\n");
+    page.push_str(&synopsis(&help));
+    for &(command, _, _) in COMMANDS {
+        page.push_str(&synopsis(&help_of(command)?));
+    }
+    page.push_str(".fi\n");
+
+    // The list of commands is replaced by a section describing each one
+    page.push_str(".SH DESCRIPTION\n");
+    let mut skipping = false;
+    let description: Vec<&str> = help
+        .lines()
+        .filter(|line| {
+            skipping = if line.starts_with("Commands (") {
+                true
+            } else {
+                skipping && !line.is_empty()
+            };
+            !skipping
+        })
+        .collect();
+    page.push_str(&body(&description.join("\n")));
+
+    page.push_str(".SH COMMANDS\n");
+    for &(command, _, summary) in COMMANDS {
+        page.push_str(&format!(".SS {}\n{}\n", command, escape(summary)));
+        let help = help_of(command)?;
+        page.push_str(&format!(".PP\n.nf\n{}.fi\n", synopsis(&help)));
+        page.push_str(&body(&help));
+    }
+
+    print!("{}", page);
+
+    Ok(0)
+}
+
+fn help_of(command: &str) -> Result<String, Box<dyn Error>> {
+    help(&[command])
+}
+
+// The help of the command given by `args`, as printed by this very program
+fn help(args: &[&str]) -> Result<String, Box<dyn Error>> {
+    let output = Command::new(env::current_exe()?)
+        .args(args)
+        .arg("-h")
+        .output()?;
+    if !output.status.success() {
+        return Err(format!("Unable to get the help of '{}'", args.join(" ")).into());
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+// The usage line of `help`, with the program name in bold
+fn synopsis(help: &str) -> String {
+    let usage = help.lines().next().unwrap_or_default();
+    let usage = usage.strip_prefix("Usage: ").unwrap_or(usage);
+    let (command, args) = match usage.find(" [").or_else(|| usage.find(" -")) {
+        Some(at) => usage.split_at(at),
+        None => (usage, ""),
+    };
+
+    format!("\\fB{}\\fR{}\n", escape(command), escape(args))
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Block {
+    None,
+    Text,
+    Option,
+    Example,
+}
+
+// The rest of `help` after the usage line: options become tagged paragraphs, indented lines
+// examples, and other lines running text
+fn body(help: &str) -> String {
+    let mut roff = String::new();
+    let mut block = Block::None;
+
+    for line in help.lines().skip(1) {
+        let indent = line.len() - line.trim_start().len();
+
+        if line.is_empty() {
+            if block == Block::Example {
+                roff.push_str(".fi\n");
+            }
+            block = Block::None;
+        } else if line.starts_with("  -") {
+            if block == Block::Example {
+                roff.push_str(".fi\n");
+            }
+            let line = &line[2..];
+            let (term, description) = line.split_once("  ").unwrap_or((line, ""));
+            let (flag, arg) = term.split_once(' ').unwrap_or((term, ""));
+            roff.push_str(&format!(".TP\n\\fB{}\\fR", escape(flag)));
+            if !arg.is_empty() {
+                roff.push_str(&format!(" \\fI{}\\fR", escape(arg)));
+            }
+            roff.push('\n');
+            if !description.trim().is_empty() {
+                roff.push_str(&format!("{}\n", escape(description.trim())));
+            }
+            block = Block::Option;
+        } else if block == Block::Option && indent > 2 {
+            // Lines indented past the option descriptions are lists within them
+            if indent > 11 {
+                roff.push_str(".br\n");
+            }
+            roff.push_str(&format!("{}\n", escape(line.trim())));
+        } else if indent > 0 {
+            if block != Block::Example {
+                roff.push_str(".PP\n.nf\n");
+            }
+            roff.push_str(&format!("{}\n", escape(&line[2.min(indent)..])));
+            block = Block::Example;
+        } else {
+            if block != Block::Text {
+                roff.push_str(".PP\n");
+            }
+            roff.push_str(&format!("{}\n", escape(line)));
+            block = Block::Text;
+        }
+    }
+    if block == Block::Example {
+        roff.push_str(".fi\n");
+    }
+
+    roff
+}
+
+fn escape(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+    if text.starts_with('.') || text.starts_with('\'') {
+        format!("\\&{}", text)
+    } else {
+        text
+    }
+}
@@ -0,0 +1,60 @@
+use super::{permute, read_input, target_opt, update, TARGET_HELP, TARGET_OPTS};
+use getopt::Opt;
+use git_ignore::{IgnoreOp, Kind, Target};
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] [-ginr] [-f FILE] SOURCE", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("{}", TARGET_HELP);
+    println!();
+    println!("  -h       display this help");
+    println!();
+    println!("Merges every pattern of the ignore file SOURCE ('-' for standard input) into the");
+    println!("target ('.gitignore' by default), keeping the comments of both: patterns go under");
+    println!("the same comment block in the target if it has one, and the rest of SOURCE is");
+    println!("appended section by section. Options may follow SOURCE.");
+    super::print_long_names("merge-file");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let optstring = format!("hn{}", TARGET_OPTS);
+    let args = permute(args, &optstring);
+    let mut opts = getopt::Parser::new(&args, &optstring);
+    let mut target = Target::configured(Kind::Ignore)?;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('n', None) => dry_run = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                opt if target_opt(&opt, &mut target) => {},
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    let source = match &args[opts.index()..] {
+        [source] => source,
+        _ => {
+            eprintln!("{}", usage_line(name));
+            return Ok(1);
+        },
+    };
+
+    update(
+        IgnoreOp::new()
+            .target(target)
+            .merge_file(read_input(source)?)
+            .dry_run(dry_run),
+    )?;
+
+    Ok(0)
+}
@@ -0,0 +1,74 @@
+use git_ignore::{bytes, Kind, Repo, Target};
+use std::{
+    env,
+    ffi::OsString,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+// Plugin names are restricted so that ordinary patterns never trigger a PATH search
+pub fn find(command: &str) -> Option<PathBuf> {
+    if command.is_empty()
+        || command.starts_with('-')
+        || !command
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return None;
+    }
+
+    let file = format!("git-ignore-{}{}", command, env::consts::EXE_SUFFIX);
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(&file))
+        .find(|path| is_executable(path))
+}
+
+#[cfg(unix)]
+pub fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+pub fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+// Plugins get the resolved context in the environment:
+//   GIT_IGNORE_FILE     the default target (from 'ignore.target', or '.gitignore' in the current
+//                       directory)
+//   GIT_IGNORE_GIT_DIR  the repository's git directory, if any
+//   GIT_IGNORE_ROOT     the repository's working tree root, if any
+pub fn run(plugin: &Path, args: &[String]) -> program::Result {
+    let mut command = Command::new(plugin);
+    command.args(args.iter().map(|arg| bytes::to_os(arg)));
+
+    let mut context: Vec<(&str, OsString)> = vec![];
+    if let Ok(path) = Target::configured(Kind::Ignore).and_then(|target| target.path()) {
+        context.push(("GIT_IGNORE_FILE", path.into()));
+    }
+    if let Ok(repo) = Repo::discover() {
+        context.push(("GIT_IGNORE_GIT_DIR", repo.git_dir().into()));
+        if let Some(root) = repo.workdir() {
+            context.push(("GIT_IGNORE_ROOT", root.into()));
+        }
+    }
+    command.envs(context);
+
+    exec(command)
+}
+
+#[cfg(unix)]
+fn exec(mut command: Command) -> program::Result {
+    use std::os::unix::process::CommandExt;
+
+    Err(command.exec().into())
+}
+
+#[cfg(not(unix))]
+fn exec(mut command: Command) -> program::Result {
+    Ok(command.status()?.code().unwrap_or(1))
+}
@@ -0,0 +1,64 @@
+use super::{permute, rewrite, target_opt, TARGET_HELP, TARGET_OPTS};
+use getopt::Opt;
+use git_ignore::{rename_prefix, IgnoreFile, Kind, Target};
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] [-ginr] [-f FILE] OLD NEW", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("{}", TARGET_HELP);
+    println!();
+    println!("  -h       display this help");
+    println!();
+    println!("Rewrites every anchored pattern of the target ('.gitignore' by default) that refers");
+    println!("to a path under the directory OLD to refer to NEW instead, after 'git mv OLD NEW'.");
+    println!("Both are relative to the directory of the target. Options may follow OLD and NEW.");
+    super::print_long_names("rename-prefix");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let optstring = format!("hn{}", TARGET_OPTS);
+    let args = permute(args, &optstring);
+    let mut opts = getopt::Parser::new(&args, &optstring);
+    let mut target = Target::configured(Kind::Ignore)?;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('n', None) => dry_run = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                opt if target_opt(&opt, &mut target) => {},
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    let (old, new) = match &args[opts.index()..] {
+        [old, new] => (old, new),
+        _ => {
+            eprintln!("{}", usage_line(name));
+            return Ok(1);
+        },
+    };
+
+    let mut count = 0;
+    rewrite(&mut IgnoreFile::open(&target)?, dry_run, |text| {
+        let (text, n) = rename_prefix(text, old, new);
+        count = n;
+        text
+    })?;
+    if dry_run {
+        eprintln!("{} pattern(s) would be rewritten", count);
+    } else {
+        eprintln!("{} pattern(s) rewritten", count);
+    }
+
+    Ok(0)
+}
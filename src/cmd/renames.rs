@@ -0,0 +1,143 @@
+use super::rewrite;
+use getopt::Opt;
+use git_ignore::{
+    bytes, directory_renames, missing_dirs, rename_prefix, sources, IgnoreFile, Level, Repo,
+};
+use std::{error::Error, io, path::Path, process::Command};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("apply", 'a'), ("revision", 'R')];
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-ahn] [-R REV]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -R REV   look for renames between REV and HEAD instead of HEAD and the index");
+    println!("  -a       apply the suggested rewrites");
+    println!("  -n       show what would be done without writing anything");
+    println!("  -h       display this help");
+    println!();
+    println!("Looks for directories renamed in the index (after 'git mv', say) or since REV, and");
+    println!("suggests the corresponding rewrites of the patterns of every '.gitignore' in the");
+    println!("working tree, as 'rename-prefix' would do. Patterns referring to directories that");
+    println!("do not exist are reported too.");
+    super::print_long_names("renames");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "ahnR:");
+    let mut rev = None;
+    let mut apply = false;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('R', Some(arg)) => rev = Some(arg),
+                Opt('a', None) => apply = true,
+                Opt('n', None) => dry_run = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let repo = Repo::discover()?;
+    let root = repo
+        .workdir()
+        .ok_or_else(|| git_ignore::Error::BareRepo(repo.git_dir().to_path_buf()))?;
+
+    let renames = directory_renames(&name_status(root, rev.as_deref())?);
+    for (old, new) in &renames {
+        println!("Renamed: {} -> {}", old, new);
+    }
+
+    for source in sources(&repo)? {
+        if source.level != Level::Tree {
+            continue;
+        }
+        let display = source.path.strip_prefix(root).unwrap_or(&source.path);
+        let dir = source.path.parent().unwrap_or(root);
+        let text = bytes::read(&source.path)?;
+
+        let mut new = text.clone();
+        for (old, renamed) in &renames {
+            if let (Some(old), Some(renamed)) =
+                (relative(old, &source.base), relative(renamed, &source.base))
+            {
+                new = rename_prefix(&new, old, renamed).0;
+            }
+        }
+        for (n, (before, after)) in text.lines().zip(new.lines()).enumerate() {
+            if before != after {
+                println!(
+                    "{}:{}: {} -> {}",
+                    display.display(),
+                    n + 1,
+                    before.trim(),
+                    after
+                );
+            }
+        }
+
+        for (n, rule, missing) in missing_dirs(&new, |path| dir.join(path).is_dir()) {
+            println!(
+                "{}:{}: '{}' refers to '{}', which does not exist",
+                display.display(),
+                n,
+                rule,
+                missing
+            );
+        }
+
+        if apply && new != text {
+            rewrite(&mut IgnoreFile::load(&source.path)?, dry_run, |_| new)?;
+        }
+    }
+
+    Ok(0)
+}
+
+// A path relative to the directory `base`, if it is below it
+fn relative<'a>(path: &'a str, base: &str) -> Option<&'a str> {
+    if base.is_empty() {
+        Some(path)
+    } else {
+        path.strip_prefix(base)?.strip_prefix('/')
+    }
+}
+
+fn name_status(root: &Path, rev: Option<&str>) -> Result<String, Box<dyn Error>> {
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(root)
+        .args(["diff", "--name-status", "-M"]);
+    match rev {
+        Some(rev) => command.args([rev, "HEAD"]),
+        None => command.args(["--cached", "HEAD"]),
+    };
+
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr)
+                .trim_end()
+                .to_string(),
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,106 @@
+use super::{report, target_opt, TARGET_HELP, TARGET_OPTS};
+use getopt::Opt;
+use git_ignore::{backups, repo, IgnoreFile, Kind, Outcome, Target};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("time", 't')];
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] [-ginr] [-f FILE] [-t TIME]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("{}", TARGET_HELP);
+    println!();
+    println!("  -t TIME  restore the newest backup whose timestamp starts with TIME (such as");
+    println!("           '20240501' for the last one made that day)");
+    println!("  -h       display this help");
+    println!();
+    println!("Lists the backups of the target ('.gitignore' by default) that '-b' made, oldest");
+    println!("first, or with -t restores one, first backing up the contents it replaces so that");
+    println!("the restore can be undone in turn. Backups are looked for where they are made:");
+    println!("next to the file, or in the directory the global 'ignore.backupDir' config names.");
+    super::print_long_names("restore");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, &format!("hnt:{}", TARGET_OPTS));
+    let mut target = Target::configured(Kind::Ignore)?;
+    let mut time = None;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('n', None) => dry_run = true,
+                Opt('t', Some(arg)) => time = Some(arg),
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                opt if target_opt(&opt, &mut target) => {},
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let path = target.path()?;
+    let dir = repo::config_path("ignore.backupDir")?;
+    let backups = backups(&path, dir.as_deref())?;
+
+    let time = match time {
+        Some(time) => time,
+        None if backups.is_empty() => {
+            eprintln!("No backups of {}", path.to_string_lossy());
+            return Ok(1);
+        },
+        None => {
+            for (stamp, backup) in &backups {
+                println!("{}  {}", stamp, backup.to_string_lossy());
+            }
+            return Ok(0);
+        },
+    };
+    let backup = match backups
+        .iter()
+        .rev()
+        .find(|(stamp, _)| stamp.starts_with(&time))
+    {
+        Some((_, backup)) => backup,
+        None => {
+            return Err(format!(
+                "No backup of {} from {} (run '{}' to list them)",
+                path.to_string_lossy(),
+                time,
+                name
+            )
+            .into())
+        },
+    };
+
+    let mut file = IgnoreFile::load(&path)?;
+    let before = file.text().to_string();
+    file.restore(backup)?;
+    let (written, backup) = if dry_run || !file.is_modified() {
+        (false, None)
+    } else {
+        let backup = file.backup(dir.as_deref())?;
+        (file.save()?, backup)
+    };
+
+    report(&Outcome {
+        path,
+        before,
+        after: file.text().to_string(),
+        written,
+        backup,
+    });
+    Ok(0)
+}
@@ -0,0 +1,69 @@
+use super::{permute, rewrite, target_opt, TARGET_HELP, TARGET_OPTS};
+use getopt::Opt;
+use git_ignore::{IgnoreFile, Kind, Substitution, Target};
+
+fn usage_line(program_name: &str) -> String {
+    format!(
+        "Usage: {} [-h] [-ginr] [-f FILE] s/REGEX/REPLACEMENT/[gi]",
+        program_name
+    )
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("{}", TARGET_HELP);
+    println!();
+    println!("  -h       display this help");
+    println!();
+    println!("Applies a sed-style substitution to every pattern of the target ('.gitignore' by");
+    println!("default), leaving comments alone; with -n, the lines that would change are shown.");
+    println!("REGEX uses extended syntax, with '(...)' for groups; in REPLACEMENT, '&' is the");
+    println!("whole match and '\\1' to '\\9' are groups. Options may follow the substitution.");
+    super::print_long_names("rewrite");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let optstring = format!("hn{}", TARGET_OPTS);
+    let args = permute(args, &optstring);
+    let mut opts = getopt::Parser::new(&args, &optstring);
+    let mut target = Target::configured(Kind::Ignore)?;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('n', None) => dry_run = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                opt if target_opt(&opt, &mut target) => {},
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    let substitution: Substitution = match &args[opts.index()..] {
+        [command] => command.parse()?,
+        _ => {
+            eprintln!("{}", usage_line(name));
+            return Ok(1);
+        },
+    };
+
+    rewrite(&mut IgnoreFile::open(&target)?, dry_run, |text| {
+        let new = substitution.apply_patterns(text);
+        if dry_run {
+            for (n, (before, after)) in text.lines().zip(new.lines()).enumerate() {
+                if before != after {
+                    println!("{}:-{}", n + 1, before);
+                    println!("{}:+{}", n + 1, after);
+                }
+            }
+        }
+        new
+    })?;
+
+    Ok(0)
+}
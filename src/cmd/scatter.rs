@@ -0,0 +1,71 @@
+use super::rewrite;
+use getopt::Opt;
+use git_ignore::{scatter, Error, IgnoreFile, Kind, Repo};
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-hn]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -n       show what would be done without writing anything");
+    println!("  -h       display this help");
+    println!();
+    println!("Moves the rules of the root '.gitignore' that only apply below an existing");
+    println!("directory ('/src/gen/*.rs', say) into that directory's '.gitignore', rewritten");
+    println!("relative to it, so that the same paths stay ignored. Rules that a later root rule");
+    println!("might override are left in place and reported.");
+    super::print_long_names("scatter");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "hn");
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('n', None) => dry_run = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let repo = Repo::discover()?;
+    let root = repo
+        .workdir()
+        .ok_or_else(|| Error::BareRepo(repo.git_dir().to_path_buf()))?;
+
+    let mut file = IgnoreFile::load(root.join(Kind::Ignore.file_name()))?;
+    let scattered = scatter(file.text(), |dir| root.join(dir).is_dir());
+    for rule in &scattered.kept {
+        eprintln!(
+            "Keeping '{}' in the root file, as a later rule may override it",
+            rule
+        );
+    }
+
+    // Rules from the root file come first, so the ones already there still take precedence
+    for (dir, rules) in &scattered.files {
+        let path = root.join(dir).join(Kind::Ignore.file_name());
+        rewrite(&mut IgnoreFile::load(path)?, dry_run, |text| {
+            let mut new = rules.join("\n");
+            new.push('\n');
+            new.push_str(text);
+            new
+        })?;
+    }
+    rewrite(&mut file, dry_run, |_| scattered.root.clone())?;
+
+    Ok(0)
+}
@@ -0,0 +1,225 @@
+use getopt::Opt;
+#[cfg(feature = "net")]
+use git_ignore::{cleanup, http, repo};
+#[cfg(feature = "net")]
+use sha2::{Digest, Sha256};
+#[cfg(feature = "net")]
+use std::{env, error::Error, fs, io, path::Path};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("url", 'u')];
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-hn] [-u URL]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -u URL   read the releases from URL instead of the 'ignore.updateUrl' config");
+    println!("  -n       only say whether a newer release exists");
+    println!();
+    println!("  -h       display this help");
+    println!();
+    println!("Replaces this executable with the newest release built for this platform, if it");
+    println!("is newer. The release feed is a text file with a line per build, of the form");
+    println!();
+    println!("  VERSION PLATFORM SHA256 URL");
+    println!();
+    println!("where PLATFORM is such as 'x86_64-linux' or 'aarch64-macos', and URL may be");
+    println!("relative to the feed. Both must be served over HTTPS. The download is only");
+    println!("installed if its SHA-256 checksum matches, and replaces the executable in one");
+    println!("step.");
+    super::print_long_names("self-update");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "hnu:");
+    let mut feed = None;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('u', Some(arg)) => feed = Some(arg),
+                Opt('n', None) => dry_run = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    update(name, feed, dry_run)
+}
+
+#[cfg(not(feature = "net"))]
+fn update(name: &str, _: Option<String>, _: bool) -> program::Result {
+    eprintln!(
+        "{}: this git-ignore was built without network support",
+        name
+    );
+    Ok(1)
+}
+
+#[cfg(feature = "net")]
+fn update(name: &str, feed: Option<String>, dry_run: bool) -> program::Result {
+    let feed = match feed {
+        Some(feed) => feed,
+        None => match repo::config("ignore.updateUrl")? {
+            Some(feed) => feed,
+            None => {
+                eprintln!(
+                    "{}: no release feed (use -u, or set 'ignore.updateUrl')",
+                    name
+                );
+                return Ok(1);
+            },
+        },
+    };
+    let platform = format!("{}-{}", env::consts::ARCH, env::consts::OS);
+    let current = env!("CARGO_PKG_VERSION");
+
+    let text = match http::get_https(&feed)? {
+        Some(body) => String::from_utf8(body)?,
+        None => return Err(format!("No release feed at {}", feed).into()),
+    };
+    let latest = releases(&text)
+        .into_iter()
+        .filter(|release| release.platform == platform)
+        .max_by(|a, b| version(a.version).cmp(&version(b.version)));
+    let release = match latest {
+        Some(release) if version(release.version) > version(current) => release,
+        Some(_) => {
+            println!("git-ignore {} is up to date", current);
+            return Ok(0);
+        },
+        None => return Err(format!("No release for {} in {}", platform, feed).into()),
+    };
+
+    let exe = env::current_exe()?;
+    eprint!(
+        "Updating {} from {} to {}... ",
+        exe.to_string_lossy(),
+        current,
+        release.version
+    );
+    if dry_run {
+        eprintln!("Skipped (dry run)");
+        return Ok(0);
+    }
+
+    let url = resolve(&feed, release.url);
+    let binary = match http::get_https(&url)? {
+        Some(binary) => binary,
+        None => return Err(format!("Nothing to download at {}", url).into()),
+    };
+    let checksum = format!("{:x}", Sha256::digest(&binary));
+    if !checksum.eq_ignore_ascii_case(release.sha256) {
+        return Err(format!(
+            "Checksum mismatch for {} (expected {}, got {})",
+            url, release.sha256, checksum
+        )
+        .into());
+    }
+    replace(&exe, &binary)?;
+    eprintln!("Done!");
+
+    Ok(0)
+}
+
+#[cfg(feature = "net")]
+struct Release<'a> {
+    version: &'a str,
+    platform: &'a str,
+    sha256: &'a str,
+    url: &'a str,
+}
+
+// The lines of the feed, skipping blank lines, comments, and lines it doesn't understand
+#[cfg(feature = "net")]
+fn releases(text: &str) -> Vec<Release<'_>> {
+    text.lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| match *line.split_whitespace().collect::<Vec<_>>() {
+            [version, platform, sha256, url] => Some(Release {
+                version,
+                platform,
+                sha256,
+                url,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+// Compare versions by their numbers, ignoring any '-' suffix
+#[cfg(feature = "net")]
+fn version(version: &str) -> Vec<u64> {
+    let version = version.trim_start_matches('v');
+    let version = version.split('-').next().unwrap_or(version);
+    version
+        .split('.')
+        .map(|number| number.parse().unwrap_or(0))
+        .collect()
+}
+
+#[cfg(feature = "net")]
+fn resolve(feed: &str, url: &str) -> String {
+    if url.contains("://") {
+        return url.to_string();
+    }
+
+    match feed.rfind('/') {
+        Some(at) => format!("{}/{}", &feed[..at], url.trim_start_matches('/')),
+        None => url.to_string(),
+    }
+}
+
+// Write the new executable next to the old one, then rename it over it, so that an
+// interruption never leaves half an executable behind
+#[cfg(feature = "net")]
+fn replace(exe: &Path, binary: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut temp = exe.as_os_str().to_owned();
+    temp.push(".new");
+    let temp = Path::new(&temp);
+
+    cleanup::register(temp);
+    let result = install(exe, temp, binary);
+    if result.is_err() {
+        let _ = fs::remove_file(temp);
+    }
+    cleanup::unregister(temp);
+
+    Ok(result?)
+}
+
+#[cfg(feature = "net")]
+fn install(exe: &Path, temp: &Path, binary: &[u8]) -> io::Result<()> {
+    fs::write(temp, binary)?;
+    fs::set_permissions(temp, fs::metadata(exe)?.permissions())?;
+
+    // Windows can't replace a running executable, but can rename it out of the way
+    #[cfg(windows)]
+    {
+        let mut old = exe.as_os_str().to_owned();
+        old.push(".old");
+        let _ = fs::remove_file(&old);
+        fs::rename(exe, &old)?;
+        // Put the old one back rather than leave no executable at all
+        fs::rename(temp, exe).map_err(|e| {
+            let _ = fs::rename(&old, exe);
+            e
+        })
+    }
+
+    #[cfg(not(windows))]
+    fs::rename(temp, exe)
+}
@@ -0,0 +1,63 @@
+use super::read_input;
+use getopt::Opt;
+use git_ignore::{bytes, set_op, SetOp};
+use std::{
+    fs,
+    io::{self, Write},
+};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("output", 'o')];
+
+fn usage_line(program_name: &str) -> String {
+    format!(
+        "Usage: {} [-h] [-o FILE] union|intersect|subtract FILE_A FILE_B",
+        program_name
+    )
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -o FILE  write to FILE instead of standard output");
+    println!("  -h       display this help");
+    println!();
+    println!("Prints the patterns in either ignore file (union), in both (intersect), or in");
+    println!("FILE_A but not FILE_B (subtract), sorted and without duplicates or comments.");
+    println!("Either file may be '-' to read standard input.");
+    super::print_long_names("set");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "ho:");
+    let mut output = None;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('o', Some(arg)) => output = Some(arg),
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    let (op, a, b) = match &args[opts.index()..] {
+        [op, a, b] => (op.parse::<SetOp>()?, a, b),
+        _ => {
+            eprintln!("{}", usage_line(name));
+            return Ok(1);
+        },
+    };
+
+    let text = set_op(op, &read_input(a)?, &read_input(b)?);
+    match output {
+        Some(file) => fs::write(file, bytes::encode(&text))?,
+        None => io::stdout().write_all(&bytes::encode(&text))?,
+    }
+
+    Ok(0)
+}
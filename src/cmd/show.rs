@@ -0,0 +1,115 @@
+use super::target_opt;
+use getopt::Opt;
+use git_ignore::{bytes, has_conflicts, Kind, Pattern, Target};
+use is_terminal::IsTerminal;
+use std::{
+    env,
+    io::{self, Write},
+};
+
+const COMMENT: &str = "\x1b[2m";
+const NEGATION: &str = "\x1b[33m";
+const DIRECTORY: &str = "\x1b[34m";
+const INVALID: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("color", 'C')];
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] [-gir] [-f FILE] [-C WHEN]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -f FILE  show FILE");
+    println!("  -g       show the global ignore file (core.excludesFile)");
+    println!("  -i       show the internal repository ignore file (_/.git/info/exclude)");
+    println!("  -r       show the root-level repository ignore file (_/.gitignore)");
+    println!("  -C WHEN  highlight the lines 'always', 'never', or in a terminal ('auto', the");
+    println!("           default)");
+    println!();
+    println!("  -h       display this help");
+    println!();
+    println!("Prints an ignore file ('.gitignore' by default). When highlighting, comments are");
+    println!("dimmed, negations are yellow, directory patterns are blue, and lines that are not");
+    println!("valid patterns (such as merge conflict markers) are red. Setting NO_COLOR turns");
+    println!("off highlighting in a terminal.");
+    super::print_long_names("show");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "C:f:ghir");
+    let mut target = Target::configured(Kind::Ignore)?;
+    let mut color = None;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('C', Some(arg)) => match arg.as_str() {
+                    "always" => color = Some(true),
+                    "never" => color = Some(false),
+                    "auto" => color = None,
+                    _ => {
+                        eprintln!("{}: unknown highlighting '{}'", name, arg);
+                        return Ok(1);
+                    },
+                },
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                opt if target_opt(&opt, &mut target) => {},
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let path = target.locate(Kind::Ignore)?;
+    let text = match bytes::read(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            eprintln!("{}: {} does not exist", name, path.to_string_lossy());
+            return Ok(1);
+        },
+        Err(e) => return Err(e.into()),
+    };
+
+    let color = color.unwrap_or_else(|| {
+        io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+    });
+    let mut output = String::new();
+    for line in text.lines() {
+        match style(line).filter(|_| color) {
+            Some(style) => output.push_str(&format!("{}{}{}\n", style, line, RESET)),
+            None => output.push_str(&format!("{}\n", line)),
+        }
+    }
+    io::stdout().write_all(&bytes::encode(&output))?;
+
+    Ok(0)
+}
+
+// The escape sequence to highlight the line with; plain patterns and blank lines aren't
+fn style(line: &str) -> Option<&'static str> {
+    let trimmed = line.trim_end();
+    if trimmed.is_empty() {
+        None
+    } else if trimmed.starts_with('#') {
+        Some(COMMENT)
+    } else if has_conflicts(line) || Pattern::parse(line).is_none() {
+        Some(INVALID)
+    } else if trimmed.starts_with('!') {
+        Some(NEGATION)
+    } else if trimmed.ends_with('/') {
+        Some(DIRECTORY)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,48 @@
+use getopt::Opt;
+use git_ignore::{sources, Level, Repo};
+use std::{fs, io};
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -h       display this help");
+    println!();
+    println!("Lists the ignore files of the current repository in the order git reads them,");
+    println!("from lowest to highest precedence: the global ignore file, the internal one, and");
+    println!("every '.gitignore' in the working tree, noting those that are missing or cannot");
+    println!("be written.");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "h");
+
+    if let Some(Opt('h', None)) = opts.next().transpose()? {
+        print_usage(name);
+        return Ok(0);
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    for source in sources(&Repo::discover()?)? {
+        let level = match source.level {
+            Level::Global => "global",
+            Level::Internal => "internal",
+            Level::Tree => "tree",
+        };
+        let note = match fs::metadata(&source.path) {
+            Ok(metadata) if metadata.permissions().readonly() => " (read-only)",
+            Ok(_) => "",
+            Err(e) if e.kind() == io::ErrorKind::NotFound => " (missing)",
+            Err(e) => return Err(e.into()),
+        };
+        println!("{:<8}  {}{}", level, source.path.to_string_lossy(), note);
+    }
+
+    Ok(0)
+}
@@ -0,0 +1,228 @@
+use super::{prompt, rewrite, target_opt, TARGET_HELP, TARGET_OPTS};
+use getopt::Opt;
+#[cfg(feature = "net")]
+use git_ignore::template::{fetch_at, reference, set_reference};
+use git_ignore::{
+    blocks, bytes, set_block,
+    template::{self, Source},
+    unified_diff, IgnoreFile, Kind, Target,
+};
+use std::{
+    error::Error,
+    io::{self, Write},
+};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("insecure", 'k'), ("template-ref", 'R'), ("yes", 'y')];
+
+// The blocks are called this and the template's name
+const BLOCK: &str = "template ";
+
+fn usage_line(program_name: &str) -> String {
+    format!(
+        "Usage: {} [-hkny] [-ginr] [-f FILE] [-R REF] add NAME... | list | update [NAME...]",
+        program_name
+    )
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("{}", TARGET_HELP);
+    println!("  -R REF   take the templates from the commit, tag or branch REF of");
+    println!("           github/gitignore (also with the 'ignore.templateRef' config)");
+    println!("  -k       use templates from the registry even if they can't be verified");
+    println!("  -y       update every template that changed without asking");
+    println!("  -h       display this help");
+    println!();
+    println!("Adds each template NAME (such as 'Rust') to the target ('.gitignore' by default)");
+    println!("in a block of its own, between '# BEGIN git-ignore template NAME' and");
+    println!("'# END git-ignore template NAME' lines, starting with a comment saying where it");
+    println!("came from: a template directory, REF, or the version of git-ignore that embeds");
+    println!("it. Listing prints the name and source of every template but those that are");
+    println!("only upstream.");
+    println!();
+    println!("The template directories, tried first, are those in GIT_IGNORE_TEMPLATE_PATH or");
+    println!("else the 'ignore.templatePath' config, separated as in PATH; each has files");
+    println!("'NAME.gitignore', there or in 'Global', as a clone of github/gitignore does.");
+    println!("An organization's registry, which the 'ignore.registry' config names, comes");
+    println!("next: a git repository of such files (a URL ending in '.git', or after 'git+'),");
+    println!("cloned into the cache, or the URL of a directory of them with an 'index' file");
+    println!("listing their names. A private one gets the token in GIT_IGNORE_TOKEN, or else");
+    println!("the credentials a git credential helper or '~/.netrc' has for it (as a");
+    println!("repository, those git finds). Its templates must have their checksums in a");
+    println!("'SHA256SUMS' file with them, signed, in 'SHA256SUMS.minisig' or");
+    println!("'SHA256SUMS.asc', by the key that the 'ignore.registryKey' config gives, if any:");
+    println!("a minisign public key, or the fingerprint or 16-digit ID of a GPG one.");
+    println!();
+    println!("Updating reads the templates in those blocks (all of them, or those named) from");
+    println!("a template directory again, or downloads them from REF or else from 'main', and");
+    println!("prints a diff of the changes to each; answering 'y' replaces the block, and");
+    println!("anything else leaves it as it is. With -n, only the diffs are printed.");
+    super::print_long_names("template");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, &format!("hknR:y{}", TARGET_OPTS));
+    let mut target = Target::configured(Kind::Ignore)?;
+    let mut dry_run = false;
+    let mut yes = false;
+    let mut unverified = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('k', None) => unverified = true,
+                Opt('n', None) => dry_run = true,
+                Opt('R', Some(arg)) => {
+                    #[cfg(feature = "net")]
+                    set_reference(Some(&arg))?;
+                    #[cfg(not(feature = "net"))]
+                    return Err(format!(
+                        "Can't download templates from {}: this build has no network support",
+                        arg
+                    )
+                    .into());
+                },
+                Opt('y', None) => yes = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                opt if target_opt(&opt, &mut target) => {},
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    let get = |name: &str| match unverified {
+        true => template::get_allowing_unverified(name),
+        false => template::get(name),
+    };
+    let mut file = IgnoreFile::open(&target)?;
+    let mut text = file.text().to_string();
+    match &args[opts.index()..] {
+        [command, names @ ..] if command == "add" && !names.is_empty() => {
+            for name in names {
+                // As upstream spells it, for updating
+                let name = template::names()
+                    .find(|embedded| embedded.eq_ignore_ascii_case(name))
+                    .unwrap_or(name);
+                let lines = block(&get(name)?, &describe(&template::source(name)?));
+                text = set_block(&text, &format!("{}{}", BLOCK, name), &lines);
+            }
+        },
+        [command] if command == "list" => {
+            let names = template::list()?;
+            let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+            for name in &names {
+                let source = describe(&template::source(name)?);
+                println!("{:width$}  {}", name, source, width = width);
+            }
+            return Ok(0);
+        },
+        [command, names @ ..] if command == "update" => {
+            let applied: Vec<(String, Vec<String>)> = blocks(&text)
+                .into_iter()
+                .filter_map(|(block, lines)| {
+                    let name = block.strip_prefix(BLOCK)?;
+                    let lines = lines.iter().map(|line| line.to_string()).collect();
+                    Some((name.to_string(), lines))
+                })
+                .filter(|(name, _)| names.is_empty() || names.contains(name))
+                .collect();
+            if let Some(missing) = names
+                .iter()
+                .find(|name| !applied.iter().any(|(applied, _)| applied == *name))
+            {
+                return Err(format!(
+                    "There is no template '{}' in {}",
+                    missing,
+                    file.path().to_string_lossy()
+                )
+                .into());
+            }
+
+            for (name, old) in applied {
+                let (new, source) = match template::source(&name)? {
+                    source @ (Source::File(_) | Source::Registry(_)) => {
+                        (get(&name)?, describe(&source))
+                    },
+                    _ => download(&name)?,
+                };
+                let new = block(&new, &source);
+                if new == old {
+                    eprintln!("Template '{}' is up to date", name);
+                    continue;
+                }
+
+                let diff = unified_diff(
+                    &lines(&old),
+                    &lines(&new),
+                    &format!("{} (applied)", name),
+                    &format!("{} ({})", name, source),
+                    3,
+                );
+                io::stdout().write_all(&bytes::encode(&diff))?;
+                if dry_run {
+                    continue;
+                }
+                if !yes {
+                    let question = format!("Update template '{}'? [y/n]", name);
+                    if prompt(&question)?.as_deref() != Some("y") {
+                        continue;
+                    }
+                }
+                text = set_block(&text, &format!("{}{}", BLOCK, name), &new);
+            }
+        },
+        _ => {
+            eprintln!("{}", usage_line(name));
+            return Ok(1);
+        },
+    }
+
+    rewrite(&mut file, dry_run, |_| text)?;
+    Ok(0)
+}
+
+// The lines of a block for the template `text`, after a comment saying where it came from
+fn block(text: &str, source: &str) -> Vec<String> {
+    let mut lines = vec![format!("# From {}", source)];
+    lines.extend(text.lines().map(str::to_string));
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+fn lines(lines: &[String]) -> String {
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+fn describe(source: &Source) -> String {
+    match source {
+        Source::File(file) => file.to_string_lossy().into_owned(),
+        Source::Registry(url) => url.clone(),
+        Source::Embedded => format!("git-ignore {} (embedded)", env!("CARGO_PKG_VERSION")),
+        Source::Upstream(pin) => format!("github/gitignore@{}", pin.as_deref().unwrap_or("main")),
+    }
+}
+
+// The template as it is upstream now, at the pinned commit or on 'main', and which that was
+#[cfg(feature = "net")]
+fn download(name: &str) -> Result<(String, String), Box<dyn Error>> {
+    let pin = reference()?;
+    let text = fetch_at(name, pin.as_deref())?;
+    let source = format!("github/gitignore@{}", pin.as_deref().unwrap_or("main"));
+    Ok((text, source))
+}
+
+#[cfg(not(feature = "net"))]
+fn download(name: &str) -> Result<(String, String), Box<dyn Error>> {
+    Err(format!(
+        "Can't download template '{}': this build has no network support",
+        name
+    )
+    .into())
+}
@@ -0,0 +1,142 @@
+use getopt::Opt;
+use git_ignore::{bytes, repo, Error, Index, Repo, Rules};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("dir", 'd')];
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] [-d DIR] A B", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -d DIR   read the patterns as relative to DIR (by default, the root of the");
+    println!("           current repository)");
+    println!("  -h       display this help");
+    println!();
+    println!("Checks that the ignore files A and B ignore exactly the same paths of the working");
+    println!("tree, as after rewriting one by hand, listing the paths that only one of them");
+    println!("ignores (a directory stands for everything in it). Exits with 1 if there are any.");
+    super::print_long_names("verify");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "d:h");
+    let mut dir = None;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('d', Some(arg)) => dir = Some(PathBuf::from(arg)),
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    let (a, b) = match &args[opts.index()..] {
+        [a, b] => (a, b),
+        _ => {
+            eprintln!("{}", usage_line(name));
+            return Ok(1);
+        },
+    };
+    let rules_a = Rules::parse(&bytes::read(a)?);
+    let rules_b = Rules::parse(&bytes::read(b)?);
+
+    let repo = match &dir {
+        Some(dir) => Repo::discover_from(dir)?,
+        None => Repo::discover()?,
+    };
+    let dir = match &dir {
+        Some(dir) => dir.clone(),
+        None => root(&repo)?,
+    };
+    let dir = match relative_dir(&repo, &dir)? {
+        Some(dir) => dir,
+        None => {
+            eprintln!(
+                "{}: {} is outside the working tree",
+                name,
+                dir.to_string_lossy()
+            );
+            return Ok(1);
+        },
+    };
+
+    let (only_a, only_b) = divergent(&Index::load(&repo)?, &dir, &rules_a, &rules_b);
+    for (title, paths) in [(a, &only_a), (b, &only_b)] {
+        if !paths.is_empty() {
+            println!("Only ignored by {}:", title);
+            for path in paths {
+                println!("  {}", path);
+            }
+        }
+    }
+
+    Ok(if only_a.is_empty() && only_b.is_empty() {
+        0
+    } else {
+        1
+    })
+}
+
+fn root(repo: &Repo) -> git_ignore::Result<PathBuf> {
+    match repo.workdir() {
+        Some(root) => Ok(repo::canonicalize(root)?),
+        None => Err(Error::BareRepo(repo.git_dir().to_path_buf()).into()),
+    }
+}
+
+// The path of `dir` relative to the root of the working tree of `repo`, with '/' separators, or
+// `None` if it is outside
+pub fn relative_dir(repo: &Repo, dir: &Path) -> git_ignore::Result<Option<String>> {
+    let root = root(repo)?;
+    Ok(git_ignore::repo::canonicalize(dir)?
+        .strip_prefix(root)
+        .ok()
+        .map(|dir| dir.to_string_lossy().replace('\\', "/")))
+}
+
+// The paths below `dir` in the working tree of `index` that only `a` ignores, and those that only
+// `b` ignores, sorted; a directory stands for everything in it
+pub fn divergent(index: &Index, dir: &str, a: &Rules, b: &Rules) -> (Vec<String>, Vec<String>) {
+    let mut only_a = vec![];
+    let mut only_b = vec![];
+    // Directories whose contents are ignored alike, or already listed
+    let mut settled = HashSet::new();
+    for (path, is_dir) in index.entries(dir) {
+        let mut parents = path.match_indices('/').map(|(i, _)| &path[..i]);
+        if parents.any(|parent| settled.contains(parent)) {
+            continue;
+        }
+
+        let ignored = (a.is_ignored(&path, is_dir), b.is_ignored(&path, is_dir));
+        let shown = if is_dir {
+            format!("{}/", path)
+        } else {
+            path.clone()
+        };
+        match ignored {
+            (true, false) => only_a.push(shown),
+            (false, true) => only_b.push(shown),
+            (true, true) => {},
+            (false, false) => continue,
+        }
+        if is_dir {
+            settled.insert(path);
+        }
+    }
+
+    only_a.sort();
+    only_b.sort();
+    (only_a, only_b)
+}
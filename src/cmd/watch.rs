@@ -0,0 +1,193 @@
+use super::{prompt, target_opt, update, TARGET_HELP, TARGET_OPTS};
+use getopt::Opt;
+use git_ignore::{bytes, classify, repo, Category, IgnoreOp, Kind, Repo, Target};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::{
+    collections::HashSet,
+    error::Error,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+// How long the tree has to be quiet before new files are looked at, so that a build creating
+// many files is asked about once
+const SETTLE: Duration = Duration::from_millis(500);
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] [-ginr] [-f FILE]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("{}", TARGET_HELP);
+    println!();
+    println!("  -h       display this help");
+    println!();
+    println!("Watches the working tree of the current repository, and whenever new untracked");
+    println!("files appear (such as build output or downloads), offers a pattern ignoring them");
+    println!("and adds it to the target ('.gitignore' by default) if accepted. Answer 'y' to");
+    println!("add the pattern, another pattern to add that instead, 'n' or nothing to leave the");
+    println!("files alone, and 'q' to stop watching.");
+    super::print_long_names("watch");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, &format!("hn{}", TARGET_OPTS));
+    let mut target = Target::configured(Kind::Ignore)?;
+    let mut dry_run = false;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('n', None) => dry_run = true,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                opt if target_opt(&opt, &mut target) => {},
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    let repo = Repo::discover()?;
+    let root = match repo.workdir() {
+        Some(root) => repo::canonicalize(root)?,
+        None => return Err(git_ignore::Error::BareRepo(repo.git_dir().to_path_buf()).into()),
+    };
+    // The directory the patterns are relative to
+    let base = match target {
+        Target::Global | Target::Internal => root.clone(),
+        _ => match target.locate(Kind::Ignore)?.parent() {
+            Some(dir) => repo::canonicalize(dir)?,
+            None => root.clone(),
+        },
+    };
+    let git_dir = repo::canonicalize(repo.git_dir())?;
+
+    let (sender, events) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    watcher.watch(&root, RecursiveMode::Recursive)?;
+    eprintln!(
+        "Watching {} for new untracked files...",
+        root.to_string_lossy()
+    );
+
+    // Files already asked about, which are not asked about again
+    let mut asked = HashSet::new();
+
+    loop {
+        let mut changed = HashSet::new();
+        let event = events.recv()?;
+        let mut next = Some(event);
+        while let Some(event) = next {
+            let event = event?;
+            if !matches!(event.kind, EventKind::Access(_) | EventKind::Remove(_)) {
+                changed.extend(
+                    event
+                        .paths
+                        .into_iter()
+                        .filter(|path| !path.starts_with(&git_dir))
+                        // Ignore files are never junk
+                        .filter(|path| !path.ends_with(Kind::Ignore.file_name())),
+                );
+            }
+            next = events.recv_timeout(SETTLE).ok();
+        }
+
+        for path in untracked(&repo, &root, &changed)? {
+            if !asked.insert(path.clone()) {
+                continue;
+            }
+            let relative = match root.join(bytes::to_os(&path)).strip_prefix(&base) {
+                Ok(relative) => bytes::from_os(relative.as_os_str()).replace('\\', "/"),
+                Err(_) => continue,
+            };
+            let dir = path.ends_with('/');
+            let suggestion = suggest(relative.trim_end_matches('/'), dir);
+
+            let question = format!(
+                "New untracked {}; ignore with '{}'? [y/N/q, or a pattern]",
+                path, suggestion
+            );
+            let pattern = match prompt(&question)?.as_deref() {
+                None | Some("q") => return Ok(0),
+                Some("y") | Some("Y") => suggestion,
+                Some("") | Some("n") | Some("N") => continue,
+                Some(pattern) => pattern.to_string(),
+            };
+            update(
+                IgnoreOp::new()
+                    .target(target.clone())
+                    .add([pattern])
+                    .dry_run(dry_run),
+            )?;
+        }
+    }
+}
+
+// Which of the changed paths git sees as untracked and not ignored, relative to the root; a
+// wholly untracked directory is given once, with a trailing '/'
+fn untracked(
+    repo: &Repo,
+    root: &Path,
+    changed: &HashSet<PathBuf>,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let changed: Vec<&Path> = changed
+        .iter()
+        .filter(|path| path.exists())
+        .filter_map(|path| path.strip_prefix(root).ok())
+        .filter(|path| !path.as_os_str().is_empty())
+        .collect();
+    if changed.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // Git only reports a wholly untracked directory as such when it gets to it from above
+    let mut paths: Vec<String> = changed
+        .iter()
+        .filter_map(|path| path.iter().next())
+        .map(bytes::from_os)
+        .collect();
+    paths.sort_unstable();
+    paths.dedup();
+
+    // Asking about the top directories also lists the files that were already there
+    Ok(repo
+        .untracked(&paths)?
+        .into_iter()
+        .filter(|entry| {
+            changed
+                .iter()
+                .any(|path| path.starts_with(bytes::to_os(entry.trim_end_matches('/'))))
+        })
+        .collect())
+}
+
+// A pattern for a new file or directory: well-known clutter wherever it is (such as
+// 'node_modules/' or '*.log'), and anything else just where it is
+pub fn suggest(path: &str, dir: bool) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let slash = if dir { "/" } else { "" };
+
+    if classify(name) != Category::Custom {
+        return format!("{}{}", name, slash);
+    }
+    if !dir {
+        if let Some((_, extension)) = name.rsplit_once('.').filter(|(stem, _)| !stem.is_empty()) {
+            let pattern = format!("*.{}", extension);
+            if classify(&pattern) != Category::Custom {
+                return pattern;
+            }
+        }
+    }
+
+    format!("/{}{}", path, slash)
+}
@@ -0,0 +1,65 @@
+use super::target_opt;
+use getopt::Opt;
+use git_ignore::{Kind, Target};
+
+// Long names for the options, besides those every command has
+pub const LONG_OPTS: &[(&str, char)] = &[("attributes", 'a'), ("sparse", 's')];
+
+fn usage_line(program_name: &str) -> String {
+    format!("Usage: {} [-h] [-agirs] [-f FILE]", program_name)
+}
+
+fn print_usage(program_name: &str) {
+    println!("{}", usage_line(program_name));
+    println!("  -f FILE  FILE, resolved against the current directory");
+    println!("  -g       the global ignore file (core.excludesFile, or 'ignore.dotfiles')");
+    println!("  -i       the internal repository ignore file (_/.git/info/exclude)");
+    println!("  -r       the root-level repository ignore file (_/.gitignore)");
+    println!("  -s       the sparse-checkout file (_/.git/info/sparse-checkout)");
+    println!("  -a       the attributes file instead of the ignore file");
+    println!();
+    println!("  -h       display this help");
+    println!();
+    println!("Prints the absolute path of the file that adding patterns with the same options");
+    println!("would change (by default, the one the 'ignore.target' config names, or");
+    println!("'.gitignore' in the current directory), and says on standard error if it does");
+    println!("not exist yet.");
+    super::print_long_names("which");
+}
+
+pub fn run(name: &str, args: &[String]) -> program::Result {
+    let mut opts = getopt::Parser::new(args, "af:ghirs");
+    let mut target = Target::Local;
+    let mut kind = Kind::Ignore;
+
+    loop {
+        match opts.next().transpose()? {
+            None => break,
+            Some(opt) => match opt {
+                Opt('a', None) => kind = Kind::Attributes,
+                Opt('s', None) => target = Target::Sparse,
+                Opt('h', None) => {
+                    print_usage(name);
+                    return Ok(0);
+                },
+                opt if target_opt(&opt, &mut target) => {},
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    if opts.index() < args.len() {
+        eprintln!("{}", usage_line(name));
+        return Ok(1);
+    }
+
+    if target == Target::Local {
+        target = Target::configured(kind)?;
+    }
+    let path = target.locate(kind)?;
+    println!("{}", path.to_string_lossy());
+    if !path.exists() {
+        eprintln!("({} does not exist yet)", path.to_string_lossy());
+    }
+    Ok(0)
+}
@@ -0,0 +1,94 @@
+use crate::export::parse_rule;
+use std::collections::{HashMap, HashSet};
+
+/// The result of [`compare`]ing two sets of ignore rules.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Comparison {
+    /// Patterns only in the first set, with no equivalent in the second.
+    pub only_a: Vec<String>,
+    /// Patterns only in the second set, with no equivalent in the first.
+    pub only_b: Vec<String>,
+    /// Pairs of patterns that are written differently but match the same paths (`foo` and
+    /// `**/foo`, say), first set first.
+    pub equivalent: Vec<(String, String)>,
+}
+
+impl Comparison {
+    /// Whether both sets match the same paths with the same patterns (up to equivalence).
+    pub fn is_same(&self) -> bool {
+        self.only_a.is_empty() && self.only_b.is_empty()
+    }
+}
+
+/// Compare the patterns of the ignore file contents `a` and `b`.
+///
+/// Comments, blank lines and the order of patterns are disregarded.
+pub fn compare(a: &str, b: &str) -> Comparison {
+    let patterns = |text: &str| -> Vec<String> {
+        let mut seen = HashSet::new();
+        text.lines()
+            .map(str::trim)
+            .filter(|line| parse_rule(line).is_some() && seen.insert(*line))
+            .map(String::from)
+            .collect()
+    };
+    let (a, b) = (patterns(a), patterns(b));
+    let (in_a, in_b): (HashSet<&String>, HashSet<&String>) =
+        (a.iter().collect(), b.iter().collect());
+
+    let mut comparison = Comparison::default();
+    let mut b_forms: HashMap<String, &String> = b
+        .iter()
+        .filter(|p| !in_a.contains(p))
+        .filter_map(|p| canonical(p).map(|form| (form, p)))
+        .collect();
+
+    for pattern in a.iter().filter(|p| !in_b.contains(p)) {
+        match canonical(pattern).and_then(|form| b_forms.remove(&form)) {
+            Some(other) => comparison.equivalent.push((pattern.clone(), other.clone())),
+            None => comparison.only_a.push(pattern.clone()),
+        }
+    }
+
+    let matched: HashSet<&String> = comparison.equivalent.iter().map(|(_, b)| b).collect();
+    comparison.only_b = b
+        .iter()
+        .filter(|p| !in_a.contains(p) && !matched.contains(p))
+        .cloned()
+        .collect();
+
+    comparison
+}
+
+/// A canonical spelling of a gitignore pattern, the same for patterns that match the same paths.
+///
+/// Anchored patterns get a leading `/`, unanchored ones with a slash a leading `**/`, and
+/// repeated `**/` components are collapsed.
+pub(crate) fn canonical(line: &str) -> Option<String> {
+    let rule = parse_rule(line)?;
+
+    let mut pattern = rule.pattern.to_string();
+    while pattern.contains("**/**/") {
+        pattern = pattern.replace("**/**/", "**/");
+    }
+    let (anchored, pattern) = match pattern.strip_prefix("**/") {
+        Some(rest) => (false, rest.to_string()),
+        None => (rule.anchored, pattern),
+    };
+
+    let mut form = String::new();
+    if rule.negated {
+        form.push('!');
+    }
+    if anchored {
+        form.push('/');
+    } else if pattern.contains('/') {
+        form.push_str("**/");
+    }
+    form.push_str(&pattern);
+    if rule.dir_only {
+        form.push('/');
+    }
+
+    Some(form)
+}
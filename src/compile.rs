@@ -0,0 +1,158 @@
+use crate::{
+    bytes, repo,
+    template::{self, Source},
+    Result,
+};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Compile an ignore file manifest (such as `.gitignore.in`) into the ignore file contents.
+///
+/// Lines are copied as they are, except for directives:
+///
+/// * `@include PATH` inserts the compiled contents of another manifest or ignore file, relative
+///   to the directory of the file the directive is in;
+/// * `@template NAME` inserts a template (see [`template::get`]), under a comment naming it and,
+///   for one from a template directory, a registry or a pinned commit, where it comes from.
+///
+/// A leading `\@` stands for a literal `@`. `{{NAME}}` placeholders anywhere in the result are
+/// replaced by the values `vars` gives for them (see [`template::substitute`]). The result
+/// starts with a header saying the file is generated, and only depends on the inputs, so
+/// compiling again gives the same file.
+pub fn compile<P, F>(path: P, vars: F) -> Result<String>
+where
+    P: AsRef<Path>,
+    F: Fn(&str) -> Option<String>,
+{
+    compile_in(path.as_ref(), vars, false)
+}
+
+/// Compile a manifest as [`compile`] does, but inserting templates from the registry even if they
+/// can't be verified, with a warning (see [`template::get_allowing_unverified`]).
+pub fn compile_allowing_unverified<P, F>(path: P, vars: F) -> Result<String>
+where
+    P: AsRef<Path>,
+    F: Fn(&str) -> Option<String>,
+{
+    compile_in(path.as_ref(), vars, true)
+}
+
+fn compile_in<F>(path: &Path, vars: F, unverified: bool) -> Result<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let mut text = format!(
+        "# Generated by git-ignore from {}; do not edit.\n# Run 'git-ignore compile' to regenerate.\n\n",
+        name
+    );
+    expand(path, &mut vec![], &mut text, unverified)?;
+
+    template::substitute(&text, vars)
+}
+
+fn expand(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    text: &mut String,
+    unverified: bool,
+) -> Result<()> {
+    let canonical = repo::canonicalize(path).map_err(|e| located(path, None, e))?;
+    if stack.contains(&canonical) {
+        return Err(located(
+            path,
+            None,
+            io::Error::new(io::ErrorKind::InvalidData, "include cycle"),
+        )
+        .into());
+    }
+    stack.push(canonical);
+
+    let source = bytes::read(path).map_err(|e| located(path, None, e))?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    for (n, line) in source.lines().enumerate() {
+        let error = |message: String| {
+            located(
+                path,
+                Some(n + 1),
+                io::Error::new(io::ErrorKind::InvalidData, message),
+            )
+        };
+
+        if let Some(literal) = line.strip_prefix("\\@") {
+            text.push('@');
+            text.push_str(literal);
+            text.push('\n');
+            continue;
+        }
+        let directive = match line.strip_prefix('@') {
+            Some(directive) => directive.trim(),
+            None => {
+                text.push_str(line);
+                text.push('\n');
+                continue;
+            },
+        };
+
+        let (keyword, argument) = directive
+            .split_once(char::is_whitespace)
+            .map(|(k, a)| (k, a.trim()))
+            .unwrap_or((directive, ""));
+        if argument.is_empty() {
+            return Err(error(format!("@{} needs an argument", keyword)).into());
+        }
+
+        match keyword {
+            "include" => {
+                text.push_str(&format!("# Included from {}\n", argument));
+                expand(&dir.join(argument), stack, text, unverified)?;
+            },
+            "template" => {
+                let template = match unverified {
+                    true => template::get_allowing_unverified(argument),
+                    false => template::get(argument),
+                };
+                let template = template.map_err(|e| error(e.to_string()))?;
+                // From a file or a pinned commit, from where, for the same result next time
+                match template::source(argument).map_err(|e| error(e.to_string()))? {
+                    Source::File(file) => text.push_str(&format!(
+                        "# Template: {} ({})\n",
+                        argument,
+                        file.to_string_lossy()
+                    )),
+                    Source::Registry(url) => {
+                        text.push_str(&format!("# Template: {} ({})\n", argument, url))
+                    },
+                    Source::Upstream(Some(pin)) => text.push_str(&format!(
+                        "# Template: {} (github/gitignore@{})\n",
+                        argument, pin
+                    )),
+                    _ => text.push_str(&format!("# Template: {}\n", argument)),
+                }
+                text.push_str(&template);
+                if !template.ends_with('\n') {
+                    text.push('\n');
+                }
+            },
+            _ => return Err(error(format!("unknown directive '@{}'", keyword)).into()),
+        }
+    }
+
+    stack.pop();
+    Ok(())
+}
+
+fn located(path: &Path, line: Option<usize>, error: io::Error) -> io::Error {
+    let location = match line {
+        Some(line) => format!("{}:{}", path.to_string_lossy(), line),
+        None => path.to_string_lossy().into_owned(),
+    };
+    io::Error::new(error.kind(), format!("{}: {}", location, error))
+}
@@ -0,0 +1,94 @@
+/// Whether `text` contains the markers git leaves around a merge conflict.
+///
+/// Merged as they are, the markers and both sides would end up as patterns.
+pub fn has_conflicts(text: &str) -> bool {
+    text.lines()
+        .any(|line| marker(line, '<') || marker(line, '>'))
+}
+
+/// Resolve the merge conflicts in `text` by keeping both sides.
+///
+/// The lines of our side come first, followed by those of their side that ours doesn't have;
+/// the common ancestor's lines of a `diff3`-style conflict are dropped.
+pub fn resolve_conflicts(text: &str) -> String {
+    let mut result = String::new();
+    // The lines of each side of the current conflict, if inside one, and whether the current
+    // line is on their side (`Some(true)`), ours (`Some(false)`), or the ancestor's (`None`)
+    let mut sides: Option<(Vec<&str>, Vec<&str>, Option<bool>)> = None;
+
+    for line in text.lines() {
+        match &mut sides {
+            None if marker(line, '<') => sides = Some((vec![], vec![], Some(false))),
+            None => {
+                result.push_str(line);
+                result.push('\n');
+            },
+            Some((ours, theirs, _)) if marker(line, '>') => {
+                for line in ours
+                    .iter()
+                    .chain(theirs.iter().filter(|t| !ours.contains(t)))
+                {
+                    result.push_str(line);
+                    result.push('\n');
+                }
+                sides = None;
+            },
+            Some((_, _, side)) if marker(line, '|') => *side = None,
+            Some((_, _, side)) if marker(line, '=') => *side = Some(true),
+            Some((_, theirs, Some(true))) => theirs.push(line),
+            Some((ours, _, Some(false))) => ours.push(line),
+            Some((_, _, None)) => {},
+        }
+    }
+
+    // An unterminated conflict keeps what it has
+    if let Some((ours, theirs, _)) = sides {
+        for line in ours
+            .iter()
+            .chain(theirs.iter().filter(|t| !ours.contains(t)))
+        {
+            result.push_str(line);
+            result.push('\n');
+        }
+    }
+
+    result
+}
+
+// A line of seven `c`s, optionally followed by a space and a label
+pub(crate) fn marker(line: &str, c: char) -> bool {
+    match line.trim_end().strip_prefix(&*c.to_string().repeat(7)) {
+        Some(rest) => rest.is_empty() || rest.starts_with(' '),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markers() {
+        assert!(marker("<<<<<<<", '<'));
+        assert!(marker("<<<<<<< HEAD", '<'));
+        assert!(marker(">>>>>>> branch  ", '>'));
+        assert!(!marker("<<<<<<<<", '<'));
+        assert!(!marker("<<<<<<", '<'));
+        assert!(!marker("=======", '<'));
+    }
+
+    #[test]
+    fn multi_byte_lines() {
+        assert!(!marker("日本語.txt", '<'));
+        assert!(!marker("abcd\u{10ff80}.txt", '<'));
+        assert!(!has_conflicts("日本語.txt\n*.log\n"));
+        assert_eq!(resolve_conflicts("日本語.txt\n"), "日本語.txt\n");
+    }
+
+    #[test]
+    fn both_sides() {
+        let text = "a\n<<<<<<< HEAD\nb\n||||||| base\nc\n=======\nb\nd\n>>>>>>> x\ne\n";
+        assert!(has_conflicts(text));
+        assert_eq!(resolve_conflicts(text), "a\nb\nd\ne\n");
+    }
+}
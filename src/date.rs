@@ -0,0 +1,89 @@
+//! Dates and times in UTC, for stamping files and showing when they changed.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A time in UTC, to the second.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DateTime {
+    /// The year, such as 2024.
+    pub year: i64,
+    /// The month, from 1 to 12.
+    pub month: u32,
+    /// The day of the month, from 1.
+    pub day: u32,
+    /// The hour, from 0 to 23.
+    pub hour: u32,
+    /// The minute, from 0 to 59.
+    pub minute: u32,
+    /// The second, from 0 to 59.
+    pub second: u32,
+}
+
+impl DateTime {
+    /// The date and time of `time`; anything before 1970 is taken as 1970-01-01 00:00:00.
+    pub fn utc(time: SystemTime) -> Self {
+        let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let (days, time) = ((secs / 86400) as i64, (secs % 86400) as u32);
+
+        // Howard Hinnant's civil_from_days
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year,
+            month: month as u32,
+            day: day as u32,
+            hour: time / 3600,
+            minute: time / 60 % 60,
+            second: time % 60,
+        }
+    }
+
+    /// The current date and time.
+    pub fn now() -> Self {
+        Self::utc(SystemTime::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn at(secs: u64) -> DateTime {
+        DateTime::utc(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    #[test]
+    fn epoch() {
+        let date = at(0);
+        assert_eq!((date.year, date.month, date.day), (1970, 1, 1));
+        assert_eq!((date.hour, date.minute, date.second), (0, 0, 0));
+        assert_eq!(DateTime::utc(UNIX_EPOCH - Duration::from_secs(1)), date);
+    }
+
+    #[test]
+    fn leap_days() {
+        let date = at(951782400);
+        assert_eq!((date.year, date.month, date.day), (2000, 2, 29));
+        let date = at(4107542399);
+        assert_eq!((date.year, date.month, date.day), (2100, 2, 28));
+        assert_eq!((date.hour, date.minute, date.second), (23, 59, 59));
+        let date = at(4107542400);
+        assert_eq!((date.year, date.month, date.day), (2100, 3, 1));
+    }
+
+    #[test]
+    fn time_of_day() {
+        let date = at(1714564805);
+        assert_eq!((date.year, date.month, date.day), (2024, 5, 1));
+        assert_eq!((date.hour, date.minute, date.second), (12, 0, 5));
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{compare::canonical, Normalization};
+use std::collections::HashSet;
+
+/// Remove the duplicate patterns of an ignore file, keeping everything else as it is.
+///
+/// Patterns are duplicates when they are equal or equivalent (see [`compare`](crate::compare())),
+/// as `foo` and `**/foo` are. git uses the last matching rule, so the last occurrence is kept.
+/// Patterns are first rewritten in the `normalize` form. Returns the new contents and the
+/// removed lines.
+pub fn dedupe(text: &str, normalize: Normalization) -> (String, Vec<String>) {
+    let normalized: Vec<String> = text
+        .lines()
+        .map(|line| {
+            if line.trim_start().starts_with('#') {
+                line.to_string()
+            } else {
+                normalize.apply(line).into_owned()
+            }
+        })
+        .collect();
+    let lines: Vec<&str> = normalized.iter().map(String::as_str).collect();
+    let mut seen = HashSet::new();
+    let mut keep = vec![true; lines.len()];
+
+    for (i, line) in lines.iter().enumerate().rev() {
+        if let Some(form) = canonical(line) {
+            keep[i] = seen.insert(form);
+        }
+    }
+
+    let mut result = String::new();
+    let mut removed = vec![];
+    for (line, keep) in lines.iter().zip(keep) {
+        if keep {
+            result.push_str(line);
+            result.push('\n');
+        } else {
+            removed.push(line.to_string());
+        }
+    }
+    if !text.ends_with('\n') {
+        result.pop();
+    }
+
+    if removed.is_empty() && normalize == Normalization::None {
+        (text.to_string(), removed)
+    } else {
+        (result, removed)
+    }
+}
@@ -0,0 +1,113 @@
+//! Generation of other tools' ignore files from gitignore rules.
+
+use std::{io, str::FromStr};
+
+/// Tools whose ignore files can be derived from a `.gitignore`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Tool {
+    /// ESLint (`.eslintignore`).
+    Eslint,
+    /// Prettier (`.prettierignore`).
+    Prettier,
+}
+
+impl FromStr for Tool {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s {
+            "eslint" => Ok(Self::Eslint),
+            "prettier" => Ok(Self::Prettier),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown tool '{}' (expected eslint or prettier)", s),
+            )),
+        }
+    }
+}
+
+impl Tool {
+    /// The name of the tool's ignore file.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Eslint => ".eslintignore",
+            Self::Prettier => ".prettierignore",
+        }
+    }
+
+    /// The name of the tool, as accepted by [`from_str`](Self::from_str).
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Eslint => "eslint",
+            Self::Prettier => "prettier",
+        }
+    }
+
+    /// The file extensions the tool processes by default.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::Eslint => &["cjs", "cts", "js", "jsx", "mjs", "mts", "ts", "tsx", "vue"],
+            Self::Prettier => &[
+                "cjs",
+                "css",
+                "cts",
+                "gql",
+                "graphql",
+                "handlebars",
+                "hbs",
+                "html",
+                "js",
+                "json",
+                "json5",
+                "jsx",
+                "less",
+                "md",
+                "mdx",
+                "mjs",
+                "mts",
+                "scss",
+                "ts",
+                "tsx",
+                "vue",
+                "yaml",
+                "yml",
+            ],
+        }
+    }
+}
+
+/// The rules of `gitignore` that are relevant to `tool`.
+///
+/// Comments are dropped, as are rules that only match files by an extension the tool never
+/// looks at (`*.log`, say, or `!keep.log`); everything else is kept as is, since both tools read gitignore
+/// syntax.
+pub fn derive_rules(gitignore: &str, tool: Tool) -> Vec<String> {
+    gitignore
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter(|line| match extension(line) {
+            Some(ext) => tool
+                .extensions()
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(ext)),
+            None => true,
+        })
+        .map(String::from)
+        .collect()
+}
+
+// The extension of a `*.EXT` rule, or of the file a negated rule re-includes, when the rule
+// only matches files
+fn extension(rule: &str) -> Option<&str> {
+    if rule.ends_with('/') {
+        return None;
+    }
+
+    let name = rule.rsplit('/').next().unwrap_or(rule);
+    let ext = match (rule.strip_prefix('!'), name.strip_prefix("*.")) {
+        (Some(_), _) => name.rsplit_once('.').map(|(_, ext)| ext),
+        (None, ext) => ext,
+    };
+    ext.filter(|ext| !ext.is_empty() && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+}
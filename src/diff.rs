@@ -0,0 +1,184 @@
+//! Unified diffs, for showing what a change does to a file.
+
+// Past this many differing lines, the rest of the files is shown as replaced wholesale
+const MAX_EDITS: usize = 1000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Edit {
+    Same,
+    Removed,
+    Added,
+}
+
+/// The unified diff (as by `diff -u`) from `before` to `after`, with `context` lines around each
+/// change and the files labelled `from` and `to`; empty if there is no change.
+pub fn unified_diff(before: &str, after: &str, from: &str, to: &str, context: usize) -> String {
+    let a: Vec<&str> = before.split_inclusive('\n').collect();
+    let b: Vec<&str> = after.split_inclusive('\n').collect();
+    let edits = edits(&a, &b);
+    if edits.iter().all(|(edit, _)| *edit == Edit::Same) {
+        return String::new();
+    }
+
+    let mut diff = format!("--- {}\n+++ {}\n", from, to);
+    // The line numbers (from 0) before each edit
+    let mut lines = Vec::with_capacity(edits.len() + 1);
+    let (mut i, mut j) = (0, 0);
+    for (edit, _) in &edits {
+        lines.push((i, j));
+        match edit {
+            Edit::Same => {
+                i += 1;
+                j += 1;
+            },
+            Edit::Removed => i += 1,
+            Edit::Added => j += 1,
+        }
+    }
+    lines.push((i, j));
+
+    let changes: Vec<usize> = (0..edits.len())
+        .filter(|&e| edits[e].0 != Edit::Same)
+        .collect();
+    let mut next = 0;
+    while next < changes.len() {
+        // Changes closer than twice the context share a hunk
+        let first = changes[next];
+        let mut last = first;
+        next += 1;
+        while next < changes.len() && changes[next] - last <= 2 * context + 1 {
+            last = changes[next];
+            next += 1;
+        }
+        let start = first.saturating_sub(context);
+        let end = (last + 1 + context).min(edits.len());
+
+        let (i, j) = lines[start];
+        let (i_end, j_end) = lines[end];
+        diff.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(i, i_end - i),
+            range(j, j_end - j)
+        ));
+        for (edit, line) in &edits[start..end] {
+            diff.push(match edit {
+                Edit::Same => ' ',
+                Edit::Removed => '-',
+                Edit::Added => '+',
+            });
+            diff.push_str(line);
+            if !line.ends_with('\n') {
+                diff.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    diff
+}
+
+// A hunk range: the first line (from 1, or the line before for an empty range) and the count
+fn range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, count),
+    }
+}
+
+// The shortest edit script from `a` to `b` (Myers' algorithm), after taking off the lines they
+// start and end with alike
+fn edits<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<(Edit, &'a str)> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (middle_a, middle_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut edits: Vec<(Edit, &str)> = a[..prefix].iter().map(|&l| (Edit::Same, l)).collect();
+    match shortest(middle_a, middle_b) {
+        Some(middle) => edits.extend(middle),
+        None => {
+            edits.extend(middle_a.iter().map(|&l| (Edit::Removed, l)));
+            edits.extend(middle_b.iter().map(|&l| (Edit::Added, l)));
+        },
+    }
+    edits.extend(a[a.len() - suffix..].iter().map(|&l| (Edit::Same, l)));
+    edits
+}
+
+// `None` if it takes more than `MAX_EDITS` edits
+fn shortest<'a>(a: &[&'a str], b: &[&'a str]) -> Option<Vec<(Edit, &'a str)>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (n + m).min(MAX_EDITS as isize);
+    let offset = max + 1;
+    // The furthest x reached on each diagonal k = x - y, and its values after each step
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    let mut trace = vec![];
+
+    let at = |k: isize| (k + offset) as usize;
+    let mut found = false;
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                v[at(k + 1)]
+            } else {
+                v[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+            if x >= n && y >= m {
+                found = true;
+                break;
+            }
+        }
+        trace.push(v.clone());
+        if found {
+            break;
+        }
+    }
+    if !found {
+        return None;
+    }
+
+    // Walk back from the end through the steps
+    let mut edits = vec![];
+    let (mut x, mut y) = (n, m);
+    for d in (0..trace.len() as isize).rev() {
+        let k = x - y;
+        let (prev_x, prev_y) = if d == 0 {
+            (0, 0)
+        } else {
+            let v = &trace[d as usize - 1];
+            let prev_k = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                k + 1
+            } else {
+                k - 1
+            };
+            (v[at(prev_k)], v[at(prev_k)] - prev_k)
+        };
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push((Edit::Same, a[x as usize]));
+        }
+        if d > 0 {
+            if x == prev_x {
+                edits.push((Edit::Added, b[prev_y as usize]));
+            } else {
+                edits.push((Edit::Removed, a[prev_x as usize]));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+
+    edits.reverse();
+    Some(edits)
+}
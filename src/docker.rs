@@ -0,0 +1,171 @@
+use crate::{
+    export::{parse_rule, rebase},
+    flatten::{literal_prefix, overlaps},
+    merge::trim_end,
+    Rules,
+};
+
+/// Translate a gitignore pattern into the equivalent `.dockerignore` pattern.
+///
+/// `base` is the directory of the gitignore file relative to the build context root (empty for
+/// the root itself). Docker patterns are always relative to the context root, so unanchored
+/// patterns (`foo`, `*.log`) become `**/foo`, anchored ones lose their leading slash, and both
+/// are prefixed with `base`. Docker cannot express directory-only patterns, so a trailing `/` is
+/// dropped; and unlike git, Docker re-includes negated files even inside excluded directories.
+pub fn to_dockerignore(pattern: &str, base: &str) -> String {
+    let pattern = pattern.trim();
+    if pattern.is_empty() || pattern.starts_with('#') {
+        return pattern.to_string();
+    }
+
+    let (negation, pattern) = match pattern.strip_prefix('!') {
+        Some(pattern) => ("!", pattern),
+        None => ("", pattern),
+    };
+    let pattern = pattern.trim_end_matches('/');
+    let anchored = pattern.contains('/');
+    let pattern = pattern.trim_start_matches('/');
+    let base = base.trim_matches('/');
+
+    let mut result = String::from(negation);
+    if !base.is_empty() {
+        result.push_str(base);
+        result.push('/');
+    }
+    if !anchored && !pattern.starts_with("**") {
+        result.push_str("**/");
+    }
+    result.push_str(pattern);
+
+    result
+}
+
+/// Convert gitignore rules into a `.dockerignore` for the repository root as build context.
+///
+/// `rules` is as for [`to_rsync`](crate::export::to_rsync); each rule is translated by
+/// [`to_dockerignore`], in order, since Docker also uses the last matching rule. Git can't
+/// re-include anything in an excluded directory, but Docker can: a negated rule that only
+/// reaches into a directory that an earlier rule excludes does nothing in git, so it is left out,
+/// and one that may reach into one excluded by an earlier directory rule (such as `!*.keep` after
+/// `build/`) is kept, but also returned, as the second element.
+pub fn to_dockerignore_rules<S: AsRef<str>>(rules: &[(S, S)]) -> (String, Vec<String>) {
+    let mut text = String::new();
+    let mut untranslated = vec![];
+    // The rules so far, rewritten for the root
+    let mut earlier: Vec<String> = vec![];
+
+    for (base, rules) in rules {
+        let base = base.as_ref().trim_matches('/');
+        for line in rules.as_ref().lines() {
+            let rule = match parse_rule(line) {
+                Some(rule) => rule,
+                None => continue,
+            };
+            let rooted = if base.is_empty() {
+                trim_end(line).to_string()
+            } else {
+                rebase(&rule, base)
+            };
+
+            if rule.negated {
+                match reach(&rooted, &earlier) {
+                    Reach::Excluded => continue,
+                    Reach::Maybe => untranslated.push(trim_end(line).to_string()),
+                    Reach::Included => {},
+                }
+            }
+            text.push_str(&to_dockerignore(line, base));
+            text.push('\n');
+            earlier.push(rooted);
+        }
+    }
+
+    (text, untranslated)
+}
+
+// Where what a negated rule re-includes is, as far as the earlier rules go
+enum Reach {
+    // All of it is in a directory they exclude
+    Excluded,
+    // Some of it may be in a directory they exclude
+    Maybe,
+    // None of it is in a directory they exclude
+    Included,
+}
+
+fn reach(negated: &str, earlier: &[String]) -> Reach {
+    let rule = match parse_rule(negated) {
+        Some(rule) => rule,
+        None => return Reach::Included,
+    };
+    let components: Vec<&str> = rule.pattern.split('/').collect();
+    let dirs = &components[..components.len() - 1];
+    let literal = literal_prefix(dirs);
+
+    if !literal.is_empty() && Rules::parse(&earlier.join("\n")).is_ignored(&literal.join("/"), true)
+    {
+        return Reach::Excluded;
+    }
+    if rule.anchored && literal.len() == dirs.len() {
+        return Reach::Included;
+    }
+
+    // Below its literal directory, the rule can match in any directory that a directory rule
+    // might exclude
+    let excludes_dirs = earlier
+        .iter()
+        .filter_map(|line| parse_rule(line))
+        .any(|other| {
+            let scope = other
+                .anchored
+                .then(|| literal_prefix(&other.pattern.split('/').collect::<Vec<_>>()));
+            !other.negated && other.dir_only && overlaps(&scope, &literal)
+        });
+    if excludes_dirs {
+        Reach::Maybe
+    } else {
+        Reach::Included
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(rules: &[(&str, &str)]) -> (String, Vec<String>) {
+        to_dockerignore_rules(rules)
+    }
+
+    #[test]
+    fn negations() {
+        // Nothing excludes the directories the negations reach into
+        assert_eq!(
+            convert(&[("", "*.log\n!important.log\n/build/\n!/out/keep\n")]),
+            (
+                String::from("**/*.log\n!**/important.log\nbuild\n!out/keep\n"),
+                vec![]
+            )
+        );
+        // Git can't re-include anything in 'build'
+        assert_eq!(
+            convert(&[("", "build/\n!build/keep\n"), ("sub", "!/build/x\n")]),
+            (String::from("**/build\n"), vec![])
+        );
+        assert_eq!(
+            convert(&[("", "/build/\n"), ("build", "!keep\n")]),
+            (String::from("build\n"), vec![])
+        );
+        // Only some of what the negations re-include may be in 'build'
+        assert_eq!(
+            convert(&[("", "build/\n!*.keep\n!/*/keep\n")]),
+            (
+                String::from("**/build\n!**/*.keep\n!*/keep\n"),
+                vec![String::from("!*.keep"), String::from("!/*/keep")]
+            )
+        );
+        assert_eq!(
+            convert(&[("", "/build/\n"), ("sub", "!*.keep\n")]),
+            (String::from("build\n!sub/**/*.keep\n"), vec![])
+        );
+    }
+}
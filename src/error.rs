@@ -0,0 +1,111 @@
+//! The failures this crate recognizes, for telling them apart from the others it passes on.
+
+use std::{error, fmt, io, path::PathBuf};
+
+/// A failure with a known cause.
+///
+/// This crate's errors are boxed (see [`Result`](crate::Result)), and these may also be wrapped
+/// in an [`io::Error`]; [`Error::find`] looks through both.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// No repository was found in the directory or any of its parents.
+    RepoNotFound(PathBuf),
+    /// The repository with this git directory has no working tree.
+    BareRepo(PathBuf),
+    /// The file is in a git directory, where git reads none but the files in `info`.
+    InGitDir(PathBuf),
+    /// The file could not be read or written for lack of permission.
+    PermissionDenied(PathBuf),
+    /// Git would refuse this line of a config file.
+    BadConfig {
+        /// The config file.
+        file: PathBuf,
+        /// The line, from 1.
+        line: usize,
+        /// What is wrong with it.
+        reason: String,
+    },
+    /// The pattern can't be written to an ignore file.
+    InvalidPattern {
+        /// The pattern.
+        pattern: String,
+        /// Why, as a phrase following it, such as "spans lines".
+        reason: String,
+    },
+    /// Downloading something failed.
+    Network {
+        /// What was being downloaded.
+        url: String,
+        /// Why.
+        reason: String,
+    },
+    /// A downloaded template could not be verified against the checksums (and signature) its
+    /// source gives.
+    Unverified {
+        /// The template.
+        name: String,
+        /// Why, as a phrase.
+        reason: String,
+    },
+}
+
+impl Error {
+    /// The `Error` that `error` is, wraps (as an [`io::Error`] can), or was caused by, if any.
+    pub fn find<'a>(error: &'a (dyn error::Error + 'static)) -> Option<&'a Self> {
+        let mut next = Some(error);
+        while let Some(error) = next {
+            if let Some(found) = error.downcast_ref::<Self>() {
+                return Some(found);
+            }
+            let inner = error
+                .downcast_ref::<io::Error>()
+                .and_then(io::Error::get_ref);
+            if let Some(found) = inner.and_then(|inner| inner.downcast_ref::<Self>()) {
+                return Some(found);
+            }
+            next = error.source();
+        }
+        None
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RepoNotFound(dir) => write!(
+                f,
+                "Not inside a git repository (searched from {})",
+                dir.to_string_lossy()
+            ),
+            Self::BareRepo(git_dir) => write!(
+                f,
+                "Repository {} is bare, without a working tree",
+                git_dir.to_string_lossy()
+            ),
+            Self::InGitDir(path) => write!(
+                f,
+                "{} is inside a git directory, where git reads no pattern files but info/exclude, \
+                 info/attributes and info/sparse-checkout",
+                path.to_string_lossy()
+            ),
+            Self::PermissionDenied(path) => {
+                write!(f, "Permission denied: {}", path.to_string_lossy())
+            },
+            Self::BadConfig { file, line, reason } => write!(
+                f,
+                "Bad config line {} in {}: {}",
+                line,
+                file.to_string_lossy(),
+                reason
+            ),
+            Self::InvalidPattern { pattern, reason } => write!(f, "{:?} {}", pattern, reason),
+            Self::Network { url, reason } => write!(f, "Failed to download {}: {}", url, reason),
+            Self::Unverified { name, reason } => {
+                write!(f, "Template '{}' could not be verified: {}", name, reason)
+            },
+        }
+    }
+}
+
+impl error::Error for Error {}
@@ -0,0 +1,309 @@
+//! Conversion of gitignore rules into other tools' exclusion syntax.
+
+use crate::merge::trim_end;
+
+/// Convert gitignore rules into an rsync filter file for `--exclude-from`.
+///
+/// `rules` is a list of `(base, text)` pairs in increasing order of precedence (the order
+/// `sources` returns them in), where `base` is the directory the patterns are relative to. rsync
+/// uses the first matching rule while git uses the last, so the rules are emitted in
+/// reverse, each with an explicit `- ` (exclude) or `+ ` (include, for negations) prefix.
+/// Patterns are anchored to the transfer root as git anchors them to their file's directory.
+pub fn to_rsync<S: AsRef<str>>(rules: &[(S, S)]) -> String {
+    let mut text = String::new();
+
+    for (base, rules) in rules.iter().rev() {
+        for line in rules.as_ref().lines().rev() {
+            let (include, patterns) = match rsync_patterns(line, base.as_ref()) {
+                Some(rule) => rule,
+                None => continue,
+            };
+            for pattern in patterns {
+                text.push_str(if include { "+ " } else { "- " });
+                text.push_str(&pattern);
+                text.push('\n');
+            }
+        }
+    }
+
+    text
+}
+
+/// Flatten gitignore rules from several files into a single root-level `.gitignore`.
+///
+/// `rules` is as for [`to_rsync`]. Rules from nested files are anchored below their directory
+/// (`foo` in `sub/.gitignore` becomes `/sub/**/foo`); git uses the last matching rule, so the
+/// order is kept. Comments and blank lines are dropped.
+pub fn to_gitignore<S: AsRef<str>>(rules: &[(S, S)]) -> String {
+    let mut text = String::new();
+
+    for (base, rules) in rules {
+        let base = base.as_ref();
+        for line in rules.as_ref().lines() {
+            let rule = match parse_rule(line) {
+                Some(rule) => rule,
+                None => continue,
+            };
+            if base.is_empty() {
+                text.push_str(trim_end(line));
+            } else {
+                text.push_str(&rebase(&rule, base));
+            }
+            text.push('\n');
+        }
+    }
+
+    text
+}
+
+/// Like [`to_gitignore`], but with each file's rules under a `# From NAME` comment.
+///
+/// `rules` is a list of `(name, base, text)` triples in increasing order of precedence, where
+/// `name` describes the file the rules come from. Files without rules are left out.
+pub fn to_annotated_gitignore<S: AsRef<str>>(rules: &[(S, S, S)]) -> String {
+    let mut text = String::new();
+
+    for (name, base, rules) in rules {
+        let rules = to_gitignore(&[(base, rules)]);
+        if rules.is_empty() {
+            continue;
+        }
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&format!("# From {}\n", name.as_ref()));
+        text.push_str(&rules);
+    }
+
+    text
+}
+
+/// Convert gitignore rules into a Mercurial `.hgignore`.
+///
+/// `rules` is as for [`to_rsync`]. Unanchored root-level patterns become plain globs and
+/// everything else a `rootglob:` rule; Mercurial cannot re-include a path or match only
+/// directories, so negated rules are returned separately, as the second element, and trailing
+/// slashes are dropped.
+pub fn to_hgignore<S: AsRef<str>>(rules: &[(S, S)]) -> (String, Vec<String>) {
+    let mut text = String::from("syntax: glob\n");
+    let mut negated = vec![];
+
+    for (base, rules) in rules {
+        let base = base.as_ref();
+        for line in rules.as_ref().lines() {
+            let rule = match parse_rule(line) {
+                Some(rule) => rule,
+                None => continue,
+            };
+            if rule.negated {
+                negated.push(trim_end(line).to_string());
+                continue;
+            }
+            // `#` starts a comment anywhere in a line
+            let pattern = rule.pattern.replace('#', "\\#");
+            let (anchored, pattern) = match pattern.strip_prefix("**/") {
+                Some(pattern) => (false, pattern),
+                None => (rule.anchored, pattern.as_str()),
+            };
+            match (anchored, base.is_empty()) {
+                (false, true) => text.push_str(pattern),
+                (false, false) => {
+                    text.push_str("rootglob:");
+                    text.push_str(base);
+                    text.push_str("/**/");
+                    text.push_str(pattern);
+                },
+                (true, _) => {
+                    text.push_str("rootglob:");
+                    if !base.is_empty() {
+                        text.push_str(base);
+                        text.push('/');
+                    }
+                    text.push_str(pattern);
+                },
+            }
+            text.push('\n');
+        }
+    }
+
+    (text, negated)
+}
+
+/// Convert gitignore rules into a GNU tar pattern file for `--exclude-from`.
+///
+/// `rules` is as for [`to_rsync`]. tar's default exclusion matching is unanchored and lets `*`
+/// match `/`, so anchored patterns can also match deeper in the tree, and it has no way to match
+/// only directories or to re-include a path: negated rules are returned separately, as the
+/// second element, instead of being emitted. This is still more faithful than
+/// `--exclude-vcs-ignores`, which doesn't read the global and internal ignore files.
+pub fn to_tar<S: AsRef<str>>(rules: &[(S, S)]) -> (String, Vec<String>) {
+    let mut text = String::new();
+    let mut negated = vec![];
+
+    for (base, rules) in rules {
+        for line in rules.as_ref().lines() {
+            let rule = match parse_rule(line) {
+                Some(rule) => rule,
+                None => continue,
+            };
+            if rule.negated {
+                negated.push(trim_end(line).to_string());
+                continue;
+            }
+            for pattern in tar_patterns(&rule, base.as_ref()) {
+                text.push_str(&pattern);
+                text.push('\n');
+            }
+        }
+    }
+
+    (text, negated)
+}
+
+// A parsed gitignore rule: whether it is negated, the pattern relative to its base directory
+// without a leading slash, whether it is anchored, and whether it only matches directories
+pub(crate) struct Rule<'a> {
+    pub negated: bool,
+    pub pattern: &'a str,
+    pub anchored: bool,
+    pub dir_only: bool,
+}
+
+pub(crate) fn parse_rule(line: &str) -> Option<Rule<'_>> {
+    let line = trim_end(line);
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (negated, pattern) = match line.strip_prefix('!') {
+        Some(pattern) => (true, pattern),
+        None => (
+            false,
+            line.strip_prefix('\\')
+                .filter(|p| p.starts_with(['#', '!']))
+                .unwrap_or(line),
+        ),
+    };
+    let dir_only = pattern.ends_with('/');
+    let pattern = pattern.trim_end_matches('/');
+    let anchored = pattern.contains('/');
+    let pattern = pattern.trim_start_matches('/');
+    if pattern.is_empty() {
+        return None;
+    }
+
+    Some(Rule {
+        negated,
+        pattern,
+        anchored,
+        dir_only,
+    })
+}
+
+// The rule as it would be written in the `.gitignore` of the parent of `base`
+pub(crate) fn rebase(rule: &Rule, base: &str) -> String {
+    let mut text = String::new();
+
+    if rule.negated {
+        text.push('!');
+    }
+    text.push('/');
+    text.push_str(base);
+    text.push('/');
+    if !rule.anchored {
+        text.push_str("**/");
+    }
+    text.push_str(rule.pattern);
+    if rule.dir_only {
+        text.push('/');
+    }
+
+    text
+}
+
+fn rsync_patterns(line: &str, base: &str) -> Option<(bool, Vec<String>)> {
+    let rule = parse_rule(line)?;
+    let prefix = if base.is_empty() {
+        String::from("/")
+    } else {
+        format!("/{}/", base)
+    };
+
+    // rsync matches slash-less patterns against the last component and other unanchored
+    // patterns against any trailing part of the path, like a leading `**/` in git
+    let unanchored = if !rule.anchored {
+        Some(rule.pattern)
+    } else {
+        rule.pattern.strip_prefix("**/")
+    };
+
+    let mut patterns = match unanchored {
+        Some(pattern) if base.is_empty() => vec![pattern.to_string()],
+        Some(pattern) => vec![
+            format!("{}{}", prefix, pattern),
+            format!("{}**/{}", prefix, pattern),
+        ],
+        None => vec![format!("{}{}", prefix, rule.pattern)],
+    };
+    if rule.dir_only {
+        for pattern in &mut patterns {
+            pattern.push('/');
+        }
+    }
+
+    Some((rule.negated, patterns))
+}
+
+fn tar_patterns(rule: &Rule, base: &str) -> Vec<String> {
+    let prefix = if base.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", base)
+    };
+
+    // Excluding a directory excludes its contents, and `*` already crosses directories
+    let pattern = rule.pattern.strip_suffix("/**").unwrap_or(rule.pattern);
+    let (anchored, pattern) = match pattern.strip_prefix("**/") {
+        Some(pattern) => (false, pattern),
+        None => (rule.anchored, pattern),
+    };
+
+    // `**/` can match no directories at all, where `*/` must match at least one
+    let mut patterns = vec![pattern.replace("/**/", "/")];
+    if pattern.contains("/**/") {
+        patterns.push(pattern.replace("/**/", "/*/"));
+    }
+
+    if anchored || base.is_empty() {
+        patterns
+            .into_iter()
+            .map(|pattern| format!("{}{}", prefix, pattern))
+            .collect()
+    } else {
+        patterns
+            .into_iter()
+            .flat_map(|pattern| {
+                [
+                    format!("{}{}", prefix, pattern),
+                    format!("{}*/{}", prefix, pattern),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trailing_spaces() {
+        let pattern = |line| parse_rule(line).map(|rule| rule.pattern);
+        assert_eq!(pattern("foo  "), Some("foo"));
+        assert_eq!(pattern("foo\\ "), Some("foo\\ "));
+        assert_eq!(pattern("foo\\   "), Some("foo\\ "));
+        assert_eq!(pattern("foo\\\\ "), Some("foo\\\\"));
+        assert_eq!(pattern(" foo"), Some(" foo"));
+        assert_eq!(pattern("   "), None);
+    }
+}
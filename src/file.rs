@@ -0,0 +1,452 @@
+use crate::{
+    bytes, cleanup, date::DateTime, merge, merge::trim, Error, MergeOptions, Result, Target,
+};
+use atomicwrites::{AllowOverwrite, AtomicFile};
+use memmap2::Mmap;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    thread,
+    time::Duration,
+};
+
+/// What an [`IgnoreFile`] does with the final newline when its contents change.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Newline {
+    /// End with a newline if the file did, or did not exist yet, and without one otherwise.
+    #[default]
+    Preserve,
+    /// End with exactly one newline.
+    Always,
+    /// End without a newline.
+    Never,
+}
+
+impl FromStr for Newline {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s {
+            "preserve" => Ok(Self::Preserve),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Unknown newline policy '{}' (expected preserve, always or never)",
+                    s
+                ),
+            )),
+        }
+    }
+}
+
+/// An ignore file loaded into memory.
+///
+/// The file is locked from [`load`](Self::load) until the value is dropped, so concurrent
+/// invocations cannot lose each other's patterns.
+#[derive(Debug)]
+pub struct IgnoreFile {
+    path: PathBuf,
+    exists: bool,
+    original: String,
+    text: String,
+    newline: Newline,
+    _lock: Lock,
+}
+
+impl IgnoreFile {
+    /// Lock and read the ignore file at `path`; a missing file is treated as empty.
+    ///
+    /// Bytes that aren't UTF-8 are kept, as [`bytes::decode`] does, and written back as they
+    /// were.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let lock = Lock::acquire(&path)?;
+
+        let (exists, original) = match fs::read(&path) {
+            Ok(contents) => (true, bytes::decode(&contents)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (false, String::new()),
+            Err(e) => return Err(with_path(e, &path)),
+        };
+
+        Ok(Self {
+            path,
+            exists,
+            text: original.clone(),
+            original,
+            newline: Newline::default(),
+            _lock: lock,
+        })
+    }
+
+    /// Resolve `target` and [`load`](Self::load) the file it refers to.
+    pub fn open(target: &Target) -> Result<Self> {
+        Ok(Self::load(target.path()?)?)
+    }
+
+    /// The path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The current (possibly modified) contents of the file.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Whether the contents differ from what is on disk.
+    pub fn is_modified(&self) -> bool {
+        self.text != self.original
+    }
+
+    /// Set what to do with the final newline when the contents change.
+    pub fn set_newline(&mut self, newline: Newline) {
+        self.newline = newline;
+    }
+
+    /// Replace the file contents, applying the newline policy.
+    pub fn set_text(&mut self, text: String) {
+        let ends = match self.newline {
+            Newline::Preserve => self.original.is_empty() || self.original.ends_with('\n'),
+            Newline::Always => true,
+            Newline::Never => false,
+        };
+
+        let mut text = text;
+        if self.newline != Newline::Preserve || !ends {
+            text.truncate(text.trim_end_matches('\n').len());
+        }
+        if ends && !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        self.text = text;
+    }
+
+    /// Merge `patterns` into the file contents; see [`merge`](crate::merge()).
+    pub fn merge<S: AsRef<str>>(&mut self, patterns: &[S], options: MergeOptions) {
+        self.set_text(merge(&self.text, patterns, options));
+    }
+
+    /// Copy the file as it is on disk to `NAME.bak-TIMESTAMP` (such as
+    /// `.gitignore.bak-20240501T120000Z`), in `dir` if given (creating it if needed) or else next
+    /// to it, so that its old contents can be recovered after [`save`](Self::save) replaces it.
+    ///
+    /// An existing backup is never overwritten: a second one in the same second gets `.1` added,
+    /// and so on. Returns the path of the backup, or `None` if the file does not exist.
+    pub fn backup(&self, dir: Option<&Path>) -> io::Result<Option<PathBuf>> {
+        if !self.exists {
+            return Ok(None);
+        }
+        if let Some(dir) = dir {
+            fs::create_dir_all(dir)?;
+        }
+        let dir = backup_dir(&self.path, dir);
+        let name = format!("{}{}", backup_prefix(&self.path), timestamp());
+
+        for n in 0.. {
+            let path = match n {
+                0 => dir.join(&name),
+                _ => dir.join(format!("{}.{}", name, n)),
+            };
+            let mut file = match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(file) => file,
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            };
+            file.write_all(&bytes::encode(&self.original))?;
+            file.sync_all()?;
+            return Ok(Some(path));
+        }
+        unreachable!()
+    }
+
+    /// Replace the contents with exactly those of the file at `backup`, final newline and all,
+    /// ready to [`save`](Self::save).
+    pub fn restore<P: AsRef<Path>>(&mut self, backup: P) -> io::Result<()> {
+        self.text = bytes::decode(&fs::read(backup)?);
+        Ok(())
+    }
+
+    /// Write the contents back to disk if they were modified, or if the file does not exist yet.
+    ///
+    /// The contents go to a temporary file next to it, which is synced and then renamed over it,
+    /// and the directory is synced after the rename (on Windows, the rename is written through),
+    /// so that after a crash the file is whole, old or new, even on a networked file system. On
+    /// Windows, files deeper than `MAX_PATH` allows are written too.
+    ///
+    /// Returns whether the contents changed.
+    pub fn save(&mut self) -> io::Result<bool> {
+        let modified = self.is_modified();
+
+        if modified || !self.exists {
+            AtomicFile::new(atomic_path(&self.path), AllowOverwrite)
+                .write(|f| f.write_all(&bytes::encode(&self.text)))
+                .map_err(|e| match e {
+                    atomicwrites::Error::Internal(e) | atomicwrites::Error::User(e) => {
+                        with_path(e, &self.path)
+                    },
+                })?;
+            self.exists = true;
+            self.original = self.text.clone();
+        }
+
+        Ok(modified)
+    }
+}
+
+/// The backups of the file at `path` that [`IgnoreFile::backup`] made in `dir` (or next to
+/// it), oldest first, each with what its name has after `.bak-`: the timestamp, and any number
+/// added to it.
+pub fn backups<P: AsRef<Path>>(path: P, dir: Option<&Path>) -> io::Result<Vec<(String, PathBuf)>> {
+    let path = path.as_ref();
+    let prefix = backup_prefix(path);
+    let entries = match fs::read_dir(backup_dir(path, dir)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e),
+    };
+
+    let mut backups = vec![];
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if let Some(stamp) = name.strip_prefix(&prefix) {
+            backups.push((stamp.to_string(), entry.path()));
+        }
+    }
+    // A timestamp sorts as text, and the number after it as a number
+    backups.sort_by_key(|(stamp, _)| match stamp.split_once('.') {
+        Some((time, n)) => (time.to_string(), n.parse().unwrap_or(usize::MAX)),
+        None => (stamp.clone(), 0),
+    });
+    Ok(backups)
+}
+
+fn backup_dir(path: &Path, dir: Option<&Path>) -> PathBuf {
+    match dir {
+        Some(dir) => dir.to_path_buf(),
+        None => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+    }
+}
+
+fn backup_prefix(path: &Path) -> String {
+    format!(
+        "{}.bak-",
+        path.file_name()
+            .map_or_else(Default::default, |name| name.to_string_lossy())
+    )
+}
+
+// The current time in UTC, as 20240501T120000Z
+fn timestamp() -> String {
+    let now = DateTime::now();
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        now.year, now.month, now.day, now.hour, now.minute, now.second
+    )
+}
+
+/// Whether the file at `path` has each of `lines` as one of its lines, comparing them as
+/// [`merge`](crate::merge()) does; a missing file has none of them.
+///
+/// A file of 16 MiB or more is memory-mapped rather than read into memory where possible, so
+/// that looking for a few lines in a huge file is cheap. It is locked meanwhile, as by
+/// [`IgnoreFile::load`], but that only stops other git-ignore processes: another program
+/// truncating it then gets this one killed by `SIGBUS`.
+pub fn has_lines<P: AsRef<Path>, S: AsRef<str>>(path: P, lines: &[S]) -> io::Result<Vec<bool>> {
+    let path = path.as_ref();
+    let mut found = vec![false; lines.len()];
+    let mut file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(found),
+        Err(e) => return Err(e),
+    };
+    let _lock = Lock::acquire(path)?;
+
+    let mut wanted: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, line) in lines.iter().enumerate() {
+        wanted.entry(trim(line.as_ref())).or_default().push(i);
+    }
+
+    // Mapping fails for empty files and for some special ones, which are read instead
+    let mut read = vec![];
+    let map = if file.metadata()?.len() >= MAP_THRESHOLD {
+        // SAFETY: the lock keeps other git-ignore processes from changing the file while it is
+        // mapped, but not anything else. Another process changing it in place can only make
+        // the lines found wrong, as they are checked to be UTF-8, or copied, before being looked
+        // at; but one truncating it (an editor saving it, or 'git checkout') makes reading the
+        // pages past the new end raise SIGBUS, which kills the process. That is the price of
+        // not copying a file this large, which such tools rarely touch.
+        unsafe { Mmap::map(&file) }.ok()
+    } else {
+        None
+    };
+    let contents = match &map {
+        Some(map) => &map[..],
+        None => {
+            file.read_to_end(&mut read)?;
+            &read[..]
+        },
+    };
+
+    let mut left = lines.len();
+    for line in contents.split(|&b| b == b'\n') {
+        let line = bytes::as_text(line);
+        if let Some(indices) = wanted.remove(trim(&line)) {
+            for i in indices {
+                found[i] = true;
+                left -= 1;
+            }
+            if left == 0 {
+                break;
+            }
+        }
+    }
+
+    Ok(found)
+}
+
+/// `path` for [`AtomicFile`], which renames with the Windows API itself: unlike the standard
+/// library, that takes a path longer than `MAX_PATH` only in its extended form, starting with
+/// `\\?\` (and the temporary file is deeper still, in a directory next to the file).
+#[cfg(windows)]
+pub(crate) fn atomic_path(path: &Path) -> PathBuf {
+    use std::{
+        env,
+        ffi::OsString,
+        os::windows::ffi::OsStrExt,
+        path::{Component, Prefix},
+    };
+
+    const MAX_PATH: usize = 260;
+    // Room for the name of the temporary directory, and a separator
+    const TEMPORARY: usize = 32;
+    if path.as_os_str().encode_wide().count() + TEMPORARY < MAX_PATH {
+        return path.to_path_buf();
+    }
+
+    let path = match env::current_dir() {
+        Ok(dir) => dir.join(path),
+        Err(_) => return path.to_path_buf(),
+    };
+    // Such as 'C:file', relative to the current directory of the drive
+    if !path.has_root() {
+        return path;
+    }
+    let mut components = path.components();
+    let mut extended = match components.next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(_) => {
+                let mut extended = OsString::from(r"\\?\");
+                extended.push(prefix.as_os_str());
+                extended
+            },
+            Prefix::UNC(server, share) => {
+                let mut extended = OsString::from(r"\\?\UNC\");
+                extended.push(server);
+                extended.push(r"\");
+                extended.push(share);
+                extended
+            },
+            // Extended already, or a device
+            _ => return path,
+        },
+        _ => return path,
+    };
+    extended.push(r"\");
+
+    // Windows takes an extended path as it is, so '.' and '..' are resolved here, as it would
+    let mut extended = PathBuf::from(extended);
+    for component in components {
+        match component {
+            Component::Normal(name) => extended.push(name),
+            Component::ParentDir => {
+                extended.pop();
+            },
+            _ => {},
+        }
+    }
+    extended
+}
+
+#[cfg(not(windows))]
+pub(crate) fn atomic_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+// The size from which `has_lines` maps files rather than reading them
+const MAP_THRESHOLD: u64 = 16 << 20;
+
+const LOCK_ATTEMPTS: u32 = 100;
+const LOCK_INTERVAL: Duration = Duration::from_millis(50);
+
+// Say which file an I/O error for lack of permission was about, keeping its kind
+fn with_path(e: io::Error, path: &Path) -> io::Error {
+    if e.kind() == io::ErrorKind::PermissionDenied {
+        io::Error::new(e.kind(), Error::PermissionDenied(path.to_path_buf()))
+    } else {
+        e
+    }
+}
+
+// Git-style lock file next to the target; held for the whole read/merge/write cycle, but for a
+// file whose directory doesn't exist yet, which nothing can be writing
+#[derive(Debug)]
+struct Lock(Option<PathBuf>);
+
+impl Lock {
+    fn acquire(file: &Path) -> io::Result<Self> {
+        let mut path = file.as_os_str().to_owned();
+        path.push(".lock");
+        let path = PathBuf::from(path);
+
+        let mut attempts = 0;
+        loop {
+            match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => {
+                    cleanup::register(&path);
+                    return Ok(Self(Some(path)));
+                },
+                Err(e)
+                    if e.kind() == io::ErrorKind::NotFound
+                        && file.parent().is_some_and(|dir| !dir.exists()) =>
+                {
+                    return Ok(Self(None))
+                },
+                Err(e) if e.kind() != io::ErrorKind::AlreadyExists => {
+                    return Err(with_path(e, &path))
+                },
+                Err(_) if attempts < LOCK_ATTEMPTS => {
+                    attempts += 1;
+                    thread::sleep(LOCK_INTERVAL);
+                },
+                Err(e) => {
+                    return Err(io::Error::new(
+                        e.kind(),
+                        format!(
+                            "Unable to lock {}: lock file exists (remove it if no other git-ignore is running)",
+                            path.to_string_lossy()
+                        ),
+                    ))
+                },
+            }
+        }
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        if let Some(path) = &self.0 {
+            let _ = fs::remove_file(path);
+            cleanup::unregister(path);
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use crate::export::{parse_rule, rebase};
+
+/// Rewrite the rules of nested `.gitignore` files for the root one.
+///
+/// `nested` is a list of `(base, text)` pairs in increasing order of precedence, where `base` is
+/// the directory of each file relative to the root. The rules of each file are anchored below
+/// its directory (see [`to_gitignore`](crate::export::to_gitignore)) and put under a
+/// `# From BASE/.gitignore` comment, ready to be merged into the root file with
+/// [`merge_file`](crate::merge_file) (without sorting, since the order decides which rule wins).
+pub fn flatten<S: AsRef<str>>(nested: &[(S, S)]) -> String {
+    let mut text = String::new();
+
+    for (base, rules) in nested {
+        let base = base.as_ref().trim_matches('/');
+        let rules: Vec<String> = rules
+            .as_ref()
+            .lines()
+            .filter_map(parse_rule)
+            .map(|rule| rebase(&rule, base))
+            .collect();
+        if rules.is_empty() {
+            continue;
+        }
+
+        text.push_str(&format!("# From {}/.gitignore\n", base));
+        for rule in rules {
+            text.push_str(&rule);
+            text.push('\n');
+        }
+    }
+
+    text
+}
+
+/// The result of [`scatter`]ing a root `.gitignore`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Scattered {
+    /// The new contents of the root file.
+    pub root: String,
+    /// The rules to move into each directory's `.gitignore`, rewritten relative to it; the
+    /// directories are relative to the root, in the order they first appear.
+    pub files: Vec<(String, Vec<String>)>,
+    /// Rules that could have been moved, but were kept because a later rule in the root file
+    /// might override them.
+    pub kept: Vec<String>,
+}
+
+/// Split the directory-specific rules of a root `.gitignore` out into per-directory files.
+///
+/// A rule is directory-specific when it is anchored below a literal directory (`/a/b/*.log`,
+/// `a/**/tmp`); it moves to the `.gitignore` of the deepest such directory for which `is_dir`
+/// holds. Rules in nested files take precedence over the root file's, so a rule stays where it
+/// is if a later root rule, or a later rule moving to a directory above its own, could also
+/// match below its directory. The moved rules should go before any existing rules of their new
+/// files, for the same reason.
+pub fn scatter<F: Fn(&str) -> bool>(root: &str, is_dir: F) -> Scattered {
+    let lines: Vec<&str> = root.lines().collect();
+    let mut moved = vec![None; lines.len()];
+    let mut kept = vec![];
+    // The literal path prefixes of the later rules staying in the root file, `None` standing for
+    // a rule that can match anywhere
+    let mut later: Vec<Option<Vec<&str>>> = vec![];
+    // The directories the later rules move to, with their literal path prefixes
+    let mut shallower: Vec<(Vec<&str>, Option<Vec<&str>>)> = vec![];
+
+    for (i, line) in lines.iter().enumerate().rev() {
+        let rule = match parse_rule(line) {
+            Some(rule) => rule,
+            None => continue,
+        };
+        let components: Vec<&str> = rule.pattern.split('/').collect();
+        let scope = if rule.anchored {
+            Some(literal_prefix(&components))
+        } else {
+            None
+        };
+
+        // The last component is what the rule matches, not a directory to move it to
+        let target = scope.as_ref().and_then(|scope| {
+            (1..=scope.len().min(components.len() - 1))
+                .rev()
+                .find(|&n| is_dir(&components[..n].join("/")))
+        });
+
+        // A rule in a deeper file would now take precedence over a later one it used to yield to
+        let blocked = |n: usize| {
+            let dir = &components[..n];
+            later.iter().any(|later| overlaps(later, dir))
+                || shallower.iter().any(|(to, scope)| {
+                    dir.len() > to.len() && dir.starts_with(to) && overlaps(scope, dir)
+                })
+        };
+
+        match target {
+            Some(n) if !blocked(n) => {
+                let rest = components[n..].join("/");
+                let mut text = String::new();
+                if rule.negated {
+                    text.push('!');
+                }
+                match rest.strip_prefix("**/") {
+                    Some(rest) => text.push_str(rest),
+                    None if rest.contains('/') => text.push_str(&rest),
+                    None => {
+                        text.push('/');
+                        text.push_str(&rest);
+                    },
+                }
+                if rule.dir_only {
+                    text.push('/');
+                }
+                moved[i] = Some((components[..n].join("/"), text));
+                shallower.push((components[..n].to_vec(), scope));
+            },
+            target => {
+                if target.is_some() {
+                    kept.push(line.trim().to_string());
+                }
+                later.push(scope);
+            },
+        }
+    }
+    kept.reverse();
+
+    let mut scattered = Scattered {
+        kept,
+        ..Scattered::default()
+    };
+
+    // Drop the comments (and the blank line before them) of sections that are moved entirely
+    let mut dropped = vec![false; lines.len()];
+    let mut i = 0;
+    while i < lines.len() {
+        let start = i;
+        while i < lines.len() && lines[i].trim().starts_with('#') {
+            i += 1;
+        }
+        let patterns = i;
+        while i < lines.len() && parse_rule(lines[i]).is_some() {
+            i += 1;
+        }
+        if patterns > start && i > patterns && moved[patterns..i].iter().all(Option::is_some) {
+            let blank = start > 0 && lines[start - 1].trim().is_empty();
+            dropped[start - usize::from(blank)..patterns].fill(true);
+        }
+        if i == start {
+            i += 1;
+        }
+    }
+
+    for ((line, moved), dropped) in lines.iter().zip(moved).zip(dropped) {
+        match moved {
+            None if dropped => {},
+            None => {
+                scattered.root.push_str(line);
+                scattered.root.push('\n');
+            },
+            Some((dir, rule)) => match scattered.files.iter_mut().find(|(d, _)| *d == dir) {
+                Some((_, rules)) => rules.push(rule),
+                None => scattered.files.push((dir, vec![rule])),
+            },
+        }
+    }
+
+    scattered
+}
+
+// The leading components without wildcards
+pub(crate) fn literal_prefix<'a>(components: &[&'a str]) -> Vec<&'a str> {
+    components
+        .iter()
+        .copied()
+        .take_while(|c| !c.contains(['*', '?', '[', '\\']))
+        .collect()
+}
+
+// Whether a rule with the given literal path prefix could match below `dir`
+pub(crate) fn overlaps(scope: &Option<Vec<&str>>, dir: &[&str]) -> bool {
+    match scope {
+        None => true,
+        Some(prefix) => prefix.starts_with(dir) || dir.starts_with(prefix),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flattened() {
+        let nested = [
+            ("a", "*.log\n# c\n!keep\n"),
+            ("/b/", "/x/\n"),
+            ("c", "# only\n"),
+        ];
+        assert_eq!(
+            flatten(&nested),
+            "# From a/.gitignore\n/a/**/*.log\n!/a/**/keep\n# From b/.gitignore\n/b/x/\n"
+        );
+    }
+
+    #[test]
+    fn scattered() {
+        let root = "*.o\n\n# Logs\n/a/b/*.log\n/a/tmp/\n\n# C\nc/**/x\n/c/keep\n";
+        let scattered = scatter(root, |dir| ["a", "a/b", "c"].contains(&dir));
+        assert_eq!(scattered.root, "*.o\n");
+        assert_eq!(
+            scattered.files,
+            [
+                (String::from("a/b"), vec![String::from("/*.log")]),
+                (String::from("a"), vec![String::from("/tmp/")]),
+                (
+                    String::from("c"),
+                    vec![String::from("x"), String::from("/keep")]
+                ),
+            ]
+        );
+        assert!(scattered.kept.is_empty());
+    }
+
+    #[test]
+    fn overridden() {
+        let scattered = scatter("/a/*.log\n*.log\n", |dir| dir == "a");
+        assert_eq!(scattered.root, "/a/*.log\n*.log\n");
+        assert!(scattered.files.is_empty());
+        assert_eq!(scattered.kept, ["/a/*.log"]);
+
+        let scattered = scatter("# B\n/a/b/*.log\n/a/*/x\n", |dir| dir == "a/b");
+        assert_eq!(scattered.root, "# B\n/a/b/*.log\n/a/*/x\n");
+        assert_eq!(scattered.kept, ["/a/b/*.log"]);
+    }
+
+    #[test]
+    fn precedence() {
+        let root = "/a/b/*.log\n!/a/**/keep.log\n/a/*/x\n/a/c/*.tmp\n";
+        let scattered = scatter(root, |dir| ["a", "a/b", "a/c"].contains(&dir));
+        assert_eq!(scattered.root, "/a/b/*.log\n");
+        assert_eq!(
+            scattered.files,
+            [
+                (
+                    String::from("a"),
+                    vec![String::from("!keep.log"), String::from("*/x")]
+                ),
+                (String::from("a/c"), vec![String::from("/*.tmp")]),
+            ]
+        );
+        assert_eq!(scattered.kept, ["/a/b/*.log"]);
+    }
+}
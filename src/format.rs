@@ -0,0 +1,180 @@
+//! A common interface to the ignore file formats this crate can read and write.
+//!
+//! Each [`Format`] provides the conversions it supports from the [`import`](crate::import) and
+//! [`export`](crate::export) modules; adding a format means adding an implementation and listing
+//! it in [`FORMATS`].
+
+use crate::{docker, export, import, import::Conversion, Result};
+use std::io;
+
+/// An ignore file format.
+pub trait Format: Sync {
+    /// The name of the format, as accepted by [`find`].
+    fn name(&self) -> &'static str;
+
+    /// A short description of the format.
+    fn description(&self) -> &'static str;
+
+    /// How the format's semantics differ from gitignore's, one note per entry.
+    fn notes(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Parse the contents of a file into gitignore patterns relative to its directory.
+    fn parse(&self, _text: &str) -> Result<Conversion> {
+        Err(unsupported(self.name(), "read"))
+    }
+
+    /// Serialize gitignore rules into the format.
+    ///
+    /// `rules` is a list of `(base, text)` pairs in increasing order of precedence, where `base`
+    /// is the directory the patterns are relative to (as listed by `sources`). Returns
+    /// the text and the rules that could not be expressed.
+    fn serialize(&self, _rules: &[(String, String)]) -> Result<(String, Vec<String>)> {
+        Err(unsupported(self.name(), "written"))
+    }
+}
+
+/// Git's own format.
+pub struct Gitignore;
+
+/// Docker build context exclusions.
+pub struct Dockerignore;
+
+/// Mercurial's `.hgignore`.
+pub struct Hgignore;
+
+/// rsync filter rules.
+pub struct Rsync;
+
+/// GNU tar exclusion patterns.
+pub struct Tar;
+
+/// Every supported format.
+pub const FORMATS: &[&dyn Format] = &[&Gitignore, &Dockerignore, &Hgignore, &Rsync, &Tar];
+
+/// Look up a format by name.
+pub fn find(name: &str) -> Option<&'static dyn Format> {
+    FORMATS.iter().copied().find(|format| format.name() == name)
+}
+
+fn unsupported(name: &str, action: &str) -> Box<dyn std::error::Error> {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("The {} format cannot be {}", name, action),
+    )
+    .into()
+}
+
+impl Format for Gitignore {
+    fn name(&self) -> &'static str {
+        "gitignore"
+    }
+
+    fn description(&self) -> &'static str {
+        "a single root-level '.gitignore'"
+    }
+
+    fn parse(&self, text: &str) -> Result<Conversion> {
+        Ok(Conversion {
+            patterns: text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(String::from)
+                .collect(),
+            untranslated: vec![],
+        })
+    }
+
+    fn serialize(&self, rules: &[(String, String)]) -> Result<(String, Vec<String>)> {
+        Ok((export::to_gitignore(rules), vec![]))
+    }
+}
+
+impl Format for Dockerignore {
+    fn name(&self) -> &'static str {
+        "dockerignore"
+    }
+
+    fn description(&self) -> &'static str {
+        "a '.dockerignore' for the repository root as build context"
+    }
+
+    fn notes(&self) -> &'static [&'static str] {
+        &[
+            "directory-only patterns match files too",
+            "negated patterns that may reach into excluded directories re-include files there",
+        ]
+    }
+
+    fn parse(&self, text: &str) -> Result<Conversion> {
+        Ok(import::from_dockerignore(text))
+    }
+
+    fn serialize(&self, rules: &[(String, String)]) -> Result<(String, Vec<String>)> {
+        Ok(docker::to_dockerignore_rules(rules))
+    }
+}
+
+impl Format for Hgignore {
+    fn name(&self) -> &'static str {
+        "hgignore"
+    }
+
+    fn description(&self) -> &'static str {
+        "a Mercurial '.hgignore'"
+    }
+
+    fn notes(&self) -> &'static [&'static str] {
+        &[
+            "negated rules cannot be expressed",
+            "directory-only patterns match files too",
+            "only regular expressions that globs can express are read",
+        ]
+    }
+
+    fn parse(&self, text: &str) -> Result<Conversion> {
+        Ok(import::from_hgignore(text))
+    }
+
+    fn serialize(&self, rules: &[(String, String)]) -> Result<(String, Vec<String>)> {
+        Ok(export::to_hgignore(rules))
+    }
+}
+
+impl Format for Rsync {
+    fn name(&self) -> &'static str {
+        "rsync"
+    }
+
+    fn description(&self) -> &'static str {
+        "a filter file for 'rsync --exclude-from=FILE'"
+    }
+
+    fn serialize(&self, rules: &[(String, String)]) -> Result<(String, Vec<String>)> {
+        Ok((export::to_rsync(rules), vec![]))
+    }
+}
+
+impl Format for Tar {
+    fn name(&self) -> &'static str {
+        "tar"
+    }
+
+    fn description(&self) -> &'static str {
+        "a pattern file for GNU 'tar --exclude-from=FILE'"
+    }
+
+    fn notes(&self) -> &'static [&'static str] {
+        &[
+            "negated rules cannot be expressed",
+            "directory-only patterns match files too",
+            "anchored patterns can also match deeper in the tree",
+        ]
+    }
+
+    fn serialize(&self, rules: &[(String, String)]) -> Result<(String, Vec<String>)> {
+        Ok(export::to_tar(rules))
+    }
+}
@@ -0,0 +1,150 @@
+//! Downloads, with bounded timeouts and a few retries.
+//!
+//! Requests go through the proxy named by the `ignore.proxy` config or, failing that, by the
+//! `https_proxy`, `http_proxy` and `all_proxy` environment variables (or their upper case
+//! forms), except for the hosts listed in `no_proxy`. An empty `ignore.proxy` disables proxies.
+
+mod auth;
+
+use crate::repo;
+use std::{env, error::Error, io::Read, thread, time::Duration};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Attempts per URL, and the delay before the first retry, doubled after each one
+const ATTEMPTS: u32 = 3;
+const BACKOFF: Duration = Duration::from_millis(500);
+
+/// Download `url`, retrying after failures that might not happen again (such as timeouts and
+/// server errors); `None` if there is nothing there (404).
+pub fn get(url: &str) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+    get_as(url, None, false)
+}
+
+/// Download `url` as [`get`] does, but only over HTTPS, refusing redirects to anything else.
+pub fn get_https(url: &str) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+    if !url.starts_with("https://") {
+        return Err(crate::Error::Network {
+            url: url.to_string(),
+            reason: String::from("only HTTPS is allowed"),
+        }
+        .into());
+    }
+    get_as(url, None, true)
+}
+
+/// Download `url` as [`get`] does, from a server that may want credentials: a token in the
+/// `GIT_IGNORE_TOKEN` environment variable, or else those a git credential helper (if one is
+/// configured) or the netrc file (`~/.netrc`, or the one `NETRC` names) has for it.
+pub fn get_authenticated(url: &str) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+    get_as(url, auth::authorization(url)?, false)
+}
+
+fn get_as(
+    url: &str,
+    authorization: Option<String>,
+    https_only: bool,
+) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+    let mut agent = ureq::AgentBuilder::new()
+        .timeout_connect(CONNECT_TIMEOUT)
+        .timeout_read(READ_TIMEOUT)
+        .https_only(https_only);
+    if let Some(proxy) = proxy(url)? {
+        agent = agent.proxy(proxy);
+    }
+    let agent = agent.build();
+
+    let mut delay = BACKOFF;
+    let mut attempt = 1;
+
+    loop {
+        let mut request = agent.get(url);
+        if let Some(authorization) = &authorization {
+            request = request.set("Authorization", authorization);
+        }
+        match request.call() {
+            Ok(response) => {
+                let mut body = vec![];
+                response.into_reader().read_to_end(&mut body)?;
+                return Ok(Some(body));
+            },
+            Err(ureq::Error::Status(404, _)) => return Ok(None),
+            Err(error) if attempt < ATTEMPTS && transient(&error) => {
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            },
+            Err(error) => {
+                // The message starts with the URL, already given
+                let reason = error.to_string();
+                let reason = reason
+                    .strip_prefix(&format!("{}: ", url))
+                    .unwrap_or(&reason);
+                return Err(crate::Error::Network {
+                    url: url.to_string(),
+                    reason: reason.to_string(),
+                }
+                .into());
+            },
+        }
+    }
+}
+
+fn proxy(url: &str) -> Result<Option<ureq::Proxy>, Box<dyn Error>> {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    let host = rest.split(['/', ':']).next().unwrap_or(rest);
+    if bypass(host) {
+        return Ok(None);
+    }
+
+    let proxy = match repo::config("ignore.proxy")? {
+        Some(proxy) => proxy,
+        None => {
+            let vars = [&format!("{}_proxy", scheme), "all_proxy"];
+            let value = vars
+                .iter()
+                .flat_map(|var| [var.to_string(), var.to_uppercase()])
+                .find_map(|var| env::var(var).ok().filter(|value| !value.is_empty()));
+            match value {
+                Some(value) => value,
+                None => return Ok(None),
+            }
+        },
+    };
+
+    if proxy.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(ureq::Proxy::new(proxy)?))
+    }
+}
+
+// Whether `no_proxy` lists the host, or a domain it is in
+fn bypass(host: &str) -> bool {
+    let list = match env::var("no_proxy").or_else(|_| env::var("NO_PROXY")) {
+        Ok(list) => list,
+        Err(_) => return false,
+    };
+
+    list.split(',')
+        .map(|entry| entry.trim())
+        .map(|entry| entry.split(':').next().unwrap_or(entry))
+        .map(|entry| entry.trim_start_matches('.'))
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            entry == "*"
+                || host.eq_ignore_ascii_case(entry)
+                || host
+                    .to_ascii_lowercase()
+                    .ends_with(&format!(".{}", entry.to_ascii_lowercase()))
+        })
+}
+
+// Failures that might not happen again
+fn transient(error: &ureq::Error) -> bool {
+    match error {
+        ureq::Error::Status(code, _) => *code == 429 || *code >= 500,
+        ureq::Error::Transport(_) => true,
+    }
+}
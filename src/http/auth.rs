@@ -0,0 +1,118 @@
+//! Credentials for downloads from private servers: a token in the `GIT_IGNORE_TOKEN`
+//! environment variable, otherwise those a git credential helper has for the URL, otherwise
+//! those the netrc file (`~/.netrc`, or the one `NETRC` names) has for its host.
+
+use crate::{repo, Result};
+use std::{
+    env, fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The value of the `Authorization` header for `url`, if there are credentials for it.
+pub fn authorization(url: &str) -> Result<Option<String>> {
+    if let Some(token) = env::var("GIT_IGNORE_TOKEN").ok().filter(|t| !t.is_empty()) {
+        return Ok(Some(format!("Bearer {}", token)));
+    }
+
+    let (scheme, rest) = url.split_once("://").unwrap_or(("https", url));
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let credentials = match helper(scheme, host, path)? {
+        Some(credentials) => Some(credentials),
+        None => netrc(host.split(':').next().unwrap_or(host)),
+    };
+    Ok(credentials.map(|(user, password)| {
+        format!(
+            "Basic {}",
+            base64(format!("{}:{}", user, password).as_bytes())
+        )
+    }))
+}
+
+// Only with a helper configured, as git would otherwise ask for them
+fn helper(scheme: &str, host: &str, path: &str) -> Result<Option<(String, String)>> {
+    if repo::config("credential.helper")?.is_none() {
+        return Ok(None);
+    }
+
+    let mut child = Command::new("git")
+        .args(["credential", "fill"])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        write!(
+            stdin,
+            "protocol={}\nhost={}\npath={}\n\n",
+            scheme, host, path
+        )?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let output = String::from_utf8_lossy(&output.stdout);
+    let value = |key: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .map(str::to_string)
+    };
+    Ok(value("username").zip(value("password")))
+}
+
+// The login and password of the first entry for `host`, or of the default entry
+fn netrc(host: &str) -> Option<(String, String)> {
+    let file = match env::var_os("NETRC").filter(|file| !file.is_empty()) {
+        Some(file) => PathBuf::from(file),
+        None => dirs::home_dir()?.join(".netrc"),
+    };
+    let text = fs::read_to_string(file).ok()?;
+
+    let mut tokens = text.split_whitespace();
+    let mut matching = false;
+    let (mut login, mut password) = (None, None);
+    while let Some(token) = tokens.next() {
+        match token {
+            "machine" | "default" => {
+                if matching && password.is_some() {
+                    break;
+                }
+                matching = token == "default" || tokens.next() == Some(host);
+                login = None;
+                password = None;
+            },
+            "login" => login = tokens.next().filter(|_| matching),
+            "password" => password = tokens.next().filter(|_| matching),
+            _ => {},
+        }
+    }
+    if !matching {
+        return None;
+    }
+    Some((login.unwrap_or_default().to_string(), password?.to_string()))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &byte)| n | u32::from(byte) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(char::from(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize]));
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
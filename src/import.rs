@@ -0,0 +1,500 @@
+//! Conversion of other version control systems' ignore rules into gitignore patterns.
+
+/// The result of converting foreign ignore rules.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Conversion {
+    /// The equivalent gitignore patterns.
+    pub patterns: Vec<String>,
+    /// The rules that have no gitignore equivalent, with their (1-based) line numbers.
+    pub untranslated: Vec<(usize, String)>,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Syntax {
+    Regexp,
+    Glob,
+    RootGlob,
+}
+
+/// Convert the contents of a `.dockerignore` file.
+///
+/// Docker patterns are relative to the build context root, so they become anchored gitignore
+/// patterns, except those starting with `**/`, which match at any depth in both. Every rule can
+/// be translated, though Docker also re-includes negated files inside excluded directories,
+/// which git does not.
+pub fn from_dockerignore(text: &str) -> Conversion {
+    let mut conversion = Conversion::default();
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (negation, pattern) = match line.strip_prefix('!') {
+            Some(pattern) => ("!", pattern.trim_start()),
+            None => ("", line),
+        };
+        let pattern = pattern.trim_start_matches("./").trim_start_matches('/');
+        let pattern = pattern.trim_end_matches('/');
+        if pattern.is_empty() {
+            continue;
+        }
+
+        conversion.patterns.push(if pattern.starts_with("**/") {
+            format!("{}{}", negation, pattern)
+        } else {
+            format!("{}/{}", negation, pattern)
+        });
+    }
+
+    conversion
+}
+
+/// Convert the contents of a Mercurial `.hgignore` file.
+///
+/// Both `syntax: glob` and `syntax: regexp` sections are supported, as are per-line prefixes
+/// (`glob:`, `re:`, `path:`, `rootglob:`, `rootfilesin:` and friends). Regular expressions are
+/// translated when they only use constructs that globs can express, such as literals, `.*`,
+/// character classes and the usual anchoring idioms; everything else (and `include:` lines) is
+/// reported as untranslated.
+pub fn from_hgignore(text: &str) -> Conversion {
+    let mut conversion = Conversion::default();
+    let mut syntax = Syntax::Regexp;
+
+    for (n, raw) in text.lines().enumerate() {
+        let line = strip_comment(raw);
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix("syntax:") {
+            match name.trim() {
+                "re" | "regexp" => syntax = Syntax::Regexp,
+                "glob" => syntax = Syntax::Glob,
+                "rootglob" => syntax = Syntax::RootGlob,
+                _ => conversion.untranslated.push((n + 1, raw.to_string())),
+            }
+            continue;
+        }
+
+        let (kind, pattern) = match line.split_once(':') {
+            Some((kind, pattern)) if is_kind(kind) => (kind, pattern),
+            _ => match syntax {
+                Syntax::Regexp => ("re", line),
+                Syntax::Glob => ("glob", line),
+                Syntax::RootGlob => ("rootglob", line),
+            },
+        };
+
+        let patterns = match kind {
+            "re" | "regexp" | "relre" => regex_to_glob(pattern).map(|p| vec![escape_leading(&p)]),
+            "glob" | "relglob" => expand_braces(pattern).map(|globs| {
+                globs
+                    .into_iter()
+                    .map(|glob| {
+                        if glob.trim_end_matches('/').contains('/') && !glob.starts_with("**/") {
+                            format!("**/{}", glob)
+                        } else {
+                            escape_leading(&glob)
+                        }
+                    })
+                    .collect()
+            }),
+            "rootglob" => expand_braces(pattern)
+                .map(|globs| globs.into_iter().map(|glob| format!("/{}", glob)).collect()),
+            "path" | "relpath" => Some(vec![format!("/{}", escape_glob(pattern))]),
+            "rootfilesin" => {
+                let dir = escape_glob(pattern.trim_end_matches('/'));
+                Some(vec![format!("/{}/*", dir), format!("!/{}/*/", dir)])
+            },
+            _ => None,
+        };
+
+        match patterns {
+            Some(patterns) => conversion.patterns.extend(patterns),
+            None => conversion.untranslated.push((n + 1, raw.to_string())),
+        }
+    }
+
+    conversion
+}
+
+/// Convert the output of `svn propget -R svn:ignore` (or, with `inherited`, of
+/// `svn propget -R svn:global-ignores`) into anchored patterns.
+///
+/// The output lists each directory's property value as `PATH - PATTERN` followed by one pattern
+/// per line. `svn:ignore` only applies to a directory's immediate children (`sub - *.o` becomes
+/// `/sub/*.o`), while `svn:global-ignores` also applies to all descendants (`/sub/**/*.o`).
+pub fn from_svn(output: &str, inherited: bool) -> Conversion {
+    let mut conversion = Conversion::default();
+    let mut dir: Option<String> = None;
+
+    for (n, line) in output.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            dir = None;
+            continue;
+        }
+
+        let pattern = match (line.split_once(" - "), &dir) {
+            (Some((path, pattern)), None) => {
+                let path = path.replace('\\', "/");
+                let path = path.trim_start_matches("./").trim_matches('/');
+                dir = Some(if path == "." {
+                    String::new()
+                } else {
+                    path.to_string()
+                });
+                pattern.trim()
+            },
+            (_, Some(_)) => line.trim(),
+            (None, None) => {
+                conversion.untranslated.push((n + 1, line.to_string()));
+                continue;
+            },
+        };
+        if pattern.is_empty() {
+            continue;
+        }
+        if pattern.contains('/') {
+            conversion.untranslated.push((n + 1, line.to_string()));
+            continue;
+        }
+
+        let dir = dir.as_deref().unwrap_or_default();
+        let mut glob = String::new();
+        if !dir.is_empty() {
+            glob.push('/');
+            glob.push_str(dir);
+        }
+        match (inherited, dir.is_empty()) {
+            (true, true) => {},
+            (true, false) => glob.push_str("/**/"),
+            (false, _) => glob.push('/'),
+        }
+        let pattern = if glob.is_empty() {
+            escape_leading(pattern)
+        } else {
+            pattern.to_string()
+        };
+        glob.push_str(&pattern);
+
+        conversion.patterns.push(glob);
+    }
+
+    conversion
+}
+
+fn is_kind(kind: &str) -> bool {
+    matches!(
+        kind,
+        "re" | "regexp"
+            | "relre"
+            | "glob"
+            | "relglob"
+            | "rootglob"
+            | "path"
+            | "relpath"
+            | "rootfilesin"
+            | "include"
+            | "subinclude"
+            | "listfile"
+            | "listfile0"
+    )
+}
+
+// Mercurial comments start at an unescaped '#'; '\#' is a literal '#'
+fn strip_comment(line: &str) -> String {
+    let mut out = String::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'#') => {
+                out.push('#');
+                chars.next();
+            },
+            '#' => break,
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+fn escape_glob(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        if matches!(c, '*' | '?' | '[' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+
+    escape_leading(&out)
+}
+
+// A leading '#' or '!' would make a comment or a negation
+fn escape_leading(pattern: &str) -> String {
+    if pattern.starts_with('#') || pattern.starts_with('!') {
+        format!("\\{}", pattern)
+    } else {
+        pattern.to_string()
+    }
+}
+
+// Expand a single level of `{a,b}` alternatives; nested braces are not supported
+fn expand_braces(glob: &str) -> Option<Vec<String>> {
+    let start = match glob.find('{') {
+        None => return Some(vec![glob.to_string()]),
+        Some(start) => start,
+    };
+    let end = start + glob[start..].find('}')?;
+    let (prefix, rest) = (&glob[..start], &glob[end + 1..]);
+    let alternatives = &glob[start + 1..end];
+    if alternatives.contains('{') {
+        return None;
+    }
+
+    let mut globs = vec![];
+    for alternative in alternatives.split(',') {
+        for tail in expand_braces(rest)? {
+            globs.push(format!("{}{}{}", prefix, alternative, tail));
+        }
+    }
+
+    Some(globs)
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Anchor {
+    // `^`
+    Root,
+    // `(^|/)` / `($|/)`
+    Component,
+    // no anchor
+    None,
+}
+
+// Translate a regular expression that is searched for in slash-separated paths into a glob
+fn regex_to_glob(re: &str) -> Option<String> {
+    let (start, re) = if let Some(re) = re.strip_prefix('^') {
+        (Anchor::Root, re)
+    } else if let Some(re) = re
+        .strip_prefix("(^|/)")
+        .or_else(|| re.strip_prefix("(?:^|/)"))
+    {
+        (Anchor::Component, re)
+    } else if let Some(re) = re.strip_prefix('/') {
+        (Anchor::Component, re)
+    } else {
+        (Anchor::None, re)
+    };
+
+    let (end, re) = if let Some(re) = re
+        .strip_suffix("($|/)")
+        .or_else(|| re.strip_suffix("(/|$)"))
+        .or_else(|| re.strip_suffix("(?:$|/)"))
+        .or_else(|| re.strip_suffix("(?:/|$)"))
+    {
+        (Anchor::Component, re)
+    } else if let Some(re) = re.strip_suffix('$').filter(|re| !re.ends_with('\\')) {
+        (Anchor::Root, re)
+    } else {
+        (Anchor::None, re)
+    };
+
+    let body = translate_regex_body(re)?;
+    if body.is_empty() {
+        return None;
+    }
+
+    let mut glob = match start {
+        Anchor::Root => format!("/{}", body),
+        _ if body.contains('/') => format!("**/{}", body),
+        Anchor::Component => body,
+        Anchor::None if body.starts_with('*') => body,
+        Anchor::None => format!("*{}", body),
+    };
+    if end == Anchor::None && !glob.ends_with('*') && !glob.ends_with('/') {
+        glob.push('*');
+    }
+
+    Some(glob)
+}
+
+fn translate_regex_body(re: &str) -> Option<String> {
+    let chars: Vec<char> = re.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let next = chars.get(i + 1).copied();
+        match chars[i] {
+            '\\' => {
+                let c = next?;
+                match c {
+                    'd' => out.push_str("[0-9]"),
+                    c if c.is_ascii_alphanumeric() => return None,
+                    c => out.push_str(&escape_glob(&c.to_string())),
+                }
+                i += 1;
+            },
+            '.' => match next {
+                // After a slash, `.*` may span directories
+                Some('*') if out.ends_with('/') => {
+                    match chars.get(i + 2) {
+                        None | Some('/') => out.push_str("**"),
+                        _ => out.push_str("**/*"),
+                    }
+                    i += 1;
+                },
+                Some('*') => {
+                    out.push('*');
+                    i += 1;
+                },
+                Some('+') => {
+                    out.push_str("?*");
+                    i += 1;
+                },
+                Some('?') | Some('{') => return None,
+                _ => out.push('?'),
+            },
+            '[' => {
+                let end = i + 1 + chars[i + 1..].iter().skip(1).position(|&c| c == ']')? + 1;
+                let class: String = chars[i + 1..end].iter().collect();
+                match (class.as_str(), chars.get(end + 1)) {
+                    ("^/", Some('*')) => out.push('*'),
+                    ("^/", Some('+')) => out.push_str("?*"),
+                    (_, Some('*')) | (_, Some('+')) | (_, Some('?')) | (_, Some('{')) => {
+                        return None
+                    },
+                    (class, _) => {
+                        out.push('[');
+                        match class.strip_prefix('^') {
+                            Some(class) => {
+                                out.push('!');
+                                out.push_str(class);
+                            },
+                            None => out.push_str(class),
+                        }
+                        out.push(']');
+                    },
+                }
+                i = if matches!(chars.get(end + 1), Some('*') | Some('+')) {
+                    end + 1
+                } else {
+                    end
+                };
+            },
+            '*' | '+' | '?' | '{' | '}' | '(' | ')' | '|' | '^' | '$' => return None,
+            c => {
+                if matches!(next, Some('*') | Some('+') | Some('?') | Some('{')) {
+                    return None;
+                }
+                out.push(c);
+            },
+        }
+        i += 1;
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hgignore() {
+        let text = r"# Generated files
+syntax: glob
+*.pyc
+build/*.o
+\#notes
+{a,b}.tmp
+*.swp  # editors
+rootglob:dist/**
+path:docs/[draft]
+rootfilesin:logs
+include:other.hgignore
+syntax: regexp
+\.orig$
+^out/.*\.log$
+(^|/)node_modules($|/)
+^[^/]*\.bak$
+core\.\d
+foo(bar|baz)
+syntax: bogus
+";
+        let conversion = from_hgignore(text);
+        assert_eq!(
+            conversion.patterns,
+            [
+                "*.pyc",
+                "**/build/*.o",
+                "\\#notes",
+                "a.tmp",
+                "b.tmp",
+                "*.swp",
+                "/dist/**",
+                "/docs/\\[draft]",
+                "/logs/*",
+                "!/logs/*/",
+                "*.orig",
+                "/out/**/*.log",
+                "node_modules",
+                "/*.bak",
+                "*core.[0-9]*",
+            ]
+        );
+        assert_eq!(
+            conversion.untranslated,
+            [
+                (11, String::from("include:other.hgignore")),
+                (18, String::from("foo(bar|baz)")),
+                (19, String::from("syntax: bogus")),
+            ]
+        );
+    }
+
+    #[test]
+    fn regexps() {
+        assert_eq!(regex_to_glob("^src/.*$").as_deref(), Some("/src/**"));
+        assert_eq!(regex_to_glob("/tmp/").as_deref(), Some("**/tmp/"));
+        assert_eq!(regex_to_glob("^a/[^/]+/b$").as_deref(), Some("/a/?*/b"));
+        assert_eq!(regex_to_glob("^[ab]c$").as_deref(), Some("/[ab]c"));
+        assert_eq!(regex_to_glob("^[^a]c$").as_deref(), Some("/[!a]c"));
+        assert_eq!(regex_to_glob("\\$x$").as_deref(), Some("*$x"));
+        assert_eq!(regex_to_glob("a+"), None);
+        assert_eq!(regex_to_glob("[0-9]+"), None);
+        assert_eq!(regex_to_glob("\\w"), None);
+        assert_eq!(regex_to_glob("^$"), None);
+    }
+
+    #[test]
+    fn svn() {
+        let output = ". - *.o\nbuild\n\nsub\\dir - *.tmp\n#x\na/b\n\nstray\n";
+        let conversion = from_svn(output, false);
+        assert_eq!(
+            conversion.patterns,
+            ["/*.o", "/build", "/sub/dir/*.tmp", "/sub/dir/#x"]
+        );
+        assert_eq!(
+            conversion.untranslated,
+            [(6, String::from("a/b")), (8, String::from("stray"))]
+        );
+
+        let conversion = from_svn(". - !keep\n\nsub - *.tmp\n", true);
+        assert_eq!(conversion.patterns, ["\\!keep", "/sub/**/*.tmp"]);
+        assert!(conversion.untranslated.is_empty());
+    }
+
+    #[test]
+    fn dockerignore() {
+        let conversion = from_dockerignore("# c\n./build/\n**/*.log\n! /keep\n\n/\n");
+        assert_eq!(conversion.patterns, ["/build", "**/*.log", "!/keep"]);
+    }
+}
@@ -0,0 +1,209 @@
+//! A listing of a working tree kept under `.git`, so that listing it again only reads the
+//! directories that changed since.
+
+use crate::{file::atomic_path, Error, Repo, Result};
+use atomicwrites::{AllowOverwrite, AtomicFile};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    mem,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const FILE_NAME: &str = "git-ignore-index";
+const HEADER: &[u8] = b"git-ignore index 1\n";
+
+// A directory changed this recently might change again without its time moving, on file
+// systems with coarse timestamps, so its listing is read again next time
+const RACY: Duration = Duration::from_secs(2);
+
+/// The files and directories of a working tree, except for `.git`.
+#[derive(Clone, Debug, Default)]
+pub struct Index {
+    dirs: BTreeMap<String, Dir>,
+}
+
+#[derive(Clone, Debug)]
+struct Dir {
+    modified: Option<SystemTime>,
+    // Names, and whether each is a directory
+    entries: Vec<(String, bool)>,
+}
+
+impl Index {
+    /// Load the index of the working tree of `repo`, bring it up to date, and save it again if
+    /// anything changed.
+    ///
+    /// Saving is best-effort: an index that can't be written is still returned.
+    pub fn load(repo: &Repo) -> Result<Self> {
+        let root = repo
+            .workdir()
+            .ok_or_else(|| Error::BareRepo(repo.git_dir().to_path_buf()))?;
+        let path = repo.git_dir().join(FILE_NAME);
+
+        let mut index = match fs::read(&path) {
+            Ok(bytes) => Self::parse(&bytes).unwrap_or_default(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e.into()),
+        };
+        if index.refresh(root)? {
+            let _ = index.save(&path);
+        }
+
+        Ok(index)
+    }
+
+    /// Every entry below `dir` (relative to the root, with `/` separators, or empty for the whole
+    /// tree), as a path relative to `dir` and whether it is a directory, with each directory
+    /// coming before what is in it.
+    pub fn entries<'a>(&'a self, dir: &'a str) -> impl Iterator<Item = (String, bool)> + 'a {
+        let dir = dir.trim_matches('/');
+        self.dirs
+            .iter()
+            .filter_map(move |(path, listing)| {
+                let prefix = if dir.is_empty() {
+                    path.as_str()
+                } else if path == dir {
+                    ""
+                } else {
+                    path.strip_prefix(dir)?.strip_prefix('/')?
+                };
+                Some((prefix, listing))
+            })
+            .flat_map(|(prefix, listing)| {
+                listing.entries.iter().map(move |(name, is_dir)| {
+                    let path = if prefix.is_empty() {
+                        name.clone()
+                    } else {
+                        format!("{}/{}", prefix, name)
+                    };
+                    (path, *is_dir)
+                })
+            })
+    }
+
+    // Read the directories below `root` whose time changed, reusing the listings of the others;
+    // returns whether any listing changed
+    fn refresh(&mut self, root: &Path) -> io::Result<bool> {
+        let start = SystemTime::now();
+        let mut old = mem::take(&mut self.dirs);
+        let mut changed = false;
+        let mut pending = vec![String::new()];
+
+        while let Some(dir) = pending.pop() {
+            let path = root.join(&dir);
+            let modified = match fs::metadata(&path).and_then(|m| m.modified()) {
+                Ok(modified) => modified,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+
+            let entries = match old.remove(&dir) {
+                Some(cached) if cached.modified == Some(modified) => cached.entries,
+                _ => {
+                    changed = true;
+                    read(&path)?
+                },
+            };
+            for (name, _) in entries.iter().filter(|(_, is_dir)| *is_dir) {
+                pending.push(if dir.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}/{}", dir, name)
+                });
+            }
+
+            let trusted = start.duration_since(modified).is_ok_and(|age| age > RACY);
+            self.dirs.insert(
+                dir,
+                Dir {
+                    modified: Some(modified).filter(|_| trusted),
+                    entries,
+                },
+            );
+        }
+
+        Ok(changed || !old.is_empty())
+    }
+
+    // A record per directory: its path, its time, how many entries it has, and the entries
+    // (directories with a trailing '/'), each followed by a NUL
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let mut bytes = HEADER.to_vec();
+        for (dir, listing) in &self.dirs {
+            let modified = listing
+                .modified
+                .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+                .map_or(String::new(), |m| {
+                    format!("{}.{:09}", m.as_secs(), m.subsec_nanos())
+                });
+            bytes.extend_from_slice(dir.as_bytes());
+            bytes.push(0);
+            bytes.extend_from_slice(modified.as_bytes());
+            bytes.push(0);
+            bytes.extend_from_slice(listing.entries.len().to_string().as_bytes());
+            bytes.push(0);
+            for (name, is_dir) in &listing.entries {
+                bytes.extend_from_slice(name.as_bytes());
+                if *is_dir {
+                    bytes.push(b'/');
+                }
+                bytes.push(0);
+            }
+        }
+
+        AtomicFile::new(atomic_path(path), AllowOverwrite)
+            .write(|f| f.write_all(&bytes))
+            .map_err(|e| match e {
+                atomicwrites::Error::Internal(e) | atomicwrites::Error::User(e) => e,
+            })
+    }
+
+    // `None` for an index from another version, or a damaged one
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        let text = std::str::from_utf8(bytes.strip_prefix(HEADER)?).ok()?;
+        let mut fields = text.split_terminator('\0');
+        let mut dirs = BTreeMap::new();
+
+        while let Some(dir) = fields.next() {
+            let modified = match fields.next()? {
+                "" => None,
+                modified => {
+                    let (secs, nanos) = modified.split_once('.')?;
+                    let since = Duration::new(secs.parse().ok()?, nanos.parse().ok()?);
+                    Some(UNIX_EPOCH + since)
+                },
+            };
+            let count: usize = fields.next()?.parse().ok()?;
+            let mut entries = vec![];
+            for _ in 0..count {
+                let name = fields.next()?;
+                entries.push(match name.strip_suffix('/') {
+                    Some(name) => (name.to_string(), true),
+                    None => (name.to_string(), false),
+                });
+            }
+            dirs.insert(dir.to_string(), Dir { modified, entries });
+        }
+
+        Some(Self { dirs })
+    }
+}
+
+// The entries of the directory at `path`, but for '.git' and names that aren't UTF-8
+fn read(path: &Path) -> io::Result<Vec<(String, bool)>> {
+    let mut entries = vec![];
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let name = match entry.file_name().into_string() {
+            Ok(name) if name != ".git" => name,
+            _ => continue,
+        };
+        let is_dir = entry.file_type()?.is_dir();
+        entries.push((name, is_dir));
+    }
+    entries.sort();
+    Ok(entries)
+}
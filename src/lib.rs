@@ -0,0 +1,108 @@
+//! Easily add entries to the various git ignore files.
+//!
+//! The [`git-ignore`](https://git.dragonma.us/rust/boring) command is a thin wrapper around this
+//! crate: resolve a [`Target`], [`load`](IgnoreFile::load) its [`IgnoreFile`],
+//! [`merge`](IgnoreFile::merge) the new patterns in, and [`save`](IgnoreFile::save) it.
+//! [`IgnoreOp`] bundles those steps behind a builder.
+//!
+//! Everything that touches the filesystem or a repository needs the `fs` feature, which is
+//! enabled by each of the backend features (`git2`, `gix`, `native`). Without them only the pure
+//! core (such as [`merge()`]) is built, which also compiles to `wasm32-unknown-unknown`; the
+//! `wasm` feature adds JavaScript bindings for it. The `net` feature (on by default) lets
+//! [`template`]s be downloaded.
+
+#[cfg(feature = "fs")]
+extern crate atomicwrites;
+
+mod attributes;
+mod block;
+pub mod bytes;
+#[cfg(feature = "fs")]
+pub mod cache;
+mod classify;
+#[cfg(feature = "fs")]
+pub mod cleanup;
+mod compare;
+#[cfg(feature = "fs")]
+mod compile;
+mod conflict;
+#[cfg(feature = "fs")]
+pub mod date;
+mod dedupe;
+pub mod derive;
+mod diff;
+mod docker;
+mod error;
+pub mod export;
+#[cfg(feature = "fs")]
+mod file;
+mod flatten;
+pub mod format;
+#[cfg(feature = "net")]
+pub mod http;
+pub mod import;
+#[cfg(feature = "fs")]
+mod index;
+mod lint;
+mod merge;
+mod normalize;
+mod npm;
+#[cfg(feature = "fs")]
+mod op;
+mod pattern;
+mod rename;
+#[cfg(feature = "fs")]
+pub mod repo;
+mod rewrite;
+mod set;
+#[cfg(feature = "fs")]
+mod sources;
+mod sparse;
+#[cfg(feature = "fs")]
+mod target;
+pub mod template;
+#[cfg(feature = "fs")]
+pub mod walk;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use attributes::merge_attributes;
+pub use block::{blocks, set_block};
+pub use classify::{classify, group, Category};
+pub use compare::{compare, Comparison};
+#[cfg(feature = "fs")]
+pub use compile::{compile, compile_allowing_unverified};
+pub use conflict::{has_conflicts, resolve_conflicts};
+pub use dedupe::dedupe;
+pub use derive::derive_rules;
+pub use diff::unified_diff;
+pub use docker::{to_dockerignore, to_dockerignore_rules};
+pub use error::Error;
+#[cfg(feature = "fs")]
+pub use file::{backups, has_lines, IgnoreFile, Newline};
+pub use flatten::{flatten, scatter, Scattered};
+pub use format::Format;
+#[cfg(feature = "fs")]
+pub use index::Index;
+pub use lint::{case_collisions, explain, lint, Lint};
+pub use merge::{
+    merge, merge_annotated, merge_file, merge_section, Comments, Dedup, MergeOptions, Negations,
+};
+pub use normalize::Normalization;
+pub use npm::missing_rules;
+#[cfg(feature = "fs")]
+pub use op::{IgnoreOp, Outcome};
+pub use pattern::{is_ignored, Pattern, Rules};
+pub use rename::{directory_renames, missing_dirs, rename_prefix};
+#[cfg(feature = "fs")]
+pub use repo::Repo;
+pub use rewrite::{ParseSubstitutionError, Substitution};
+pub use set::{set_op, SetOp};
+#[cfg(feature = "fs")]
+pub use sources::{sources, Level, Source};
+pub use sparse::{cone_patterns, lift_cone_restrictions};
+#[cfg(feature = "fs")]
+pub use target::{Kind, Target, Tool};
+
+/// The result type used throughout this crate; the failures it recognizes are [`Error`]s.
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
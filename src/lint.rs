@@ -0,0 +1,164 @@
+use crate::{compare::canonical, conflict::marker, export::parse_rule, Pattern};
+use std::collections::HashMap;
+
+/// A problem with a line of an ignore file; see [`lint`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Lint {
+    /// The number of the line, from 0.
+    pub line: usize,
+    /// What is wrong with it.
+    pub message: String,
+}
+
+/// Look for lines of the ignore file contents `text` that don't do what they seem to: conflict
+/// markers, patterns made redundant by a later equivalent one (see [`dedupe`](crate::dedupe())),
+/// unescaped trailing whitespace, `./` components, `\` used as a path separator, `**` next to
+/// other characters, and patterns that can't be compiled.
+pub fn lint(text: &str) -> Vec<Lint> {
+    let mut lints = vec![];
+    let mut last = HashMap::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let mut report = |message: String| lints.push(Lint { line: i, message });
+
+        if ['<', '=', '|', '>'].iter().any(|&c| marker(line, c)) {
+            report(String::from("Merge conflict marker"));
+            continue;
+        }
+        let rule = match parse_rule(line) {
+            Some(rule) => rule,
+            None => continue,
+        };
+
+        let trimmed = line.trim_end();
+        let backslashes = trimmed.len() - trimmed.trim_end_matches('\\').len();
+        if trimmed.len() < line.len() && backslashes % 2 == 0 {
+            report(String::from(
+                "Trailing whitespace is ignored (escape it as '\\ ' to match it)",
+            ));
+        }
+        if rule.pattern.starts_with("./") || rule.pattern.contains("/./") {
+            report(String::from(
+                "A '.' component never matches anything; remove the './'",
+            ));
+        }
+        if has_backslash_separator(rule.pattern) {
+            report(String::from(
+                "'\\' escapes the next character; directories are separated by '/'",
+            ));
+        }
+        if rule
+            .pattern
+            .split('/')
+            .any(|c| c.contains("**") && c != "**")
+        {
+            report(String::from(
+                "'**' is only special as a whole path component; here it is the same as '*'",
+            ));
+        }
+        if Pattern::parse(line).is_none() {
+            report(String::from("Invalid pattern"));
+        }
+
+        if let Some(form) = canonical(line) {
+            last.insert(form, i);
+        }
+    }
+
+    // git uses the last matching rule, so the earlier copies are the redundant ones
+    for (i, line) in text.lines().enumerate() {
+        if let Some(&later) = canonical(line).and_then(|form| last.get(&form)) {
+            if later != i {
+                lints.push(Lint {
+                    line: i,
+                    message: format!(
+                        "Redundant: line {} has the same pattern, and comes later",
+                        later + 1
+                    ),
+                });
+            }
+        }
+    }
+
+    lints.sort_by_key(|lint| lint.line);
+    lints
+}
+
+/// Look for patterns of the ignore file contents `text` that differ from an earlier one only in
+/// case: the same pattern to git when it ignores case (`core.ignoreCase`, which it sets on the
+/// case-insensitive file systems of Windows and macOS), but not elsewhere, so the file ignores
+/// different paths on different machines.
+pub fn case_collisions(text: &str) -> Vec<Lint> {
+    let mut first: HashMap<String, (usize, String)> = HashMap::new();
+    let mut lints = vec![];
+
+    for (i, line) in text.lines().enumerate() {
+        let form = match canonical(line) {
+            Some(form) => form,
+            None => continue,
+        };
+        match first.get(&form.to_lowercase()) {
+            Some((earlier, earlier_form)) if *earlier_form != form => lints.push(Lint {
+                line: i,
+                message: format!(
+                    "Differs from line {} only in case, so the two are the same only where git \
+                     ignores case",
+                    earlier + 1
+                ),
+            }),
+            Some(_) => {},
+            None => {
+                first.insert(form.to_lowercase(), (i, form));
+            },
+        }
+    }
+
+    lints
+}
+
+/// Say in words what the ignore file line `line` does, or `None` for comments and blank lines.
+pub fn explain(line: &str) -> Option<String> {
+    let rule = parse_rule(line)?;
+
+    let verb = if rule.negated {
+        "Re-includes"
+    } else {
+        "Ignores"
+    };
+    let what = if rule.dir_only {
+        "directories"
+    } else {
+        "files and directories"
+    };
+    let how = if rule.pattern.contains(['*', '?', '[']) {
+        "matching"
+    } else if rule.anchored {
+        "at"
+    } else {
+        "named"
+    };
+    let place = if rule.anchored {
+        "relative to the directory of the ignore file"
+    } else {
+        "in any directory"
+    };
+
+    Some(format!(
+        "{} {} {} '{}', {}",
+        verb, what, how, rule.pattern, place
+    ))
+}
+
+// A backslash before a letter or digit, as in a Windows path such as 'build\out'
+fn has_backslash_separator(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some(next) if next.is_ascii_alphanumeric() => return true,
+                _ => {},
+            }
+        }
+    }
+    false
+}
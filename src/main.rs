@@ -1,192 +1,66 @@
-extern crate atomicwrites;
 extern crate getopt;
-extern crate git2;
+extern crate git_ignore;
 
-use atomicwrites::{AllowOverwrite, AtomicFile};
-use getopt::Opt;
-use git2 as git;
-use std::{
-    collections::HashSet,
-    env,
-    error::Error,
-    fs,
-    io::{self, Write},
-    path::{Path, PathBuf},
-};
+mod cmd;
 
-program::main!("git-ignore");
-
-fn usage_line(program_name: &str) -> String {
-    format!(
-        "Usage: {} [-h] [-gir] [-f FILE] pattern [pattern ...]",
-        program_name
-    )
-}
+use git_ignore::bytes;
+use std::env;
 
-fn print_usage(program_name: &str) {
-    println!("{}", usage_line(program_name));
-    println!("  -f FILE  add patterns to FILE");
-    println!("  -g       add patterns to global ignore file (core.excludesFile)");
-    println!("  -i       add patterns to internal repository ignore file (_/.git/info/exclude)");
-    println!("  -r       add patterns to root-level repository ignore file (_/.gitignore)");
-    println!();
-    println!("  -h       display this help");
-    println!();
-    println!("By default, patterns are added to the file '.gitignore' in the current directory.");
-    println!("The specified file is created if it does not exist.");
-}
+program::main!("git-ignore");
 
 fn program(name: &str) -> program::Result {
-    let mut args = program::args();
-    let mut opts = getopt::Parser::new(&args, "f:ghir");
-    let mut mode = Mode::File(".gitignore".to_string());
-
-    loop {
-        match opts.next().transpose()? {
-            None => break,
-            Some(opt) => match opt {
-                Opt('f', Some(arg)) => mode = Mode::File(arg),
-                Opt('g', None) => mode = Mode::Global,
-                Opt('i', None) => mode = Mode::Internal,
-                Opt('r', None) => mode = Mode::Root,
-                Opt('h', None) => {
-                    print_usage(name);
-                    return Ok(0);
-                },
-                _ => unreachable!(),
-            },
-        }
-    }
-
-    let args = args.split_off(opts.index());
-    if args.is_empty() {
-        eprintln!("{}", usage_line(name));
-        return Ok(1);
-    }
-
-    update(mode, args)
-}
-
-enum Mode {
-    File(String),
-    Global,
-    Internal,
-    Root,
-}
-
-fn update(mode: Mode, args: Vec<String>) -> program::Result {
-    let file = get_file(mode)?;
-
-    let old = fs::read_to_string(&file).or_else(|e| {
-        if e.kind() != io::ErrorKind::NotFound {
-            return Err(e);
-        }
-
-        fs::File::create(&file)?;
-        Ok("".to_string())
-    })?;
-
-    eprint!("Updating {}... ", file.to_string_lossy());
-    let new = merge(&old, &args);
-
-    if new == old {
-        eprintln!("Nothing to do!");
-    } else {
-        AtomicFile::new(&file, AllowOverwrite).write(|f| f.write_all(new.as_bytes()))?;
-        eprintln!("Done!");
-    }
-
-    Ok(0)
-}
-
-fn get_file(mode: Mode) -> Result<PathBuf, Box<dyn Error>> {
-    match mode {
-        Mode::File(name) => Ok(env::current_dir()?.join(name)),
-        Mode::Global => global_ignore_file(),
-        Mode::Internal => internal_ignore_file(),
-        Mode::Root => root_ignore_file(),
-    }
-}
-
-fn global_ignore_file() -> Result<PathBuf, Box<dyn Error>> {
-    match git::Config::open_default()?.get_path("core.excludesFile") {
-        Err(error) => {
-            if error.class() == git::ErrorClass::Config && error.code() == git::ErrorCode::NotFound
-            {
-                let dir = dirs::config_dir()
-                    .ok_or_else(|| {
-                        Box::new(git::Error::from_str("Could not find XDG_CONFIG_HOME"))
-                    })?
-                    .join("git");
-                fs::create_dir_all(&dir)?;
-                Ok(dir.join("ignore"))
-            } else {
-                Err(error.into())
-            }
+    // Arguments that aren't UTF-8 (file names, say) are kept
+    let args: Vec<String> = env::args_os().map(|arg| bytes::from_os(&arg)).collect();
+    let args = cmd::long_options(args)?;
+    cmd::handle_interrupts();
+
+    let result = match args.get(1).map(String::as_str) {
+        Some("__complete") => {
+            cmd::completions::complete(&format!("{} __complete", name), &args[1..])
         },
-        Ok(path) => Ok(path),
-    }
-}
-
-// `git::Repository::open_from_env()?.path()` returns a path that uses '/' on Windows; fix that
-fn fix_path<P: AsRef<Path>>(path: P) -> PathBuf {
-    let mut new = PathBuf::new();
-    for e in path.as_ref().iter() {
-        new.push(e)
-    }
-
-    new
-}
-
-fn internal_ignore_file() -> Result<PathBuf, Box<dyn Error>> {
-    let dir = fix_path(git::Repository::open_from_env()?.path()).join("info");
-    fs::create_dir_all(&dir)?;
-    Ok(dir.join("exclude"))
-}
-
-fn root_ignore_file() -> Result<PathBuf, Box<dyn Error>> {
-    match git::Repository::open_from_env()?.workdir() {
-        None => Err(git::Error::from_str("Repository is bare").into()),
-        Some(path) => Ok(path.join(".gitignore")),
-    }
-}
-
-fn merge(text: &str, args: &[String]) -> String {
-    let mut lines: HashSet<String> = text.lines().map(String::from).collect();
-
-    for arg in args {
-        lines.insert(arg.to_string());
-    }
-
-    let mut lines: Vec<String> = lines
-        .into_iter()
-        .filter_map(|line| {
-            let line = line.trim().to_string();
-
-            if line.is_empty() || line.starts_with('#') {
-                None
-            } else {
-                Some(line)
-            }
-        })
-        .collect();
-
-    let lines = lines.as_mut_slice();
-    lines.sort_unstable();
-
-    let mut lines = lines.to_vec();
-    lines.dedup();
-
-    let (neg, pos): (Vec<String>, Vec<String>) =
-        lines.iter().cloned().partition(|l| l.starts_with('!'));
-    lines.clear();
-    lines.extend(pos);
-    lines.extend(neg);
-
-    let mut text = lines.join("\n");
-    if !text.is_empty() {
-        text.push('\n');
-    }
-
-    text
+        Some("audit") => cmd::audit::run(&format!("{} audit", name), &args[1..]),
+        Some("cache") => cmd::cache::run(&format!("{} cache", name), &args[1..]),
+        Some("compare") => cmd::compare::run(&format!("{} compare", name), &args[1..]),
+        Some("compile") => cmd::compile::run(&format!("{} compile", name), &args[1..]),
+        Some("completions") => cmd::completions::run(&format!("{} completions", name), &args[1..]),
+        Some("dedupe") => cmd::dedupe::run(&format!("{} dedupe", name), &args[1..]),
+        Some("derive") => cmd::derive::run(&format!("{} derive", name), &args[1..]),
+        Some("export") => cmd::export::run(&format!("{} export", name), &args[1..]),
+        Some("export-effective") => {
+            cmd::export_effective::run(&format!("{} export-effective", name), &args[1..])
+        },
+        Some("flatten") => cmd::flatten::run(&format!("{} flatten", name), &args[1..]),
+        Some("fmt") => cmd::fmt::run(&format!("{} fmt", name), &args[1..]),
+        Some("from-status") => cmd::from_status::run(&format!("{} from-status", name), &args[1..]),
+        Some("import-from") => cmd::import_from::run(&format!("{} import-from", name), &args[1..]),
+        Some("import-hg") => cmd::import_hg::run(&format!("{} import-hg", name), &args[1..]),
+        Some("import-svn") => cmd::import_svn::run(&format!("{} import-svn", name), &args[1..]),
+        Some("install-alias") => {
+            cmd::install_alias::run(&format!("{} install-alias", name), &args[1..])
+        },
+        Some("lsp") => cmd::lsp::run(&format!("{} lsp", name), &args[1..]),
+        Some("manpage") => cmd::manpage::run(&format!("{} manpage", name), &args[1..]),
+        Some("merge-file") => cmd::merge_file::run(&format!("{} merge-file", name), &args[1..]),
+        Some("rename-prefix") => {
+            cmd::rename_prefix::run(&format!("{} rename-prefix", name), &args[1..])
+        },
+        Some("renames") => cmd::renames::run(&format!("{} renames", name), &args[1..]),
+        Some("restore") => cmd::restore::run(&format!("{} restore", name), &args[1..]),
+        Some("rewrite") => cmd::rewrite::run(&format!("{} rewrite", name), &args[1..]),
+        Some("scatter") => cmd::scatter::run(&format!("{} scatter", name), &args[1..]),
+        Some("self-update") => cmd::self_update::run(&format!("{} self-update", name), &args[1..]),
+        Some("set") => cmd::set::run(&format!("{} set", name), &args[1..]),
+        Some("show") => cmd::show::run(&format!("{} show", name), &args[1..]),
+        Some("sources") => cmd::sources::run(&format!("{} sources", name), &args[1..]),
+        Some("template") => cmd::template::run(&format!("{} template", name), &args[1..]),
+        Some("verify") => cmd::verify::run(&format!("{} verify", name), &args[1..]),
+        Some("watch") => cmd::watch::run(&format!("{} watch", name), &args[1..]),
+        Some("which") => cmd::which::run(&format!("{} which", name), &args[1..]),
+        Some(command) => match cmd::plugin::find(command) {
+            Some(plugin) => cmd::plugin::run(&plugin, &args[2..]),
+            None => cmd::add::run(name, args),
+        },
+        None => cmd::add::run(name, args),
+    };
+    cmd::exit_status(name, result)
 }
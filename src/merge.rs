@@ -0,0 +1,412 @@
+use crate::Normalization;
+use std::{borrow::Cow, collections::HashSet};
+
+/// Options controlling how [`merge`] combines patterns.
+///
+/// The default options reproduce the behaviour of the `git-ignore` command: comments and blank
+/// lines are dropped, all duplicates are removed, and patterns are sorted with negations last.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MergeOptions {
+    /// Sort patterns; when comments are kept, each run of patterns between comments is sorted
+    /// separately.
+    pub sort: bool,
+    /// What to do with comments and blank lines.
+    pub comments: Comments,
+    /// Which duplicate patterns to remove.
+    pub dedup: Dedup,
+    /// Where to put negated (`!`) patterns.
+    pub negations: Negations,
+    /// The Unicode normalization form to rewrite patterns in, before looking for duplicates.
+    pub normalize: Normalization,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self {
+            sort: true,
+            comments: Comments::Strip,
+            dedup: Dedup::All,
+            negations: Negations::Last,
+            normalize: Normalization::None,
+        }
+    }
+}
+
+/// Comment and blank line handling for [`MergeOptions`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Comments {
+    /// Drop comments and blank lines.
+    Strip,
+    /// Keep comments and blank lines where they are; new patterns are appended at the end.
+    Keep,
+}
+
+/// Duplicate handling for [`MergeOptions`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Dedup {
+    /// Remove every repeated pattern, keeping the first occurrence.
+    All,
+    /// Only skip additions that are already present; existing duplicates are left alone.
+    Additions,
+    /// Keep every pattern, even repeated ones.
+    None,
+}
+
+/// Negated pattern placement for [`MergeOptions`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Negations {
+    /// Move negated patterns after the patterns they may override.
+    Last,
+    /// Leave negated patterns where sorting puts them.
+    InPlace,
+}
+
+// Lines borrow from the input unless normalizing changed them
+enum Line<'a> {
+    Other(&'a str),
+    Pattern(Cow<'a, str>),
+}
+
+// A line starting with `\#` or `\!` is a pattern for a name starting with `#` or `!`
+fn is_pattern(line: &str) -> bool {
+    !line.is_empty() && !line.starts_with('#')
+}
+
+// Trim surrounding whitespace, but keep a trailing space escaped as `\ `
+pub(crate) fn trim(line: &str) -> &str {
+    trim_end(line.trim_start())
+}
+
+// Trim trailing whitespace, but keep a trailing space escaped as `\ `, as git does
+pub(crate) fn trim_end(line: &str) -> &str {
+    let trimmed = line.trim_end();
+
+    let backslashes = trimmed.len() - trimmed.trim_end_matches('\\').len();
+    if backslashes % 2 == 1 && trimmed.len() < line.len() {
+        &line[..trimmed.len() + 1]
+    } else {
+        trimmed
+    }
+}
+
+/// Merge the patterns in `additions` into the ignore file contents `existing`, returning the new
+/// contents.
+///
+/// Lines are trimmed of surrounding whitespace, except for a trailing space escaped with `\`; a
+/// leading `\#` or `\!` escape is kept, so such lines stay patterns for names starting with `#`
+/// or `!`. The result ends with a newline unless it is empty.
+pub fn merge<S: AsRef<str>>(existing: &str, additions: &[S], options: MergeOptions) -> String {
+    let count = existing.bytes().filter(|&b| b == b'\n').count() + 1 + additions.len();
+    let mut seen = HashSet::with_capacity(count);
+    let mut lines = Vec::with_capacity(count);
+
+    for line in existing.lines().map(trim) {
+        if !is_pattern(line) {
+            if options.comments == Comments::Keep {
+                lines.push(Line::Other(line));
+            }
+            continue;
+        }
+        let line = options.normalize.apply(line);
+        if seen.insert(line.clone()) || options.dedup != Dedup::All {
+            lines.push(Line::Pattern(line));
+        }
+    }
+
+    for line in additions.iter().map(|s| trim(s.as_ref())) {
+        if !is_pattern(line) {
+            continue;
+        }
+        let line = options.normalize.apply(line);
+        if seen.insert(line.clone()) || options.dedup == Dedup::None {
+            lines.push(Line::Pattern(line));
+        }
+    }
+    drop(seen);
+
+    let length = existing.len()
+        + additions
+            .iter()
+            .map(|s| s.as_ref().len() + 1)
+            .sum::<usize>();
+    let mut text = String::with_capacity(length + 1);
+    let mut run = Vec::new();
+
+    for line in lines {
+        match line {
+            Line::Pattern(pattern) => run.push(pattern),
+            Line::Other(other) => {
+                flush(&mut text, &mut run, options);
+                text.push_str(other);
+                text.push('\n');
+            },
+        }
+    }
+    flush(&mut text, &mut run, options);
+
+    text
+}
+
+/// Merge the whole contents of another ignore file, `other`, into `existing`, keeping the
+/// comments of both.
+///
+/// `other` is read as sections: a block of comment lines followed by patterns. The new patterns
+/// of a section whose comments already appear in `existing` are added to the patterns under
+/// them there; other sections are appended, comments and all. Patterns before the first comment
+/// go into the leading patterns of `existing`. The result is then normalized as by [`merge`],
+/// always keeping comments.
+pub fn merge_file(existing: &str, other: &str, options: MergeOptions) -> String {
+    let mut lines: Vec<String> = existing.lines().map(|l| trim(l).to_string()).collect();
+    let mut seen: HashSet<String> = lines.iter().filter(|l| is_pattern(l)).cloned().collect();
+
+    for (header, patterns) in sections(other) {
+        let new: Vec<String> = patterns
+            .into_iter()
+            .filter(|p| seen.insert(p.clone()) || options.dedup == Dedup::None)
+            .collect();
+        if new.is_empty() {
+            continue;
+        }
+
+        let start = (0..=lines.len().saturating_sub(header.len()))
+            .find(|&i| lines[i..].starts_with(&header));
+        match start {
+            Some(start) => {
+                let mut at = start + header.len();
+                while at < lines.len() && lines[at].starts_with('#') {
+                    at += 1;
+                }
+                while at < lines.len() && is_pattern(&lines[at]) {
+                    at += 1;
+                }
+                lines.splice(at..at, new);
+            },
+            None => {
+                if lines.last().is_some_and(|line| !line.is_empty()) {
+                    lines.push(String::new());
+                }
+                lines.extend(header);
+                lines.extend(new);
+            },
+        }
+    }
+
+    let options = MergeOptions {
+        comments: Comments::Keep,
+        ..options
+    };
+    merge(&lines.join("\n"), &[] as &[&str], options)
+}
+
+/// Merge `additions` into `existing` under the comment header `# NAME`, which is appended if
+/// missing; see [`merge_file`].
+pub fn merge_section<S: AsRef<str>>(
+    existing: &str,
+    name: &str,
+    additions: &[S],
+    options: MergeOptions,
+) -> String {
+    let mut other = format!("# {}\n", name.trim());
+    for addition in additions {
+        other.push_str(addition.as_ref());
+        other.push('\n');
+    }
+
+    merge_file(existing, &other, options)
+}
+
+/// Merge `additions` into `existing` below the comment `# ANNOTATION`, keeping other comments.
+///
+/// Only the patterns that are new get the comment. They go at the end of the section `# NAME`
+/// when `section` is given (appending the section if missing), and at the end of the file
+/// otherwise; the result is then normalized as by [`merge_file`].
+pub fn merge_annotated<S: AsRef<str>>(
+    existing: &str,
+    section: Option<&str>,
+    annotation: &str,
+    additions: &[S],
+    options: MergeOptions,
+) -> String {
+    let mut lines: Vec<String> = existing.lines().map(|l| trim(l).to_string()).collect();
+    let mut seen: HashSet<String> = lines.iter().filter(|l| is_pattern(l)).cloned().collect();
+    let new: Vec<String> = additions
+        .iter()
+        .map(|p| trim(p.as_ref()).to_string())
+        .filter(|p| is_pattern(p) && (seen.insert(p.clone()) || options.dedup == Dedup::None))
+        .collect();
+
+    if !new.is_empty() {
+        let header = section.map(|name| format!("# {}", name.trim()));
+        let mut at = match header.and_then(|h| lines.iter().position(|line| *line == h)) {
+            Some(start) => start + 1,
+            None => {
+                if lines.last().is_some_and(|line| !line.is_empty()) {
+                    lines.push(String::new());
+                }
+                lines.extend(section.map(|name| format!("# {}", name.trim())));
+                lines.len()
+            },
+        };
+        while at < lines.len() && (lines[at].starts_with('#') || is_pattern(&lines[at])) {
+            at += 1;
+        }
+
+        let block = std::iter::once(format!("# {}", annotation.trim())).chain(new);
+        lines.splice(at..at, block);
+    }
+
+    let options = MergeOptions {
+        comments: Comments::Keep,
+        ..options
+    };
+    merge(&lines.join("\n"), &[] as &[&str], options)
+}
+
+// Split ignore file contents into comment blocks and the patterns following them
+fn sections(text: &str) -> Vec<(Vec<String>, Vec<String>)> {
+    let mut sections = vec![(vec![], vec![])];
+
+    for line in text.lines().map(trim).filter(|line| !line.is_empty()) {
+        if is_pattern(line) {
+            sections.last_mut().unwrap().1.push(line.to_string());
+        } else {
+            if !sections.last().unwrap().1.is_empty() {
+                sections.push((vec![], vec![]));
+            }
+            sections.last_mut().unwrap().0.push(line.to_string());
+        }
+    }
+
+    sections
+}
+
+// Sort a run of consecutive patterns as requested and append it to `text`
+fn flush(text: &mut String, run: &mut Vec<Cow<'_, str>>, options: MergeOptions) {
+    let negated = |pattern: &Cow<'_, str>| pattern.starts_with('!');
+    match (options.sort, options.negations) {
+        (true, Negations::Last) => {
+            run.sort_unstable_by(|a, b| (negated(a), a).cmp(&(negated(b), b)))
+        },
+        (true, Negations::InPlace) => run.sort_unstable(),
+        // A stable sort, so that the patterns otherwise keep their order
+        (false, Negations::Last) => run.sort_by_key(negated),
+        (false, Negations::InPlace) => {},
+    }
+
+    for pattern in run.drain(..) {
+        text.push_str(&pattern);
+        text.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(sort: bool, comments: Comments, dedup: Dedup, negations: Negations) -> MergeOptions {
+        MergeOptions {
+            sort,
+            comments,
+            dedup,
+            negations,
+            normalize: Normalization::None,
+        }
+    }
+
+    #[test]
+    fn defaults() {
+        let existing = "b\n# c\n\n!keep\n  a\nb\n";
+        let additions = ["c", " a ", "name\\ ", "#x", "", "\\#y"];
+        assert_eq!(
+            merge(existing, &additions, MergeOptions::default()),
+            "\\#y\na\nb\nc\nname\\ \n!keep\n"
+        );
+        assert_eq!(merge("", &[] as &[&str], MergeOptions::default()), "");
+    }
+
+    #[test]
+    fn comments_and_duplicates() {
+        let existing = "# top\nb\na\nb\n\n# more\nz\n";
+        let keep = |dedup| options(true, Comments::Keep, dedup, Negations::Last);
+        assert_eq!(
+            merge(existing, &["a", "c"], keep(Dedup::All)),
+            "# top\na\nb\n\n# more\nc\nz\n"
+        );
+        assert_eq!(
+            merge(existing, &["a", "c"], keep(Dedup::Additions)),
+            "# top\na\nb\nb\n\n# more\nc\nz\n"
+        );
+        assert_eq!(
+            merge(existing, &["a", "c"], keep(Dedup::None)),
+            "# top\na\nb\nb\n\n# more\na\nc\nz\n"
+        );
+    }
+
+    #[test]
+    fn negations() {
+        let order = |sort, negations| {
+            let options = options(sort, Comments::Strip, Dedup::All, negations);
+            merge("c\n!b\na\n", &[] as &[&str], options)
+        };
+        assert_eq!(order(true, Negations::Last), "a\nc\n!b\n");
+        assert_eq!(order(true, Negations::InPlace), "!b\na\nc\n");
+        assert_eq!(order(false, Negations::Last), "c\na\n!b\n");
+        assert_eq!(order(false, Negations::InPlace), "c\n!b\na\n");
+    }
+
+    #[test]
+    fn files() {
+        let existing = "*.o\n\n# Logs\n*.log\n\n# Other\nx\n";
+        let other = "a\n# Logs\n*.tmp\n*.log\n# New\nn\n";
+        assert_eq!(
+            merge_file(existing, other, MergeOptions::default()),
+            "*.o\na\n\n# Logs\n*.log\n*.tmp\n\n# Other\nx\n\n# New\nn\n"
+        );
+        assert_eq!(
+            merge_file(existing, "*.log\n", MergeOptions::default()),
+            existing
+        );
+    }
+
+    #[test]
+    fn sections() {
+        let existing = "# A\nx\n";
+        assert_eq!(
+            merge_section(existing, " B ", &["y", "x"], MergeOptions::default()),
+            "# A\nx\n\n# B\ny\n"
+        );
+        assert_eq!(
+            merge_section(existing, "A", &["w"], MergeOptions::default()),
+            "# A\nw\nx\n"
+        );
+    }
+
+    #[test]
+    fn annotated() {
+        let existing = "# A\nx\n\n# B\ny\n";
+        assert_eq!(
+            merge_annotated(
+                existing,
+                Some("A"),
+                "added",
+                &["z", "y"],
+                MergeOptions::default()
+            ),
+            "# A\nx\n# added\nz\n\n# B\ny\n"
+        );
+        assert_eq!(
+            merge_annotated("x\n", None, "added", &["z"], MergeOptions::default()),
+            "x\n\n# added\nz\n"
+        );
+        assert_eq!(
+            merge_annotated(
+                existing,
+                Some("C"),
+                "added",
+                &["y"],
+                MergeOptions::default()
+            ),
+            existing
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use std::{borrow::Cow, io, str::FromStr};
+use unicode_normalization::{is_nfc, is_nfd, UnicodeNormalization};
+
+/// The Unicode normalization form to write patterns in.
+///
+/// git compares the bytes of patterns and paths, so `é` typed as one character (NFC) doesn't
+/// match the same name as macOS returns it, decomposed (NFD), unless both are normalized alike.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Normalization {
+    /// Leave patterns as they are.
+    #[default]
+    None,
+    /// Composed characters, as most input methods produce them.
+    Nfc,
+    /// Decomposed characters, as macOS file systems return names.
+    Nfd,
+}
+
+impl Normalization {
+    /// Normalize `s` to the form.
+    pub fn apply(self, s: &str) -> Cow<'_, str> {
+        match self {
+            Self::Nfc if !is_nfc(s) => Cow::Owned(s.nfc().collect()),
+            Self::Nfd if !is_nfd(s) => Cow::Owned(s.nfd().collect()),
+            _ => Cow::Borrowed(s),
+        }
+    }
+}
+
+impl FromStr for Normalization {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "nfc" => Ok(Self::Nfc),
+            "nfd" => Ok(Self::Nfd),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown normalization '{}' (expected nfc, nfd or none)", s),
+            )),
+        }
+    }
+}
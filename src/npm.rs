@@ -0,0 +1,16 @@
+use std::collections::HashSet;
+
+/// The patterns of a `.gitignore` that are missing from the `.npmignore` next to it.
+///
+/// npm ignores `.gitignore` entirely once a `.npmignore` exists, so rules that should apply to
+/// the published package too need to be repeated there.
+pub fn missing_rules(gitignore: &str, npmignore: &str) -> Vec<String> {
+    let present: HashSet<&str> = npmignore.lines().map(str::trim).collect();
+
+    gitignore
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !present.contains(line))
+        .map(String::from)
+        .collect()
+}
@@ -0,0 +1,321 @@
+use crate::merge::trim;
+use crate::{
+    cone_patterns, has_conflicts, has_lines, lift_cone_restrictions, merge_annotated,
+    merge_attributes, merge_file, merge_section, resolve_conflicts, set_block, Comments, Dedup,
+    IgnoreFile, Kind, MergeOptions, Negations, Newline, Repo, Result, Target,
+};
+use std::{
+    collections::HashSet,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// A composable operation on an ignore file.
+///
+/// ```no_run
+/// use git_ignore::{IgnoreOp, Target};
+///
+/// let outcome = IgnoreOp::new()
+///     .target(Target::Root)
+///     .add(["target/"])
+///     .dry_run(true)
+///     .execute()?;
+/// println!("{} would change: {}", outcome.path.display(), outcome.changed());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct IgnoreOp {
+    target: Target,
+    git_dir: bool,
+    kind: Kind,
+    patterns: Vec<String>,
+    options: MergeOptions,
+    block: Option<String>,
+    other: Option<String>,
+    section: Option<String>,
+    annotation: Option<String>,
+    resolve: bool,
+    keep_conflicts: bool,
+    skip_present: bool,
+    newline: Newline,
+    backup: bool,
+    backup_dir: Option<PathBuf>,
+    dry_run: bool,
+}
+
+/// The result of an [`IgnoreOp`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Outcome {
+    /// The resolved path of the ignore file.
+    pub path: PathBuf,
+    /// The contents of the file before the operation.
+    pub before: String,
+    /// The contents of the file after the operation.
+    pub after: String,
+    /// Whether the file was written to disk.
+    pub written: bool,
+    /// Where the old contents were backed up to, if they were.
+    pub backup: Option<PathBuf>,
+}
+
+impl Outcome {
+    /// Whether the operation changed (or, in a dry run, would change) the file contents.
+    pub fn changed(&self) -> bool {
+        self.before != self.after
+    }
+
+    /// The lines that are in the file after the operation but were not before, in order, leaving
+    /// out comments and blank lines.
+    pub fn added(&self) -> Vec<&str> {
+        let before: HashSet<&str> = self.before.lines().map(str::trim_end).collect();
+        self.after
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter(|line| !before.contains(line))
+            .collect()
+    }
+
+    /// Whether the operation added `pattern`, comparing it to the lines of the file as
+    /// [`merge`](crate::merge()) with `options` does: trimmed, and in the normalization form they
+    /// ask for.
+    pub fn adds(&self, pattern: &str, options: MergeOptions) -> bool {
+        let pattern = options.normalize.apply(trim(pattern));
+        let has = |text: &str| {
+            text.lines()
+                .any(|line| options.normalize.apply(trim(line)) == pattern)
+        };
+        has(&self.after) && !has(&self.before)
+    }
+}
+
+impl IgnoreOp {
+    /// Create an operation on the default target (`.gitignore` in the current directory).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the file to operate on.
+    pub fn target(mut self, target: Target) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Let a [`Target::File`] be anywhere in a git directory, where git would not read it (see
+    /// [`Target::locate_allowing_git_dir`]).
+    pub fn allow_git_dir(mut self, allow: bool) -> Self {
+        self.git_dir = allow;
+        self
+    }
+
+    /// Set the kind of file to operate on.
+    pub fn kind(mut self, kind: Kind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Add patterns to merge into the file.
+    ///
+    /// For [`Target::Sparse`] in cone mode, these are directories; see [`cone_patterns`]. For
+    /// [`Kind::Attributes`], these are attribute lines; see [`merge_attributes`].
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.patterns.extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set the merge options.
+    pub fn options(mut self, options: MergeOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Replace the managed block called `name` with the patterns instead of merging them; see
+    /// [`set_block`].
+    pub fn block<S: Into<String>>(mut self, name: S) -> Self {
+        self.block = Some(name.into());
+        self
+    }
+
+    /// Merge the whole contents of another ignore file instead of individual patterns, keeping
+    /// its comments; see [`merge_file`].
+    pub fn merge_file<S: Into<String>>(mut self, text: S) -> Self {
+        self.other = Some(text.into());
+        self
+    }
+
+    /// Add the patterns under the comment header `# NAME`, keeping the file's comments; see
+    /// [`merge_section`].
+    pub fn section<S: Into<String>>(mut self, name: S) -> Self {
+        self.section = Some(name.into());
+        self
+    }
+
+    /// Put the comment `# TEXT` above the patterns that are new; see [`merge_annotated`].
+    pub fn annotate<S: Into<String>>(mut self, text: S) -> Self {
+        self.annotation = Some(text.into());
+        self
+    }
+
+    /// Resolve any merge conflicts in the file by keeping both sides, instead of failing; see
+    /// [`resolve_conflicts`].
+    pub fn resolve_conflicts(mut self, resolve: bool) -> Self {
+        self.resolve = resolve;
+        self
+    }
+
+    /// Merge into a file with merge conflicts as it is, markers and all, instead of failing; the
+    /// new patterns are then appended, leaving the rest of the file untouched.
+    pub fn keep_conflicts(mut self, keep: bool) -> Self {
+        self.keep_conflicts = keep;
+        self
+    }
+
+    /// Leave the file exactly as it is if it already has every pattern to add, only looking
+    /// for them with [`has_lines`]; the contents are then left out of the [`Outcome`], and the
+    /// file is not normalized (sorted, say) as it otherwise would be. This makes adding patterns
+    /// that are already there to huge files cheap.
+    pub fn skip_present(mut self, skip: bool) -> Self {
+        self.skip_present = skip;
+        self
+    }
+
+    /// Set what to do with the final newline; see [`Newline`].
+    pub fn newline(mut self, newline: Newline) -> Self {
+        self.newline = newline;
+        self
+    }
+
+    /// Back the file up before changing it; see [`IgnoreFile::backup`].
+    pub fn backup(mut self, backup: bool) -> Self {
+        self.backup = backup;
+        self
+    }
+
+    /// Put backups in `dir` instead of next to the file.
+    pub fn backup_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.backup_dir = Some(dir.into());
+        self
+    }
+
+    /// Compute the outcome without writing anything.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Run the operation.
+    ///
+    /// Fails if the file has merge conflicts, unless they are to be resolved or kept.
+    pub fn execute(&self) -> Result<Outcome> {
+        let path = match (self.dry_run, self.git_dir) {
+            (true, false) => self.target.locate(self.kind)?,
+            (true, true) => self.target.locate_allowing_git_dir(self.kind)?,
+            (false, false) => self.target.resolve(self.kind)?,
+            (false, true) => self.target.resolve_allowing_git_dir(self.kind)?,
+        };
+        if self.skip_present && self.all_present(&path)? {
+            return Ok(Outcome {
+                path,
+                before: String::new(),
+                after: String::new(),
+                written: false,
+                backup: None,
+            });
+        }
+
+        let mut file = IgnoreFile::load(path)?;
+        file.set_newline(self.newline);
+        let before = file.text().to_string();
+
+        let mut options = self.options;
+        if has_conflicts(&before) {
+            if self.resolve {
+                file.set_text(resolve_conflicts(&before));
+            } else if self.keep_conflicts {
+                // Tidying would scatter the markers and the sides they mark
+                options = MergeOptions {
+                    sort: false,
+                    comments: Comments::Keep,
+                    dedup: Dedup::Additions,
+                    negations: Negations::InPlace,
+                    ..options
+                };
+            } else {
+                return Err(conflicts(file.path()).into());
+            }
+        }
+        let text = file.text().to_string();
+
+        if let Some(name) = &self.block {
+            file.set_text(set_block(&text, name, &self.patterns));
+        } else if let Some(other) = &self.other {
+            file.set_text(merge_file(&text, other, options));
+        } else if self.kind == Kind::Attributes {
+            file.set_text(merge_attributes(&text, &self.patterns));
+        } else if self.target == Target::Sparse
+            && Repo::discover()?.config_bool("core.sparseCheckoutCone")?
+        {
+            // A directory added after one below it is no longer cut down to that one
+            file.set_text(lift_cone_restrictions(&text, &self.patterns));
+            let text = file.text().to_string();
+            file.merge(&cone_patterns(&text, &self.patterns), options);
+        } else if let Some(annotation) = &self.annotation {
+            file.set_text(merge_annotated(
+                &text,
+                self.section.as_deref(),
+                annotation,
+                &self.patterns,
+                options,
+            ));
+        } else if let Some(name) = &self.section {
+            file.set_text(merge_section(&text, name, &self.patterns, options));
+        } else {
+            file.merge(&self.patterns, options);
+        }
+
+        let backup = if self.backup && !self.dry_run && file.is_modified() {
+            file.backup(self.backup_dir.as_deref())?
+        } else {
+            None
+        };
+        let written = !self.dry_run && file.save()?;
+
+        Ok(Outcome {
+            path: file.path().to_path_buf(),
+            before,
+            after: file.text().to_string(),
+            written,
+            backup,
+        })
+    }
+
+    // Whether the file already has every pattern, for the operations that would add nothing then
+    fn all_present(&self, path: &Path) -> Result<bool> {
+        let plain = self.block.is_none()
+            && self.other.is_none()
+            && self.kind != Kind::Attributes
+            && self.target != Target::Sparse;
+        if !plain || self.patterns.is_empty() {
+            return Ok(false);
+        }
+
+        let patterns: Vec<_> = self
+            .patterns
+            .iter()
+            .map(|pattern| self.options.normalize.apply(pattern))
+            .collect();
+        Ok(has_lines(path, &patterns)?.into_iter().all(|found| found))
+    }
+}
+
+fn conflicts(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{} has unresolved merge conflicts", path.to_string_lossy()),
+    )
+}
@@ -0,0 +1,236 @@
+use crate::export::parse_rule;
+use regex::Regex;
+
+/// A gitignore rule, compiled for matching paths.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    negated: bool,
+    anchored: bool,
+    dir_only: bool,
+    regex: Regex,
+}
+
+impl Pattern {
+    /// Compile a line of an ignore file; comments and blank lines give `None`.
+    pub fn parse(line: &str) -> Option<Self> {
+        let rule = parse_rule(line)?;
+        let regex = Regex::new(&format!("^{}$", translate(rule.pattern)?)).ok()?;
+
+        Some(Self {
+            negated: rule.negated,
+            anchored: rule.anchored,
+            dir_only: rule.dir_only,
+            regex,
+        })
+    }
+
+    /// Whether the rule re-includes what it matches (`!pattern`).
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    /// Whether the rule matches `path`, relative to the directory of its file with `/`
+    /// separators, by itself: the rules matching parent directories are not considered (see
+    /// [`is_ignored`]).
+    pub fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+
+        let path = path.trim_matches('/');
+        if self.anchored {
+            self.regex.is_match(path)
+        } else {
+            self.regex.is_match(path.rsplit('/').next().unwrap_or(path))
+        }
+    }
+}
+
+/// Whether the rules of an ignore file (`text`) ignore `path`, relative to its directory.
+///
+/// As in git, the last matching rule wins, and nothing below an ignored directory can be
+/// re-included.
+pub fn is_ignored(text: &str, path: &str, is_dir: bool) -> bool {
+    Rules::parse(text).is_ignored(path, is_dir)
+}
+
+/// The compiled rules of an ignore file, for asking about many paths (see [`is_ignored`]).
+#[derive(Clone, Debug)]
+pub struct Rules {
+    patterns: Vec<Pattern>,
+}
+
+impl Rules {
+    /// Compile the lines of the ignore file contents `text`.
+    pub fn parse(text: &str) -> Self {
+        Self {
+            patterns: text.lines().filter_map(Pattern::parse).collect(),
+        }
+    }
+
+    /// Whether the rules ignore `path`, relative to the directory of their file.
+    pub fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        let path = path.trim_matches('/');
+        let mut end = 0;
+        while let Some(offset) = path[end..].find('/') {
+            end += offset;
+            if self.last_match(&path[..end], true) == Some(true) {
+                return true;
+            }
+            end += 1;
+        }
+
+        self.last_match(path, is_dir) == Some(true)
+    }
+
+    // Whether the last rule matching `path` by itself ignores it (or re-includes it), if any does
+    pub(crate) fn last_match(&self, path: &str, is_dir: bool) -> Option<bool> {
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| pattern.matches(path, is_dir))
+            .map(|pattern| !pattern.negated)
+    }
+}
+
+// The regular expression for a wildmatch pattern; `None` for one that matches nothing
+fn translate(pattern: &str) -> Option<String> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut regex = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*')
+                && (i == 0 || chars[i - 1] == '/')
+                && (i + 2 == chars.len() || chars[i + 2] == '/') =>
+            {
+                if i + 2 == chars.len() {
+                    // `a/**` matches everything inside `a`
+                    regex.push_str(".*");
+                } else {
+                    // `**/` matches zero or more directories
+                    regex.push_str("(?:.*/)?");
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            },
+            '*' => {
+                while chars.get(i + 1) == Some(&'*') {
+                    i += 1;
+                }
+                regex.push_str("[^/]*");
+            },
+            '?' => regex.push_str("[^/]"),
+            '[' => match class(&chars[i..]) {
+                Some((class, _)) if class.is_empty() => return None,
+                Some((class, len)) => {
+                    regex.push_str(&class);
+                    i += len;
+                    continue;
+                },
+                None => regex.push_str("\\["),
+            },
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                regex.push_str(&regex::escape(&chars[i].to_string()));
+            },
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+
+    Some(regex)
+}
+
+// The character classes a bracket expression can have, as `[:alpha:]`
+const NAMED: &[&str] = &[
+    "alnum", "alpha", "blank", "cntrl", "digit", "graph", "lower", "print", "punct", "space",
+    "upper", "xdigit",
+];
+
+// A bracket expression at the start of `chars`, as a regex class, and its length; an empty
+// class if it has a character class git doesn't know, with which it matches nothing
+fn class(chars: &[char]) -> Option<(String, usize)> {
+    let mut class = String::from("[");
+    let mut i = 1;
+
+    let negated = matches!(chars.get(i), Some('!') | Some('^'));
+    if negated {
+        class.push('^');
+        i += 1;
+    }
+    let start = i;
+
+    while i < chars.len() {
+        let mut escaped = false;
+        let c = match chars[i] {
+            // Neither kind of class matches `/`
+            ']' if i > start && negated => {
+                class.push_str("/]");
+                return Some((class, i + 1));
+            },
+            ']' if i > start => {
+                class.push_str("&&[^/]]");
+                return Some((class, i + 1));
+            },
+            '[' if chars.get(i + 1) == Some(&':') => {
+                // As git reads it, up to the next `]`, if `:` comes before that
+                let end = chars[i + 2..].iter().position(|&c| c == ']');
+                match end.filter(|&end| end > 0 && chars[i + 1 + end] == ':') {
+                    Some(end) => {
+                        let name: String = chars[i + 2..i + 1 + end].iter().collect();
+                        if !NAMED.contains(&name.as_str()) {
+                            return Some((String::new(), 0));
+                        }
+                        class.push_str(&format!("[:{}:]", name));
+                        i += end + 3;
+                        continue;
+                    },
+                    None => '[',
+                }
+            },
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                escaped = true;
+                chars[i]
+            },
+            c => c,
+        };
+        // A '-' first, last or escaped stands for itself
+        let range = c == '-' && !escaped && i > start && chars.get(i + 1) != Some(&']');
+        if !range && c.is_ascii_punctuation() {
+            class.push('\\');
+        }
+        class.push(c);
+        i += 1;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn character_classes() {
+        let matches = |pattern, path| Pattern::parse(pattern).unwrap().matches(path, false);
+        assert!(matches("[[:alpha:]]", "a"));
+        assert!(!matches("[[:alpha:]]", ":"));
+        assert!(matches("a[[:space:]]b", "a b"));
+        assert!(!matches("a[[:space:]]b", "a_b"));
+        assert!(matches("[![:digit:]]x", "ax"));
+        assert!(!matches("[![:digit:]]x", "1x"));
+        assert!(matches("[[:digit:]_-]", "_"));
+        assert!(matches("[[:digit:]_-]", "-"));
+        assert!(matches("[a-c]", "b"));
+        assert!(!matches("[a\\-c]", "b"));
+        assert!(matches("[[:upper:][:digit:]]", "7"));
+        assert!(!matches("[[:alpha:]]", "/"));
+        // Without ':' before the first ']', the '[' is a character of the set
+        assert!(matches("[[:a]", ":"));
+        assert!(Pattern::parse("[[:nothing:]]").is_none());
+    }
+}
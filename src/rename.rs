@@ -0,0 +1,153 @@
+use crate::export::parse_rule;
+
+/// Rewrite the patterns of the ignore file contents `text` that refer to paths under the
+/// directory `old` (relative to the file's directory) to refer to `new` instead.
+///
+/// Only anchored patterns refer to a particular path, so unanchored ones (`old`, `*.log`) are
+/// left alone, as are comments and the order of lines. Returns the new contents and the number of
+/// patterns rewritten.
+pub fn rename_prefix(text: &str, old: &str, new: &str) -> (String, usize) {
+    let old = old.trim_matches('/');
+    let new = new.trim_matches('/');
+    let mut result = String::new();
+    let mut count = 0;
+
+    for line in text.lines() {
+        match rename_rule(line, old, new) {
+            Some(rule) => {
+                result.push_str(&rule);
+                count += 1;
+            },
+            None => result.push_str(line),
+        }
+        result.push('\n');
+    }
+
+    (result, count)
+}
+
+fn rename_rule(line: &str, old: &str, new: &str) -> Option<String> {
+    let rule = parse_rule(line)?;
+    if !rule.anchored || old.is_empty() {
+        return None;
+    }
+
+    let rest = match rule.pattern.strip_prefix(old) {
+        Some("") => "",
+        Some(rest) if rest.starts_with('/') => rest,
+        _ => return None,
+    };
+
+    let mut path = String::from(new);
+    path.push_str(rest);
+    let path = path.trim_start_matches('/');
+    if path.is_empty() {
+        return None;
+    }
+
+    let line = line.trim();
+    let mut text = String::new();
+    if rule.negated {
+        text.push('!');
+    }
+    // Keep the leading slash if there was one, or add one if the pattern would lose its anchor
+    if line.trim_start_matches('!').starts_with('/') || !path.contains('/') {
+        text.push('/');
+    }
+    text.push_str(path);
+    if rule.dir_only {
+        text.push('/');
+    }
+
+    Some(text)
+}
+
+/// Infer directory renames from the output of `git diff --name-status -M`.
+///
+/// Each renamed file whose name (and possibly some parent directories) stayed the same
+/// contributes its differing leading directories; for every old directory the most common new
+/// one wins. Renames implied by a renamed parent directory are left out. Returns `(old, new)`
+/// pairs, sorted.
+pub fn directory_renames(name_status: &str) -> Vec<(String, String)> {
+    let mut votes: Vec<((String, String), usize)> = vec![];
+
+    for line in name_status.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let (old, new) = match fields.as_slice() {
+            [status, old, new] if status.starts_with('R') => (*old, *new),
+            _ => continue,
+        };
+
+        let old: Vec<&str> = old.split('/').collect();
+        let new: Vec<&str> = new.split('/').collect();
+        let common = old
+            .iter()
+            .rev()
+            .zip(new.iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        if common == 0 || common == old.len() || common == new.len() {
+            continue;
+        }
+
+        let pair = (
+            old[..old.len() - common].join("/"),
+            new[..new.len() - common].join("/"),
+        );
+        match votes.iter_mut().find(|(p, _)| *p == pair) {
+            Some((_, n)) => *n += 1,
+            None => votes.push((pair, 1)),
+        }
+    }
+
+    let mut renames: Vec<(String, String)> = vec![];
+    votes.sort_by(|(a, m), (b, n)| n.cmp(m).then(a.cmp(b)));
+    for ((old, new), _) in votes {
+        if !renames.iter().any(|(o, _)| *o == old) {
+            renames.push((old, new));
+        }
+    }
+
+    let implied = |(old, new): &(String, String)| {
+        renames.iter().any(|(o, n)| {
+            match (old.strip_prefix(o.as_str()), new.strip_prefix(n.as_str())) {
+                (Some(a), Some(b)) => a == b && a.starts_with('/'),
+                _ => false,
+            }
+        })
+    };
+    let mut result: Vec<(String, String)> =
+        renames.iter().filter(|r| !implied(r)).cloned().collect();
+    result.sort();
+    result
+}
+
+/// The anchored patterns of the ignore file contents `text` that refer to a directory that does
+/// not exist, with (1-based) line numbers.
+///
+/// `exists` is asked about the literal leading directories of each anchored pattern (`src/old`
+/// for `/src/old/*.o`), relative to the file's directory; the shortest missing one is returned.
+pub fn missing_dirs<F: Fn(&str) -> bool>(text: &str, exists: F) -> Vec<(usize, String, String)> {
+    let mut missing = vec![];
+
+    for (n, line) in text.lines().enumerate() {
+        let rule = match parse_rule(line) {
+            Some(rule) if rule.anchored => rule,
+            _ => continue,
+        };
+
+        let components: Vec<&str> = rule.pattern.split('/').collect();
+        let dirs = components[..components.len() - 1]
+            .iter()
+            .take_while(|c| !c.contains(['*', '?', '[', '\\']))
+            .count();
+        if let Some(dir) = (1..=dirs)
+            .map(|n| components[..n].join("/"))
+            .find(|dir| !exists(dir))
+        {
+            missing.push((n + 1, line.trim().to_string(), dir));
+        }
+    }
+
+    missing
+}
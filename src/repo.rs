@@ -0,0 +1,386 @@
+//! Repository and configuration access.
+//!
+//! The work is delegated to a backend: libgit2 (via `git2`) by default, gitoxide (via `gix`)
+//! when the `gix` feature is enabled, or a minimal pure-Rust implementation when only the `native`
+//! feature is enabled (`cargo build --no-default-features --features native`), which allows
+//! fully static builds.
+
+use crate::{Error, Result};
+use std::{
+    env, fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+mod config;
+
+#[cfg(feature = "gix")]
+mod gitoxide;
+#[cfg(feature = "gix")]
+use self::gitoxide as backend;
+
+#[cfg(all(feature = "git2", not(feature = "gix")))]
+mod libgit2;
+#[cfg(all(feature = "git2", not(feature = "gix")))]
+use self::libgit2 as backend;
+
+#[cfg(all(feature = "native", not(any(feature = "git2", feature = "gix"))))]
+mod native;
+#[cfg(all(feature = "native", not(any(feature = "git2", feature = "gix"))))]
+use self::native as backend;
+
+#[cfg(not(any(feature = "git2", feature = "gix", feature = "native")))]
+compile_error!("the `fs` feature needs one of the `git2`, `gix` or `native` backend features");
+
+/// A discovered git repository.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Repo {
+    git_dir: PathBuf,
+    workdir: Option<PathBuf>,
+}
+
+impl Repo {
+    /// Find the repository the way git does, honouring `GIT_DIR` and friends.
+    ///
+    /// Fails with [`Error::RepoNotFound`] if there is none.
+    pub fn discover() -> Result<Self> {
+        match backend::discover() {
+            Ok(found) => Ok(Self::new(found)),
+            Err(_) if env::var_os("GIT_DIR").is_none() && nothing_at(&env::current_dir()?) => {
+                Err(Error::RepoNotFound(env::current_dir()?).into())
+            },
+            Err(e) => Err(bad_config(e, found_config_files(&env::current_dir()?))),
+        }
+    }
+
+    /// Find the repository containing `dir`, ignoring `GIT_DIR` and friends.
+    ///
+    /// Fails with [`Error::RepoNotFound`] if there is none.
+    pub fn discover_from<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        match backend::discover_from(dir) {
+            Ok(found) => Ok(Self::new(found)),
+            Err(_) if nothing_at(dir) => Err(Error::RepoNotFound(dir.to_path_buf()).into()),
+            Err(e) => Err(bad_config(e, found_config_files(dir))),
+        }
+    }
+
+    fn new((git_dir, workdir): (PathBuf, Option<PathBuf>)) -> Self {
+        Self {
+            git_dir: fix_path(git_dir),
+            workdir: workdir.map(fix_path),
+        }
+    }
+
+    /// The git directory (usually `.git` in the working tree).
+    pub fn git_dir(&self) -> &Path {
+        &self.git_dir
+    }
+
+    /// The directory that linked worktrees share (with `info/exclude`, say), which is the git
+    /// directory itself outside of them.
+    pub fn common_dir(&self) -> PathBuf {
+        let dir = match fs::read_to_string(self.git_dir.join("commondir")) {
+            Ok(dir) => dir,
+            Err(_) => return self.git_dir.clone(),
+        };
+
+        // Usually '../..', which is better resolved
+        let mut common = PathBuf::new();
+        for component in self.git_dir.join(dir.trim_end()).components() {
+            match component {
+                Component::CurDir => {},
+                Component::ParentDir if common.file_name().is_some() => {
+                    common.pop();
+                },
+                component => common.push(component),
+            }
+        }
+        common
+    }
+
+    /// The root of the working tree, or `None` for a bare repository.
+    pub fn workdir(&self) -> Option<&Path> {
+        self.workdir.as_deref()
+    }
+
+    /// Look up a key in the repository's configuration (including the global configuration).
+    pub fn config(&self, key: &str) -> Result<Option<String>> {
+        // Not every backend reads the per-worktree configuration (`extensions.worktreeConfig`),
+        // where git keeps settings such as `core.sparseCheckoutCone`
+        if let Some(value) = config::get(&self.git_dir.join("config.worktree"), key)? {
+            return Ok(Some(value));
+        }
+
+        backend::repo_config(&self.git_dir, key).map_err(|e| {
+            let mut files = config::global_files();
+            files.push(self.common_dir().join("config"));
+            bad_config(e, files)
+        })
+    }
+
+    /// Look up a boolean key in the repository's configuration; unset keys are `false`.
+    pub fn config_bool(&self, key: &str) -> Result<bool> {
+        Ok(self.config(key)?.is_some_and(|value| is_true(&value)))
+    }
+
+    /// The files git tracks, relative to the root of the working tree with `/` separators, as
+    /// `git ls-files` lists them.
+    pub fn tracked(&self) -> Result<Vec<String>> {
+        backend::tracked(&self.git_dir)
+    }
+
+    /// The files that are neither tracked nor ignored, relative to the root of the working tree
+    /// with `/` separators and a wholly untracked directory once with a trailing `/`, as
+    /// `git status` shows them.
+    ///
+    /// Only `paths` (relative to the root) and what is below them are looked at, unless there
+    /// are none. Fails with [`Error::BareRepo`] for a bare repository.
+    pub fn untracked(&self, paths: &[String]) -> Result<Vec<String>> {
+        match &self.workdir {
+            Some(workdir) => backend::untracked(&self.git_dir, workdir, paths),
+            None => Err(Error::BareRepo(self.git_dir.clone()).into()),
+        }
+    }
+}
+
+/// Look up a path-valued key (such as `core.excludesFile`) in the global git configuration.
+///
+/// A leading `~/` is expanded to the home directory.
+pub fn config_path(key: &str) -> Result<Option<PathBuf>> {
+    backend::config_path(key).map_err(|e| bad_config(e, config::global_files()))
+}
+
+/// Look up `key` in the configuration of the current repository, or in the global
+/// configuration outside one.
+pub fn config(key: &str) -> Result<Option<String>> {
+    match Repo::discover() {
+        Ok(repo) => repo.config(key),
+        Err(_) => backend::global_config(key).map_err(|e| bad_config(e, config::global_files())),
+    }
+}
+
+/// Look up a boolean `key` as [`config`] does; a missing key is false.
+pub fn config_bool(key: &str) -> Result<bool> {
+    Ok(config(key)?.is_some_and(|value| is_true(&value)))
+}
+
+// A backend failing over a config file git would refuse fails with `Error::BadConfig` instead,
+// which says where the problem is
+fn bad_config(e: Box<dyn std::error::Error>, files: Vec<PathBuf>) -> Box<dyn std::error::Error> {
+    if Error::find(&*e).is_some() {
+        return e;
+    }
+    for file in files {
+        if let Some((line, reason)) = fs::read_to_string(&file)
+            .ok()
+            .and_then(|text| config::check(&text))
+        {
+            return Error::BadConfig {
+                file,
+                line,
+                reason: reason.to_string(),
+            }
+            .into();
+        }
+    }
+    e
+}
+
+// The config files that could have kept the repository at or above `dir` from being opened
+fn found_config_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = config::global_files();
+    let repo = dir.ancestors().find_map(|dir| {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+        // A submodule or linked worktree, whose '.git' file names its git directory
+        let text = fs::read_to_string(&dot_git).ok()?;
+        let git_dir = dir.join(text.trim_end().strip_prefix("gitdir:")?.trim());
+        Some(match fs::read_to_string(git_dir.join("commondir")) {
+            Ok(common) => git_dir.join(common.trim_end()),
+            Err(_) => git_dir,
+        })
+    });
+    files.extend(repo.map(|repo| repo.join("config")));
+    files
+}
+
+// Whether there is no '.git' in `dir` or its parents, as when the backend failed to find a
+// repository rather than to open one
+fn nothing_at(dir: &Path) -> bool {
+    let dir = canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    !dir.ancestors().any(|dir| dir.join(".git").exists())
+}
+
+fn is_true(value: &str) -> bool {
+    matches!(
+        value.to_ascii_lowercase().as_str(),
+        "" | "true" | "yes" | "on" | "1"
+    )
+}
+
+/// `path` made absolute, with every symbolic link (and directory junction, on Windows) resolved,
+/// as [`fs::canonicalize`] makes it; but on Windows, without the `\\?\` prefix it adds (as in
+/// `\\?\C:\repo` or `\\?\UNC\server\share\repo`), which git does not understand, wherever
+/// the path means the same without it.
+pub fn canonicalize<P: AsRef<Path>>(path: P) -> io::Result<PathBuf> {
+    let path = fs::canonicalize(path)?;
+
+    #[cfg(windows)]
+    return Ok(simplify(path));
+
+    #[cfg(not(windows))]
+    Ok(path)
+}
+
+// Backends may return paths that use '/' on Windows (even '//server/share/repo'), or that start
+// with '\\?\'; fix that
+fn fix_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut new = PathBuf::new();
+    for e in path.as_ref().iter() {
+        new.push(e)
+    }
+
+    #[cfg(windows)]
+    return simplify(new);
+
+    #[cfg(not(windows))]
+    new
+}
+
+// With '\\' separators throughout, and without any '\\?\' prefix that isn't needed
+#[cfg(windows)]
+fn simplify(path: PathBuf) -> PathBuf {
+    match path.to_str() {
+        Some(text) => PathBuf::from(simplify_str(text)),
+        None => path,
+    }
+}
+
+// The string logic of `simplify`, apart so that it is tested everywhere
+#[cfg_attr(not(windows), allow(dead_code))]
+fn simplify_str(text: &str) -> String {
+    // The prefix of a UNC path keeps the separators it was written with
+    let text = text.replace('/', "\\");
+    let plain = match text.strip_prefix(r"\\?\") {
+        Some(rest) => match rest.strip_prefix(r"UNC\") {
+            Some(share) => format!(r"\\{}", share),
+            None if is_drive(rest) => rest.to_string(),
+            // Such as a volume GUID, which nothing shorter names
+            None => return text,
+        },
+        None => return text,
+    };
+
+    // Without the prefix, a path can't be longer than MAX_PATH, nor have a name ending in '.' or
+    // ' ', or one of a device
+    let same = plain.encode_utf16().count() < 260
+        && plain
+            .split('\\')
+            .skip(1)
+            .all(|name| !name.ends_with(['.', ' ']) && !is_device(name));
+    if same {
+        plain
+    } else {
+        text
+    }
+}
+
+// Whether `path` starts with a drive, as in 'C:\'
+#[cfg_attr(not(windows), allow(dead_code))]
+fn is_drive(path: &str) -> bool {
+    // 'C:' alone is the current directory of the drive
+    matches!(path.as_bytes(), [letter, b':', b'\\', ..] if letter.is_ascii_alphabetic())
+}
+
+// Whether Windows takes the file `name` for a device, as it does 'NUL' and 'con.txt'
+#[cfg_attr(not(windows), allow(dead_code))]
+fn is_device(name: &str) -> bool {
+    let stem = name
+        .split('.')
+        .next()
+        .unwrap_or(name)
+        .trim_end()
+        .to_ascii_uppercase();
+    match stem.as_str() {
+        "CON" | "PRN" | "AUX" | "NUL" => true,
+        _ => match stem.get(..3) {
+            Some("COM" | "LPT") => {
+                stem.len() == 4 && stem.as_bytes()[3].is_ascii_digit() && !stem.ends_with('0')
+            },
+            _ => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drive_paths() {
+        assert_eq!(simplify_str(r"\\?\C:\repo\.git"), r"C:\repo\.git");
+        assert_eq!(simplify_str(r"\\?\C:\"), r"C:\");
+        assert_eq!(simplify_str("C:/repo/.git"), r"C:\repo\.git");
+        // The current directory of the drive, which the prefix does not mean
+        assert_eq!(simplify_str(r"\\?\C:"), r"\\?\C:");
+    }
+
+    #[test]
+    fn unc_paths() {
+        assert_eq!(simplify_str(r"\\?\UNC\server\share"), r"\\server\share");
+        assert_eq!(
+            simplify_str(r"\\?\UNC\server\share\repo"),
+            r"\\server\share\repo"
+        );
+        assert_eq!(simplify_str("//server/share/repo"), r"\\server\share\repo");
+    }
+
+    #[test]
+    fn volume_guids() {
+        let path = r"\\?\Volume{0b1c2d3e-4f50-6172-8394-a5b6c7d8e9f0}\repo";
+        assert_eq!(simplify_str(path), path);
+    }
+
+    #[test]
+    fn names_windows_would_change() {
+        assert_eq!(simplify_str(r"\\?\C:\repo.\x"), r"\\?\C:\repo.\x");
+        assert_eq!(simplify_str(r"\\?\C:\repo \x"), r"\\?\C:\repo \x");
+        assert_eq!(simplify_str(r"\\?\C:\repo\con.txt"), r"\\?\C:\repo\con.txt");
+        assert_eq!(simplify_str(r"\\?\C:\repo\COM1"), r"\\?\C:\repo\COM1");
+        assert_eq!(simplify_str(r"\\?\C:\repo\COM0"), r"C:\repo\COM0");
+        assert_eq!(simplify_str(r"\\?\C:\repo\console"), r"C:\repo\console");
+    }
+
+    #[test]
+    fn devices() {
+        for name in [
+            "CON",
+            "con.txt",
+            "nul",
+            "Aux.tar.gz",
+            "PRN ",
+            "COM1",
+            "lpt9.log",
+        ] {
+            assert!(is_device(name), "{}", name);
+        }
+        for name in [
+            "COM0", "LPT0", "COM10", "CONSOLE", "conf", "null.txt", "COM",
+        ] {
+            assert!(!is_device(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn max_path() {
+        // 'C:\' and the name, short of MAX_PATH (260) or reaching it
+        let short = format!(r"\\?\C:\{}", "a".repeat(256));
+        assert_eq!(simplify_str(&short), &short[4..]);
+        let long = format!(r"\\?\C:\{}", "a".repeat(257));
+        assert_eq!(simplify_str(&long), long);
+        let longer = format!(r"\\?\UNC\server\share\{}", "a".repeat(300));
+        assert_eq!(simplify_str(&longer), longer);
+    }
+}
@@ -0,0 +1,188 @@
+// A minimal reader for git configuration files
+use crate::{Error, Result};
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+// Find the last value of `key` (`section.name` or `section.subsection.name`) in a config file
+pub fn get(file: &Path, key: &str) -> Result<Option<String>> {
+    let text = match fs::read_to_string(file) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    if let Some((line, reason)) = check(&text) {
+        return Err(Error::BadConfig {
+            file: file.to_path_buf(),
+            line,
+            reason: reason.to_string(),
+        }
+        .into());
+    }
+
+    let (section, name) = match key.rsplit_once('.') {
+        Some(parts) => parts,
+        None => return Ok(None),
+    };
+    let section = match section.split_once('.') {
+        Some((section, subsection)) => format!("{}.{}", section.to_lowercase(), subsection),
+        None => section.to_lowercase(),
+    };
+
+    let mut value = None;
+    let mut current = String::new();
+    let mut lines = text.lines();
+
+    while let Some(line) = lines.next() {
+        let mut line = line.trim_start().to_string();
+
+        if line.starts_with('[') {
+            let end = match line.find(']') {
+                Some(end) => end,
+                None => continue,
+            };
+            current = parse_section(&line[1..end]);
+            line = line[end + 1..].trim_start().to_string();
+        }
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        // Join continuation lines
+        while line.ends_with('\\') && !line.ends_with("\\\\") {
+            line.pop();
+            match lines.next() {
+                Some(next) => line.push_str(next),
+                None => break,
+            }
+        }
+
+        let (k, v) = match line.split_once('=') {
+            Some((k, v)) => (k.trim(), parse_value(v)),
+            None => (line.trim(), "true".to_string()),
+        };
+        if current == section && k.eq_ignore_ascii_case(name) {
+            value = Some(v);
+        }
+    }
+
+    Ok(value)
+}
+
+// The system and global config files, in the order git reads them
+pub fn global_files() -> Vec<PathBuf> {
+    let mut files = vec![PathBuf::from("/etc/gitconfig")];
+
+    if let Some(file) = env::var_os("GIT_CONFIG_SYSTEM") {
+        files[0] = PathBuf::from(file);
+    }
+
+    if let Some(file) = env::var_os("GIT_CONFIG_GLOBAL") {
+        files.push(PathBuf::from(file));
+    } else {
+        match env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => files.push(Path::new(&dir).join("git/config")),
+            _ => files.extend(dirs::home_dir().map(|home| home.join(".config/git/config"))),
+        }
+        files.extend(dirs::home_dir().map(|home| home.join(".gitconfig")));
+    }
+
+    files
+}
+
+// The first line (from 1) of a config file that git would refuse, and why
+pub fn check(text: &str) -> Option<(usize, &'static str)> {
+    let mut lines = text.lines().enumerate();
+
+    while let Some((i, line)) = lines.next() {
+        let mut line = line.trim_start();
+        if line.starts_with('[') {
+            let end = match line.find(']') {
+                Some(end) => end,
+                None => return Some((i + 1, "missing ']' in section header")),
+            };
+            let section = line[1..end].split(char::is_whitespace).next().unwrap_or("");
+            if section.is_empty()
+                || !section
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+            {
+                return Some((i + 1, "invalid section name"));
+            }
+            line = line[end + 1..].trim_start();
+        }
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        let key = line.split('=').next().unwrap_or(line).trim();
+        let valid = key.starts_with(|c: char| c.is_ascii_alphabetic())
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        if !valid {
+            return Some((i + 1, "invalid key name"));
+        }
+        // Skip continuation lines
+        let mut line = line.to_string();
+        while line.ends_with('\\') && !line.ends_with("\\\\") {
+            match lines.next() {
+                Some((_, next)) => line = next.to_string(),
+                None => break,
+            }
+        }
+    }
+
+    None
+}
+
+fn parse_section(header: &str) -> String {
+    match header.split_once(|c: char| c.is_whitespace()) {
+        Some((section, subsection)) => {
+            let subsection = subsection.trim().trim_matches('"').replace("\\\"", "\"");
+            format!(
+                "{}.{}",
+                section.to_lowercase(),
+                subsection.replace("\\\\", "\\")
+            )
+        },
+        // Deprecated `[section.subsection]` syntax; the subsection is case-insensitive
+        None => header.to_lowercase(),
+    }
+}
+
+fn parse_value(raw: &str) -> String {
+    let mut value = String::new();
+    let mut quoted = false;
+    let mut whitespace = String::new();
+    let mut chars = raw.trim().chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' => {
+                value.push_str(&whitespace);
+                whitespace.clear();
+                match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('b') => {
+                        value.pop();
+                    },
+                    Some(c) => value.push(c),
+                    None => {},
+                }
+            },
+            '#' | ';' if !quoted => break,
+            c if c.is_whitespace() && !quoted => whitespace.push(c),
+            c => {
+                if !value.is_empty() {
+                    value.push_str(&whitespace);
+                }
+                whitespace.clear();
+                value.push(c);
+            },
+        }
+    }
+
+    value
+}
@@ -0,0 +1,111 @@
+use crate::{bytes, Result};
+use gix::{
+    bstr::BString,
+    config::{self, path::interpolate},
+    dir::{
+        entry::{Kind, Status},
+        walk::{delegate::Collect, EmissionMode},
+    },
+};
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+pub fn discover() -> Result<(PathBuf, Option<PathBuf>)> {
+    let cwd = env::current_dir()?;
+    let repo =
+        gix::ThreadSafeRepository::discover_with_environment_overrides(&cwd)?.to_thread_local();
+    // gix leaves them as relative as `GIT_DIR` and `GIT_WORK_TREE` are
+    Ok((
+        cwd.join(repo.git_dir()),
+        repo.workdir().map(|dir| cwd.join(dir)),
+    ))
+}
+
+pub fn discover_from(dir: &Path) -> Result<(PathBuf, Option<PathBuf>)> {
+    let repo = gix::ThreadSafeRepository::discover(dir)?.to_thread_local();
+    Ok((
+        repo.git_dir().to_path_buf(),
+        repo.workdir().map(PathBuf::from),
+    ))
+}
+
+pub fn config_path(key: &str) -> Result<Option<PathBuf>> {
+    let file = config::File::from_globals()?;
+
+    match file.path(key) {
+        None => Ok(None),
+        Some(path) => {
+            let home = dirs::home_dir();
+            let path = path.interpolate(interpolate::Context {
+                home_dir: home.as_deref(),
+                ..Default::default()
+            })?;
+            Ok(Some(path))
+        },
+    }
+}
+
+pub fn global_config(key: &str) -> Result<Option<String>> {
+    let file = config::File::from_globals()?;
+    Ok(file.string(key).map(|v| v.to_string()))
+}
+
+pub fn repo_config(git_dir: &Path, key: &str) -> Result<Option<String>> {
+    let repo = gix::open(git_dir)?;
+    let value = repo.config_snapshot().string(key).map(|v| v.to_string());
+    Ok(value)
+}
+
+pub fn tracked(git_dir: &Path) -> Result<Vec<String>> {
+    let index = gix::open(git_dir)?.index_or_empty()?;
+    let mut paths: Vec<String> = index
+        .entries()
+        .iter()
+        .map(|entry| bytes::decode(entry.path(&index)))
+        .collect();
+    // A conflicted file has an entry for each side
+    paths.dedup();
+    Ok(paths)
+}
+
+pub fn untracked(git_dir: &Path, workdir: &Path, paths: &[String]) -> Result<Vec<String>> {
+    let mut repo = gix::open(git_dir)?;
+    repo.set_workdir(workdir.to_path_buf())?;
+    let index = repo.index_or_empty()?;
+
+    let options = repo
+        .dirwalk_options()?
+        .emit_untracked(EmissionMode::CollapseDirectory);
+    let patterns: Vec<BString> = paths
+        .iter()
+        .map(|path| {
+            let mut pattern = BString::from(":(literal)");
+            pattern.extend_from_slice(&bytes::encode(path));
+            pattern
+        })
+        .collect();
+    let mut collect = Collect::default();
+    repo.dirwalk(
+        &index,
+        patterns,
+        &AtomicBool::new(false),
+        options,
+        &mut collect,
+    )?;
+
+    Ok(collect
+        .into_entries_by_path()
+        .into_iter()
+        .filter(|(entry, _)| entry.status == Status::Untracked)
+        .map(|(entry, _)| {
+            let path = bytes::decode(&entry.rela_path);
+            match entry.disk_kind {
+                Some(Kind::Directory | Kind::Repository) => path + "/",
+                _ => path,
+            }
+        })
+        .collect())
+}
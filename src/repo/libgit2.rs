@@ -0,0 +1,74 @@
+use crate::{bytes, Result};
+use git2 as git;
+use std::path::{Path, PathBuf};
+
+pub fn discover() -> Result<(PathBuf, Option<PathBuf>)> {
+    let repo = git::Repository::open_from_env()?;
+    Ok((repo.path().to_path_buf(), repo.workdir().map(PathBuf::from)))
+}
+
+pub fn discover_from(dir: &Path) -> Result<(PathBuf, Option<PathBuf>)> {
+    let repo = git::Repository::discover(dir)?;
+    Ok((repo.path().to_path_buf(), repo.workdir().map(PathBuf::from)))
+}
+
+pub fn config_path(key: &str) -> Result<Option<PathBuf>> {
+    match git::Config::open_default()?.get_path(key) {
+        Ok(path) => Ok(Some(path)),
+        Err(error)
+            if error.class() == git::ErrorClass::Config
+                && error.code() == git::ErrorCode::NotFound =>
+        {
+            Ok(None)
+        },
+        Err(error) => Err(error.into()),
+    }
+}
+
+pub fn global_config(key: &str) -> Result<Option<String>> {
+    match git::Config::open_default()?.get_string(key) {
+        Ok(value) => Ok(Some(value)),
+        Err(error) if error.code() == git::ErrorCode::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+pub fn repo_config(git_dir: &Path, key: &str) -> Result<Option<String>> {
+    match git::Repository::open(git_dir)?.config()?.get_string(key) {
+        Ok(value) => Ok(Some(value)),
+        Err(error) if error.code() == git::ErrorCode::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+pub fn tracked(git_dir: &Path) -> Result<Vec<String>> {
+    let mut paths: Vec<String> = git::Repository::open(git_dir)?
+        .index()?
+        .iter()
+        .map(|entry| bytes::decode(&entry.path))
+        .collect();
+    // A conflicted file has an entry for each side
+    paths.dedup();
+    Ok(paths)
+}
+
+pub fn untracked(git_dir: &Path, workdir: &Path, paths: &[String]) -> Result<Vec<String>> {
+    let repo = git::Repository::open(git_dir)?;
+    repo.set_workdir(workdir, false)?;
+
+    let mut options = git::StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(false)
+        .disable_pathspec_match(true);
+    for path in paths {
+        options.pathspec(bytes::encode(path).into_owned());
+    }
+
+    let statuses = repo.statuses(Some(&mut options))?;
+    Ok(statuses
+        .iter()
+        .filter(|entry| entry.status().is_wt_new())
+        .map(|entry| bytes::decode(entry.path_bytes()))
+        .collect())
+}
@@ -0,0 +1,374 @@
+// Minimal native implementations of repository discovery and config lookup, for builds without
+// libgit2 or gitoxide; `include` directives and conditional includes are not supported
+use super::config;
+use crate::{bytes, Kind, Result, Rules, Target};
+use std::{
+    collections::BTreeSet,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+pub fn discover() -> Result<(PathBuf, Option<PathBuf>)> {
+    let cwd = env::current_dir()?;
+
+    if let Some(dir) = env::var_os("GIT_DIR") {
+        let mut git_dir = cwd.join(dir);
+        if git_dir.is_file() {
+            git_dir = read_gitdir_file(&git_dir)?;
+        }
+        let workdir = match env::var_os("GIT_WORK_TREE") {
+            Some(dir) => Some(cwd.join(dir)),
+            None if is_bare(&git_dir) => None,
+            None => Some(worktree(&git_dir)?.unwrap_or(cwd)),
+        };
+        return Ok((git_dir, workdir));
+    }
+
+    discover_from(&cwd)
+}
+
+pub fn discover_from(cwd: &Path) -> Result<(PathBuf, Option<PathBuf>)> {
+    let ceilings: Vec<PathBuf> = env::var_os("GIT_CEILING_DIRECTORIES")
+        .map(|dirs| env::split_paths(&dirs).collect())
+        .unwrap_or_default();
+
+    for dir in cwd.ancestors() {
+        // As git does, never look in a ceiling directory, though it may be where the search starts
+        if dir != cwd && ceilings.iter().any(|ceiling| ceiling == dir) {
+            break;
+        }
+        let dot_git = dir.join(".git");
+
+        if dot_git.is_file() {
+            return Ok((read_gitdir_file(&dot_git)?, Some(dir.to_path_buf())));
+        }
+        if is_git_dir(&dot_git) {
+            return Ok((dot_git, Some(dir.to_path_buf())));
+        }
+        if is_git_dir(dir) {
+            let workdir = if is_bare(dir) {
+                None
+            } else {
+                match worktree(dir)? {
+                    Some(workdir) => Some(workdir),
+                    None => dir.parent().map(PathBuf::from),
+                }
+            };
+            return Ok((dir.to_path_buf(), workdir));
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("could not find repository at '{}'", cwd.to_string_lossy()),
+    )
+    .into())
+}
+
+pub fn config_path(key: &str) -> Result<Option<PathBuf>> {
+    let mut value = None;
+
+    for file in config::global_files() {
+        if let Some(v) = config::get(&file, key)? {
+            value = Some(v);
+        }
+    }
+
+    Ok(value.map(|v| expand_home(&v)))
+}
+
+pub fn global_config(key: &str) -> Result<Option<String>> {
+    let mut value = None;
+
+    for file in config::global_files() {
+        if let Some(v) = config::get(&file, key)? {
+            value = Some(v);
+        }
+    }
+
+    Ok(value)
+}
+
+pub fn repo_config(git_dir: &Path, key: &str) -> Result<Option<String>> {
+    let mut value = None;
+
+    for file in config::global_files()
+        .into_iter()
+        .chain([common_dir(git_dir).join("config")])
+    {
+        if let Some(v) = config::get(&file, key)? {
+            value = Some(v);
+        }
+    }
+
+    Ok(value)
+}
+
+// Linked worktrees share most of the repository through the common directory
+fn common_dir(git_dir: &Path) -> PathBuf {
+    match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(dir) => git_dir.join(dir.trim_end()),
+        Err(_) => git_dir.to_path_buf(),
+    }
+}
+
+fn is_git_dir(dir: &Path) -> bool {
+    dir.join("HEAD").is_file() && dir.join("objects").is_dir() && dir.join("refs").is_dir()
+}
+
+fn is_bare(git_dir: &Path) -> bool {
+    matches!(
+        config::get(&git_dir.join("config"), "core.bare"),
+        Ok(Some(value)) if value.eq_ignore_ascii_case("true") || value == "1" || value.eq_ignore_ascii_case("yes")
+    )
+}
+
+// The working tree that `core.worktree` names, relative to the git directory, as it does for
+// the git directory of a submodule in '.git/modules' of its superproject
+fn worktree(git_dir: &Path) -> Result<Option<PathBuf>> {
+    let workdir = config::get(&git_dir.join("config"), "core.worktree")?;
+    Ok(workdir.map(|dir| resolve(&git_dir.join(dir))))
+}
+
+// A `.git` file (worktrees, submodules) contains a single `gitdir: <path>` line
+fn read_gitdir_file(file: &Path) -> Result<PathBuf> {
+    let text = fs::read_to_string(file)?;
+
+    match text.trim_end().strip_prefix("gitdir:") {
+        Some(path) => Ok(resolve(
+            &file
+                .parent()
+                .unwrap_or_else(|| Path::new(""))
+                .join(path.trim()),
+        )),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid gitfile format: {}", file.to_string_lossy()),
+        )
+        .into()),
+    }
+}
+
+// Without the '..' of a relative path such as '../.git/modules/sub', as git resolves it
+fn resolve(path: &Path) -> PathBuf {
+    super::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn expand_home(value: &str) -> PathBuf {
+    match (value.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(value),
+    }
+}
+
+pub fn tracked(git_dir: &Path) -> Result<Vec<String>> {
+    let path = git_dir.join("index");
+    let data = match fs::read(&path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+    let sha256 = config::get(
+        &common_dir(git_dir).join("config"),
+        "extensions.objectFormat",
+    )?
+    .is_some_and(|format| format.eq_ignore_ascii_case("sha256"));
+
+    read_index(&data, if sha256 { 32 } else { 20 }).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid index file: {}", path.to_string_lossy()),
+        )
+        .into()
+    })
+}
+
+pub fn untracked(git_dir: &Path, workdir: &Path, paths: &[String]) -> Result<Vec<String>> {
+    let mut walk = Walk {
+        root: workdir,
+        paths,
+        tracked: tracked(git_dir)?.into_iter().collect(),
+        rules: vec![
+            (
+                String::new(),
+                read_rules(&Target::Global.locate(Kind::Ignore)?)?,
+            ),
+            (
+                String::new(),
+                read_rules(&common_dir(git_dir).join("info").join("exclude"))?,
+            ),
+        ],
+    };
+    let mut found = vec![];
+    walk.visit("", &mut found)?;
+    Ok(found)
+}
+
+// The paths of the entries of an index file (versions 2 to 4), a conflicted one once
+fn read_index(data: &[u8], hash_len: usize) -> Option<Vec<String>> {
+    let word = |at: usize| Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?));
+    if data.get(..4)? != b"DIRC" {
+        return None;
+    }
+    let version = word(4)?;
+    if !(2..=4).contains(&version) {
+        return None;
+    }
+
+    let mut paths = vec![];
+    let mut previous: Vec<u8> = vec![];
+    let mut at = 12;
+    for _ in 0..word(8)? {
+        // Times, device, inode, mode, ids and size come before the hash and the flags
+        let flags_at = at + 40 + hash_len;
+        let flags = u16::from_be_bytes(data.get(flags_at..flags_at + 2)?.try_into().ok()?);
+        let mut name_at = flags_at + 2;
+        if version >= 3 && flags & 0x4000 != 0 {
+            name_at += 2;
+        }
+
+        let path = if version == 4 {
+            // The name is what remains of the previous one, less some bytes, then a suffix
+            let (strip, len) = varint(data.get(name_at..)?)?;
+            name_at += len;
+            let end = name_at + data.get(name_at..)?.iter().position(|&b| b == 0)?;
+            let mut path = previous[..previous.len().checked_sub(strip)?].to_vec();
+            path.extend_from_slice(&data[name_at..end]);
+            at = end + 1;
+            path
+        } else {
+            let end = name_at + data.get(name_at..)?.iter().position(|&b| b == 0)?;
+            // Entries are padded with NULs to a multiple of eight bytes
+            at += (end - at + 8) & !7;
+            data[name_at..end].to_vec()
+        };
+
+        // A conflicted file has an entry for each side
+        if path != previous {
+            paths.push(bytes::decode(&path));
+        }
+        previous = path;
+    }
+
+    Some(paths)
+}
+
+// A number in git's offset encoding, and how many bytes it took
+fn varint(data: &[u8]) -> Option<(usize, usize)> {
+    let mut byte = *data.first()?;
+    let mut value = usize::from(byte & 0x7f);
+    let mut len = 1;
+    while byte & 0x80 != 0 {
+        byte = *data.get(len)?;
+        len += 1;
+        value = ((value + 1) << 7) | usize::from(byte & 0x7f);
+    }
+    Some((value, len))
+}
+
+fn read_rules(path: &Path) -> io::Result<Rules> {
+    match bytes::read(path) {
+        Ok(text) => Ok(Rules::parse(&text)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Rules::parse("")),
+        Err(e) => Err(e),
+    }
+}
+
+// A walk of the working tree for what `git status` shows as untracked
+struct Walk<'a> {
+    root: &'a Path,
+    paths: &'a [String],
+    tracked: BTreeSet<String>,
+    // The rules of each ignore file that applies, in increasing order of precedence, with the
+    // directory they are relative to
+    rules: Vec<(String, Rules)>,
+}
+
+impl Walk<'_> {
+    fn visit(&mut self, dir: &str, found: &mut Vec<String>) -> io::Result<()> {
+        let path = self.root.join(bytes::to_os(dir));
+        let own_rules = path.join(".gitignore").is_file();
+        if own_rules {
+            self.rules
+                .push((dir.to_string(), read_rules(&path.join(".gitignore"))?));
+        }
+
+        let mut entries = fs::read_dir(&path)?.collect::<io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            let name = bytes::from_os(&entry.file_name());
+            if dir.is_empty() && name == ".git" {
+                continue;
+            }
+            let path = if dir.is_empty() {
+                name
+            } else {
+                format!("{}/{}", dir, name)
+            };
+            if !self.wanted(&path) {
+                continue;
+            }
+
+            if !entry.file_type()?.is_dir() {
+                if !self.tracked.contains(&path) && !self.is_ignored(&path, false) {
+                    found.push(path);
+                }
+            } else if self.tracked.contains(&path) || self.is_ignored(&path, true) {
+                // A submodule, or a directory nothing in which can be re-included
+            } else if self.has_tracked(&path) {
+                self.visit(&path, found)?;
+            } else if entry.path().join(".git").exists() {
+                found.push(path + "/");
+            } else {
+                // A wholly untracked directory shows once, unless there is nothing to show in it
+                let mut inside = vec![];
+                self.visit(&path, &mut inside)?;
+                if !inside.is_empty() {
+                    found.push(path + "/");
+                }
+            }
+        }
+
+        if own_rules {
+            self.rules.pop();
+        }
+        Ok(())
+    }
+
+    // Whether `path` is one of the paths asked about, in one, or leads to one
+    fn wanted(&self, path: &str) -> bool {
+        let within = |outer: &str, inner: &str| {
+            inner == outer || inner.starts_with(outer) && inner[outer.len()..].starts_with('/')
+        };
+        self.paths.is_empty()
+            || self
+                .paths
+                .iter()
+                .any(|wanted| within(wanted, path) || within(path, wanted))
+    }
+
+    fn has_tracked(&self, dir: &str) -> bool {
+        let prefix = format!("{}/", dir);
+        self.tracked
+            .range(prefix.clone()..)
+            .next()
+            .is_some_and(|path| path.starts_with(&prefix))
+    }
+
+    // The nearest ignore file with a rule matching `path` decides, as in git; the directories it
+    // is in were already found not to be ignored
+    fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find_map(|(base, rules)| {
+                let path = if base.is_empty() {
+                    path
+                } else {
+                    path.strip_prefix(base.as_str())?.strip_prefix('/')?
+                };
+                rules.last_match(path, is_dir)
+            })
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,136 @@
+use crate::export::parse_rule;
+use regex::{Regex, RegexBuilder};
+use std::{error::Error, fmt, str::FromStr};
+
+/// A sed-style substitution command, `s/REGEX/REPLACEMENT/FLAGS`.
+///
+/// Any character may stand in for `/`, and escaping it with `\` includes it literally. The regex
+/// uses the [`regex`](https://docs.rs/regex) crate's syntax, which is close to extended regular
+/// expressions; in the replacement, `&` is the whole match and `\1` to `\9` are groups, as in
+/// sed. The flags are `g` (replace every match, not just the first) and `i` (ignore case).
+#[derive(Clone, Debug)]
+pub struct Substitution {
+    regex: Regex,
+    replacement: String,
+    global: bool,
+}
+
+/// An error parsing a [`Substitution`].
+#[derive(Debug)]
+pub struct ParseSubstitutionError(String);
+
+impl fmt::Display for ParseSubstitutionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for ParseSubstitutionError {}
+
+impl FromStr for Substitution {
+    type Err = ParseSubstitutionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |message: &str| ParseSubstitutionError(format!("{}: '{}'", message, s));
+
+        let mut chars = s.chars();
+        let delimiter = match (chars.next(), chars.next()) {
+            (Some('s'), Some(d)) if d != '\\' && d != '\n' => d,
+            _ => return Err(error("Expected a substitution (s/REGEX/REPLACEMENT/)")),
+        };
+
+        let mut parts = vec![String::new()];
+        let mut escaped = false;
+        for c in chars {
+            if parts.len() == 3 {
+                parts[2].push(c);
+            } else if escaped {
+                if c != delimiter {
+                    parts.last_mut().unwrap().push('\\');
+                }
+                parts.last_mut().unwrap().push(c);
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == delimiter {
+                parts.push(String::new());
+            } else {
+                parts.last_mut().unwrap().push(c);
+            }
+        }
+        if parts.len() != 3 || escaped {
+            return Err(error("Unterminated substitution"));
+        }
+
+        let (mut global, mut ignore_case) = (false, false);
+        for flag in parts[2].chars() {
+            match flag {
+                'g' => global = true,
+                'i' | 'I' => ignore_case = true,
+                _ => return Err(error(&format!("Unknown flag '{}'", flag))),
+            }
+        }
+
+        let regex = RegexBuilder::new(&parts[0])
+            .case_insensitive(ignore_case)
+            .build()
+            .map_err(|e| ParseSubstitutionError(e.to_string()))?;
+
+        Ok(Self {
+            regex,
+            replacement: sed_replacement(&parts[1]),
+            global,
+        })
+    }
+}
+
+impl Substitution {
+    /// Apply the substitution to a single line.
+    pub fn apply<'a>(&self, line: &'a str) -> std::borrow::Cow<'a, str> {
+        if self.global {
+            self.regex.replace_all(line, self.replacement.as_str())
+        } else {
+            self.regex.replace(line, self.replacement.as_str())
+        }
+    }
+
+    /// Apply the substitution to every pattern of the ignore file contents `text`, leaving
+    /// comments and blank lines alone.
+    pub fn apply_patterns(&self, text: &str) -> String {
+        let mut result = String::new();
+
+        for line in text.lines() {
+            if parse_rule(line).is_some() {
+                result.push_str(&self.apply(line));
+            } else {
+                result.push_str(line);
+            }
+            result.push('\n');
+        }
+
+        result
+    }
+}
+
+// Translate a sed replacement into the `regex` crate's syntax
+fn sed_replacement(sed: &str) -> String {
+    let mut replacement = String::new();
+    let mut chars = sed.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '&' => replacement.push_str("${0}"),
+            '$' => replacement.push_str("$$"),
+            '\\' => match chars.next() {
+                Some(d) if d.is_ascii_digit() => replacement.push_str(&format!("${{{}}}", d)),
+                Some('n') => replacement.push('\n'),
+                Some('$') => replacement.push_str("$$"),
+                Some(other) => replacement.push(other),
+                None => replacement.push('\\'),
+            },
+            _ => replacement.push(c),
+        }
+    }
+
+    replacement
+}
@@ -0,0 +1,56 @@
+use crate::{merge, MergeOptions};
+use std::{collections::HashSet, io, str::FromStr};
+
+/// A set operation on the patterns of two ignore files; see [`set_op`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SetOp {
+    /// The patterns in either file.
+    Union,
+    /// The patterns in both files.
+    Intersect,
+    /// The patterns in the first file but not the second.
+    Subtract,
+}
+
+impl FromStr for SetOp {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s {
+            "union" => Ok(Self::Union),
+            "intersect" => Ok(Self::Intersect),
+            "subtract" => Ok(Self::Subtract),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Unknown operation '{}' (expected union, intersect or subtract)",
+                    s
+                ),
+            )),
+        }
+    }
+}
+
+/// Apply `op` to the patterns of the ignore file contents `a` and `b`.
+///
+/// Patterns are compared after trimming; comments and blank lines are ignored. The result is
+/// normalized as by [`merge`](crate::merge()) with the default options.
+pub fn set_op(op: SetOp, a: &str, b: &str) -> String {
+    let patterns = |text: &str| -> Vec<String> {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect()
+    };
+    let (a, b) = (patterns(a), patterns(b));
+    let in_b: HashSet<&str> = b.iter().map(String::as_str).collect();
+
+    let result: Vec<&String> = match op {
+        SetOp::Union => a.iter().chain(&b).collect(),
+        SetOp::Intersect => a.iter().filter(|p| in_b.contains(p.as_str())).collect(),
+        SetOp::Subtract => a.iter().filter(|p| !in_b.contains(p.as_str())).collect(),
+    };
+
+    merge("", &result, MergeOptions::default())
+}
@@ -0,0 +1,94 @@
+use crate::{walk::walk, Kind, Repo, Result, Target};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Where an ignore file sits in git's precedence order.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Level {
+    /// The global ignore file (`core.excludesFile`).
+    Global,
+    /// The internal repository ignore file (`$GIT_COMMON_DIR/info/exclude`).
+    Internal,
+    /// A `.gitignore` in the working tree.
+    Tree,
+}
+
+/// An ignore file that contributes to a repository's effective rules.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Source {
+    /// The path of the file, which might not exist.
+    pub path: PathBuf,
+    /// The level of the file.
+    pub level: Level,
+    /// The directory the patterns are relative to, from the working tree root, using `/`
+    /// separators; empty for the root itself and for the global and internal files.
+    pub base: String,
+}
+
+/// List the ignore files of `repo` in evaluation order, from lowest to highest precedence.
+///
+/// Nested `.gitignore` files are found by walking the working tree, skipping `.git` and any
+/// nested repositories.
+pub fn sources(repo: &Repo) -> Result<Vec<Source>> {
+    let mut sources = vec![
+        Source {
+            path: Target::Global.locate(Kind::Ignore)?,
+            level: Level::Global,
+            base: String::new(),
+        },
+        Source {
+            path: repo.common_dir().join("info").join("exclude"),
+            level: Level::Internal,
+            base: String::new(),
+        },
+    ];
+
+    if let Some(root) = repo.workdir() {
+        for path in find_ignore_files(root)? {
+            let base = path
+                .parent()
+                .and_then(|dir| dir.strip_prefix(root).ok())
+                .map(|dir| {
+                    dir.iter()
+                        .map(|c| c.to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/")
+                })
+                .unwrap_or_default();
+            sources.push(Source {
+                path,
+                level: Level::Tree,
+                base,
+            });
+        }
+    }
+
+    Ok(sources)
+}
+
+// Parents come before their children, so precedence increases down the list
+fn find_ignore_files(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let found = walk(root, |dir, entries| {
+        // A nested repository has ignore files of its own
+        if dir != root && entries.iter().any(|entry| entry.file_name() == ".git") {
+            return (None, vec![]);
+        }
+
+        let file = entries
+            .iter()
+            .find(|entry| entry.file_name() == Kind::Ignore.file_name())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file());
+        let subdirs = entries
+            .iter()
+            .filter(|entry| entry.file_name() != ".git")
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+            .map(|entry| entry.path())
+            .collect();
+        (file, subdirs)
+    })?;
+
+    Ok(found.into_iter().map(|(_, file)| file).collect())
+}
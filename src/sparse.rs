@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+/// Expand directories into the patterns that cone-mode sparse-checkout requires.
+///
+/// Adding `a/b` yields `/*`, `!/*/`, `/a/`, `!/a/*/` and `/a/b/`; ancestors that are already
+/// included recursively by `existing` are not restricted again.
+pub fn cone_patterns<S: AsRef<str>>(existing: &str, dirs: &[S]) -> Vec<String> {
+    let lines: HashSet<&str> = existing.lines().map(str::trim).collect();
+    let mut patterns = vec!["/*".to_string(), "!/*/".to_string()];
+
+    for dir in dirs {
+        let dir = dir.as_ref().trim().trim_matches('/');
+        if dir.is_empty() {
+            continue;
+        }
+
+        let mut prefix = String::from("/");
+        let mut parts = dir.split('/').filter(|p| !p.is_empty()).peekable();
+
+        while let Some(part) = parts.next() {
+            prefix.push_str(part);
+            prefix.push('/');
+
+            let parent = format!("!{}*/", prefix);
+            if lines.contains(prefix.as_str()) && !lines.contains(parent.as_str()) {
+                // Already included recursively
+                break;
+            }
+
+            patterns.push(prefix.clone());
+            if parts.peek().is_some() {
+                patterns.push(parent);
+            }
+        }
+    }
+
+    patterns
+}
+
+/// Drop the lines of the cone-mode patterns `existing` that would keep `dirs` from being included
+/// recursively: the `!/a/*/` left by adding `a/b` before adding `a`, and what `/a/` then covers,
+/// such as `/a/b/`.
+pub fn lift_cone_restrictions<S: AsRef<str>>(existing: &str, dirs: &[S]) -> String {
+    let prefixes: Vec<String> = dirs
+        .iter()
+        .map(|dir| dir.as_ref().trim().trim_matches('/'))
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| format!("/{}/", dir))
+        .collect();
+
+    existing
+        .split_inclusive('\n')
+        .filter(|line| {
+            let line = line.trim();
+            let pattern = line.strip_prefix('!').unwrap_or(line);
+            !prefixes
+                .iter()
+                .any(|prefix| pattern.len() > prefix.len() && pattern.starts_with(prefix.as_str()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested() {
+        assert_eq!(
+            cone_patterns("", &["a/b"]),
+            ["/*", "!/*/", "/a/", "!/a/*/", "/a/b/"]
+        );
+        assert_eq!(cone_patterns("/*\n!/*/\n/a/\n", &["a/b"]), ["/*", "!/*/"]);
+        assert_eq!(
+            cone_patterns("/*\n!/*/\n/a/\n!/a/*/\n/a/b/\n", &["a/c"]),
+            ["/*", "!/*/", "/a/", "!/a/*/", "/a/c/"]
+        );
+    }
+
+    #[test]
+    fn parent_after_child() {
+        let existing = "/*\n!/*/\n/a/\n!/a/*/\n/a/b/\n!/a/b/*/\n/a/b/c/\n/d/\n";
+        let lifted = lift_cone_restrictions(existing, &["a"]);
+        assert_eq!(lifted, "/*\n!/*/\n/a/\n/d/\n");
+        assert_eq!(cone_patterns(&lifted, &["a"]), ["/*", "!/*/"]);
+
+        assert_eq!(
+            lift_cone_restrictions(existing, &["a/b/"]),
+            "/*\n!/*/\n/a/\n!/a/*/\n/a/b/\n/d/\n"
+        );
+        assert_eq!(lift_cone_restrictions(existing, &["d"]), existing);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::{repo, Error, Repo, Result};
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The file to operate on.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum Target {
+    /// The file of the given [`Kind`] in the current directory (`.gitignore` by default).
+    #[default]
+    Local,
+    /// An arbitrary file; relative paths are resolved against the current directory. Inside a
+    /// git directory, only the files in `info` that git reads can be chosen, failing with
+    /// [`Error::InGitDir`] otherwise (but see
+    /// [`locate_allowing_git_dir`](Self::locate_allowing_git_dir)).
+    File(PathBuf),
+    /// The global file (`core.excludesFile` or `core.attributesFile`, say). For ignore patterns,
+    /// the `ignore.dotfiles` config takes precedence, naming a file kept in a dotfiles repository.
+    Global,
+    /// The internal repository file (`info/exclude` or `info/attributes` in `$GIT_COMMON_DIR`,
+    /// which linked worktrees share).
+    Internal,
+    /// The root-level repository file (`.gitignore` or `.gitattributes` in the working tree
+    /// root).
+    Root,
+    /// The sparse-checkout pattern file (`$GIT_DIR/info/sparse-checkout`).
+    Sparse,
+}
+
+/// The kind of file a [`Target`] refers to.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Kind {
+    /// Ignore patterns (`.gitignore`).
+    #[default]
+    Ignore,
+    /// Attributes (`.gitattributes`).
+    Attributes,
+    /// Docker build context exclusions (`.dockerignore`); see [`to_dockerignore`](crate::to_dockerignore).
+    Docker,
+    /// npm package exclusions (`.npmignore`); same syntax as `.gitignore`.
+    Npm,
+    /// Search tool exclusions; same syntax as `.gitignore`.
+    Tool(Tool),
+}
+
+/// Search tools that read their own ignore files.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Tool {
+    /// `.ignore`, read by both ripgrep and fd.
+    Any,
+    /// `.rgignore`, read only by ripgrep.
+    Ripgrep,
+    /// `.fdignore`, read only by fd (which also has a global file, `$XDG_CONFIG_HOME/fd/ignore`).
+    Fd,
+}
+
+impl FromStr for Tool {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s {
+            "any" | "ignore" => Ok(Self::Any),
+            "ripgrep" | "rg" => Ok(Self::Ripgrep),
+            "fd" => Ok(Self::Fd),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown tool '{}' (expected any, ripgrep or fd)", s),
+            )),
+        }
+    }
+}
+
+impl Kind {
+    /// The name of the file in a working tree directory.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Ignore => ".gitignore",
+            Self::Attributes => ".gitattributes",
+            Self::Docker => ".dockerignore",
+            Self::Npm => ".npmignore",
+            Self::Tool(Tool::Any) => ".ignore",
+            Self::Tool(Tool::Ripgrep) => ".rgignore",
+            Self::Tool(Tool::Fd) => ".fdignore",
+        }
+    }
+
+    fn info_name(self) -> Option<&'static str> {
+        match self {
+            Self::Ignore => Some("exclude"),
+            Self::Attributes => Some("attributes"),
+            Self::Docker | Self::Npm | Self::Tool(_) => None,
+        }
+    }
+
+    // The git config keys, in order of precedence, and the default path under `$XDG_CONFIG_HOME`
+    fn global(self) -> Option<(&'static [&'static str], &'static str)> {
+        match self {
+            Self::Ignore => Some((&["ignore.dotfiles", "core.excludesFile"], "git/ignore")),
+            Self::Attributes => Some((&["core.attributesFile"], "git/attributes")),
+            Self::Tool(Tool::Fd) => Some((&[], "fd/ignore")),
+            Self::Docker | Self::Npm | Self::Tool(_) => None,
+        }
+    }
+}
+
+impl Target {
+    /// The default target for files of the given kind, as the `ignore.target` config names it:
+    /// `local`, `root`, `internal` or `global`. Unset, or naming a file there is none of for the
+    /// kind, it is [`Local`](Self::Local).
+    pub fn configured(kind: Kind) -> Result<Self> {
+        let target = match repo::config("ignore.target")?.as_deref() {
+            None | Some("local") => Self::Local,
+            Some("root") => Self::Root,
+            Some("internal") => Self::Internal,
+            Some("global") => Self::Global,
+            Some(value) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Unknown ignore.target '{}' (expected local, root, internal or global)",
+                        value
+                    ),
+                )
+                .into())
+            },
+        };
+        Ok(match target {
+            Self::Internal if kind.info_name().is_none() => Self::Local,
+            Self::Global if kind.global().is_none() => Self::Local,
+            target => target,
+        })
+    }
+
+    /// Resolve the target to the absolute path of an ignore file; see [`resolve`](Self::resolve).
+    pub fn path(&self) -> Result<PathBuf> {
+        self.resolve(Kind::Ignore)
+    }
+
+    /// Resolve the target to the absolute path of a file of the given kind, creating any
+    /// directories the tool manages, for writing it.
+    pub fn resolve(&self, kind: Kind) -> Result<PathBuf> {
+        self.resolve_in(kind, false)
+    }
+
+    /// Resolve the target as [`resolve`](Self::resolve) does, but letting [`File`](Self::File)
+    /// name any file in a git directory, where git would not read it.
+    pub fn resolve_allowing_git_dir(&self, kind: Kind) -> Result<PathBuf> {
+        self.resolve_in(kind, true)
+    }
+
+    /// Resolve the target to the absolute path of a file of the given kind without creating
+    /// anything, for reading or showing it.
+    pub fn locate(&self, kind: Kind) -> Result<PathBuf> {
+        self.locate_in(kind, false)
+    }
+
+    /// Resolve the target as [`locate`](Self::locate) does, but letting [`File`](Self::File)
+    /// name any file in a git directory, where git would not read it.
+    pub fn locate_allowing_git_dir(&self, kind: Kind) -> Result<PathBuf> {
+        self.locate_in(kind, true)
+    }
+
+    fn resolve_in(&self, kind: Kind, git_dir: bool) -> Result<PathBuf> {
+        let path = self.locate_in(kind, git_dir)?;
+        // The directory of the global file, and '.git/info'
+        if matches!(self, Self::Global | Self::Internal | Self::Sparse) {
+            if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                fs::create_dir_all(dir)?;
+            }
+        }
+        Ok(path)
+    }
+
+    fn locate_in(&self, kind: Kind, git_dir: bool) -> Result<PathBuf> {
+        match self {
+            Self::Local => Ok(env::current_dir()?.join(kind.file_name())),
+            Self::File(name) if git_dir => Ok(env::current_dir()?.join(name)),
+            Self::File(name) => outside_git_dir(env::current_dir()?.join(name)),
+            Self::Global => match kind.global() {
+                Some((keys, name)) => global_file(keys, name),
+                None => Err(unsupported(kind, "global")),
+            },
+            Self::Internal => match kind.info_name() {
+                Some(name) => info_file(name),
+                None => Err(unsupported(kind, "internal")),
+            },
+            Self::Root => root_file(kind),
+            Self::Sparse if kind == Kind::Ignore => info_file("sparse-checkout"),
+            Self::Sparse => Err(unsupported(kind, "sparse-checkout")),
+        }
+    }
+}
+
+fn unsupported(kind: Kind, target: &str) -> Box<dyn std::error::Error> {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("There is no {} {} file", target, kind.file_name()),
+    )
+    .into()
+}
+
+// The configured global file, or the default one
+fn global_file(keys: &[&str], name: &str) -> Result<PathBuf> {
+    let mut configured = None;
+    for key in keys {
+        configured = repo::config_path(key)?;
+        if configured.is_some() {
+            break;
+        }
+    }
+
+    let path = match configured {
+        Some(path) => path,
+        None => dirs::config_dir()
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "Could not find XDG_CONFIG_HOME")
+            })?
+            .join(name),
+    };
+    Ok(path)
+}
+
+// `path`, unless it is in a git directory, where it would do nothing but for the files in 'info'
+fn outside_git_dir(path: PathBuf) -> Result<PathBuf> {
+    // The file, and even its directory, may not exist yet
+    let dir = match path.ancestors().skip(1).find(|dir| dir.is_dir()) {
+        Some(dir) => dir,
+        None => return Ok(path),
+    };
+    let repo = match Repo::discover_from(dir) {
+        Ok(repo) => repo,
+        Err(_) => return Ok(path),
+    };
+    let full = repo::canonicalize(dir)?.join(path.strip_prefix(dir)?);
+
+    for git_dir in [repo.git_dir().to_path_buf(), repo.common_dir()] {
+        let git_dir = repo::canonicalize(git_dir)?;
+        if let Ok(inside) = full.strip_prefix(&git_dir) {
+            let known = ["exclude", "attributes", "sparse-checkout"]
+                .iter()
+                .any(|name| inside == Path::new("info").join(name));
+            if !known {
+                return Err(Error::InGitDir(path).into());
+            }
+        }
+    }
+    Ok(path)
+}
+
+// Linked worktrees share the files in 'info', but for 'sparse-checkout', which git reads from
+// each worktree's own directory
+fn info_file(name: &str) -> Result<PathBuf> {
+    let repo = Repo::discover()?;
+    let dir = match name {
+        "sparse-checkout" => repo.git_dir().join("info"),
+        _ => repo.common_dir().join("info"),
+    };
+    Ok(dir.join(name))
+}
+
+fn root_file(kind: Kind) -> Result<PathBuf> {
+    let repo = Repo::discover()?;
+    match repo.workdir() {
+        None => Err(Error::BareRepo(repo.git_dir().to_path_buf()).into()),
+        Some(path) => Ok(path.join(kind.file_name())),
+    }
+}
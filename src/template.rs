@@ -0,0 +1,217 @@
+//! Ignore file templates from [github/gitignore](https://github.com/github/gitignore).
+//!
+//! A few common templates are embedded in the crate, so they are available offline and always
+//! give the same result; with the `net` feature, any other template is downloaded from the
+//! repository, and templates can be pinned to one of its commits, so that they give the same
+//! result everywhere too. Templates in local directories take precedence over all of these, and
+//! then those of an organization's registry.
+
+use std::{error::Error, io, path::PathBuf};
+
+#[cfg(feature = "fs")]
+mod dir;
+#[cfg(feature = "net")]
+mod net;
+#[cfg(feature = "net")]
+mod registry;
+#[cfg(feature = "fs")]
+pub use self::dir::{directories, local};
+#[cfg(feature = "net")]
+pub use self::net::{fetch, fetch_at, is_offline, reference, set_offline, set_reference, UPSTREAM};
+#[cfg(feature = "net")]
+pub use self::registry::{registered, registry};
+
+// Kept sorted by name
+const EMBEDDED: &[(&str, &str)] = &[
+    ("C", include_str!("templates/C.gitignore")),
+    ("C++", include_str!("templates/C++.gitignore")),
+    ("Emacs", include_str!("templates/Emacs.gitignore")),
+    ("Go", include_str!("templates/Go.gitignore")),
+    ("Java", include_str!("templates/Java.gitignore")),
+    ("JetBrains", include_str!("templates/JetBrains.gitignore")),
+    ("Linux", include_str!("templates/Linux.gitignore")),
+    ("Node", include_str!("templates/Node.gitignore")),
+    ("Python", include_str!("templates/Python.gitignore")),
+    ("Rust", include_str!("templates/Rust.gitignore")),
+    ("Vim", include_str!("templates/Vim.gitignore")),
+    (
+        "VisualStudioCode",
+        include_str!("templates/VisualStudioCode.gitignore"),
+    ),
+    ("Windows", include_str!("templates/Windows.gitignore")),
+    ("macOS", include_str!("templates/macOS.gitignore")),
+];
+
+/// The names of the embedded templates.
+pub fn names() -> impl Iterator<Item = &'static str> {
+    EMBEDDED.iter().map(|(name, _)| *name)
+}
+
+/// The names of the templates there are but for those only upstream: the embedded ones, those in
+/// the template directories, and those of the registry (as it was when last reached, if it can't
+/// be now), sorted ignoring case.
+pub fn list() -> Result<Vec<String>, Box<dyn Error>> {
+    let mut names: Vec<String> = names().map(str::to_string).collect();
+    #[cfg(feature = "fs")]
+    names.extend(local()?.into_iter().map(|(name, _)| name));
+    #[cfg(feature = "net")]
+    names.extend(registered()?);
+    names.sort_by_key(|name| name.to_lowercase());
+    names.dedup();
+    Ok(names)
+}
+
+/// The embedded template called `name`, ignoring case.
+pub fn embedded(name: &str) -> Option<&'static str> {
+    EMBEDDED
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, text)| *text)
+}
+
+/// Where [`get`] takes a template from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Source {
+    /// A file in one of the template directories (see [`directories`]).
+    File(PathBuf),
+    /// The registry at this URL (see [`registry`]).
+    Registry(String),
+    /// The copy embedded in the crate.
+    Embedded,
+    /// github/gitignore: the commit it is pinned to, or else `main`.
+    Upstream(Option<String>),
+}
+
+/// Where the template called `name` is: the first of the template directories that has it,
+/// otherwise the registry if it has it, otherwise the embedded copy, otherwise
+/// github/gitignore; pinned, github/gitignore rather than the embedded copy.
+pub fn source(name: &str) -> Result<Source, Box<dyn Error>> {
+    check_name(name)?;
+
+    #[cfg(feature = "fs")]
+    if let Some(file) = dir::find(name)? {
+        return Ok(Source::File(file));
+    }
+    #[cfg(feature = "net")]
+    if let Some(url) = registry()? {
+        if registered()?.iter().any(|registered| registered == name) {
+            return Ok(Source::Registry(url));
+        }
+    }
+    let pin = pinned()?;
+    if pin.is_none() && embedded(name).is_some() {
+        return Ok(Source::Embedded);
+    }
+    Ok(Source::Upstream(pin))
+}
+
+/// The template called `name`, from where [`source`] says; github/gitignore needs the `net`
+/// feature.
+pub fn get(name: &str) -> Result<String, Box<dyn Error>> {
+    get_in(name, false)
+}
+
+/// The template called `name` as [`get`] gives it, but one from the registry even if it can't
+/// be verified, with a warning.
+pub fn get_allowing_unverified(name: &str) -> Result<String, Box<dyn Error>> {
+    get_in(name, true)
+}
+
+#[cfg_attr(not(feature = "net"), allow(unused_variables))]
+fn get_in(name: &str, unverified: bool) -> Result<String, Box<dyn Error>> {
+    match source(name)? {
+        Source::File(file) => Ok(crate::bytes::read(file)?),
+        #[cfg(feature = "net")]
+        Source::Registry(_) => registry::fetch(name, unverified),
+        #[cfg(not(feature = "net"))]
+        Source::Registry(_) => Err(unknown(name)),
+        Source::Embedded => Ok(embedded(name).ok_or_else(|| unknown(name))?.to_string()),
+        #[cfg(feature = "net")]
+        Source::Upstream(pin) => fetch_at(name, pin.as_deref()),
+        #[cfg(not(feature = "net"))]
+        Source::Upstream(_) => Err(unknown(name)),
+    }
+}
+
+/// The commit, tag or branch of github/gitignore that templates are pinned to, if any; never
+/// without the `net` feature, which pinning needs.
+pub fn pinned() -> Result<Option<String>, Box<dyn Error>> {
+    #[cfg(feature = "net")]
+    return reference();
+
+    #[cfg(not(feature = "net"))]
+    Ok(None)
+}
+
+/// Replace the `{{NAME}}` placeholders of `text` with the values `vars` gives for them.
+///
+/// Names are made of ASCII letters, digits, `_` and `-`, and may be surrounded by spaces inside
+/// the braces; anything else in double braces is left alone. A placeholder `vars` has no value
+/// for is an error.
+pub fn substitute<F>(text: &str, vars: F) -> Result<String, Box<dyn Error>>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+
+        let name = after.find("}}").map(|end| (after[..end].trim(), end));
+        match name {
+            Some((name, end))
+                if !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') =>
+            {
+                match vars(name) {
+                    Some(value) => result.push_str(&value),
+                    None => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::NotFound,
+                            format!("Undefined variable '{}'", name),
+                        )
+                        .into())
+                    },
+                }
+                rest = &after[end + 2..];
+            },
+            _ => {
+                result.push_str("{{");
+                rest = after;
+            },
+        }
+    }
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+fn unknown(name: &str) -> Box<dyn Error> {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("Unknown template '{}'", name),
+    )
+    .into()
+}
+
+// Names end up in URLs and paths
+fn check_name(name: &str) -> io::Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+-._".contains(c));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid template name '{}'", name),
+        ))
+    }
+}
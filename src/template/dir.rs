@@ -0,0 +1,72 @@
+//! Template directories of `NAME.gitignore` files, such as a clone of github/gitignore or a share
+//! of a company's own.
+
+use super::check_name;
+use crate::{repo, Result};
+use std::{collections::BTreeMap, env, fs, io, path::PathBuf};
+
+// Where a clone of github/gitignore has templates
+const SUBDIRS: &[&str] = &["", "Global"];
+
+/// The template directories, in order of precedence: those in the `GIT_IGNORE_TEMPLATE_PATH`
+/// environment variable, or else in the `ignore.templatePath` config, separated as in `PATH`;
+/// a leading `~/` is expanded to the home directory.
+pub fn directories() -> Result<Vec<PathBuf>> {
+    let path = match env::var_os("GIT_IGNORE_TEMPLATE_PATH").filter(|path| !path.is_empty()) {
+        Some(path) => path,
+        None => match repo::config("ignore.templatePath")? {
+            Some(path) => path.into(),
+            None => return Ok(vec![]),
+        },
+    };
+
+    Ok(env::split_paths(&path)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map(|dir| match (dir.strip_prefix("~"), dirs::home_dir()) {
+            (Ok(rest), Some(home)) => home.join(rest),
+            _ => dir,
+        })
+        .collect())
+}
+
+/// The templates in the template directories, sorted by name, each with the file it is in
+/// (the one in the first directory, if several have it).
+pub fn local() -> Result<Vec<(String, PathBuf)>> {
+    let mut templates = BTreeMap::new();
+    for dir in directories()? {
+        for subdir in SUBDIRS {
+            let entries = match fs::read_dir(dir.join(subdir)) {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            for entry in entries {
+                let path = entry?.path();
+                let name = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|name| name.strip_suffix(".gitignore"))
+                    .filter(|name| check_name(name).is_ok() && path.is_file());
+                if let Some(name) = name {
+                    templates
+                        .entry(name.to_string())
+                        .or_insert_with(|| path.clone());
+                }
+            }
+        }
+    }
+    Ok(templates.into_iter().collect())
+}
+
+// The file of the template called `name` in the first template directory that has one
+pub fn find(name: &str) -> Result<Option<PathBuf>> {
+    for dir in directories()? {
+        for subdir in SUBDIRS {
+            let file = dir.join(subdir).join(format!("{}.gitignore", name));
+            if file.is_file() {
+                return Ok(Some(file));
+            }
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,204 @@
+//! Downloading templates, as [`http`](crate::http) does; every download is cached, and the
+//! cached copy is used when the network fails.
+//!
+//! In offline mode, nothing is downloaded, and only cached templates are available.
+
+use super::{check_name, unknown};
+use crate::{cache, http, repo};
+use std::{
+    env,
+    error::Error,
+    fs, io,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+
+/// Where github/gitignore is downloaded from.
+pub const UPSTREAM: &str = "https://raw.githubusercontent.com/github/gitignore/main";
+
+// Where any commit of it is
+const REPOSITORY: &str = "https://raw.githubusercontent.com/github/gitignore";
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+static REFERENCE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Turn offline mode on (or off, unless it is configured).
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Whether nothing may be downloaded: after [`set_offline`], if the `GIT_IGNORE_OFFLINE`
+/// environment variable is set (to anything but `0`), or if the `ignore.offline` config is true.
+pub fn is_offline() -> Result<bool, Box<dyn Error>> {
+    if OFFLINE.load(Ordering::Relaxed) {
+        return Ok(true);
+    }
+    if let Some(value) = env::var_os("GIT_IGNORE_OFFLINE") {
+        return Ok(!value.is_empty() && value != "0");
+    }
+
+    repo::config_bool("ignore.offline")
+}
+
+/// Pin templates to a commit, tag or branch of github/gitignore, instead of what is on `main`
+/// when they are downloaded (or, with `None`, unpin them, unless the pin is configured).
+pub fn set_reference(reference: Option<&str>) -> io::Result<()> {
+    if let Some(reference) = reference {
+        check_reference(reference)?;
+    }
+    *REFERENCE.lock().unwrap_or_else(|e| e.into_inner()) = reference.map(str::to_string);
+    Ok(())
+}
+
+/// The commit, tag or branch templates are pinned to: after [`set_reference`], or as the
+/// `ignore.templateRef` config says; with none, templates follow `main`.
+pub fn reference() -> Result<Option<String>, Box<dyn Error>> {
+    let reference = REFERENCE.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    if reference.is_some() {
+        return Ok(reference);
+    }
+
+    let reference = repo::config("ignore.templateRef")?;
+    if let Some(reference) = &reference {
+        check_reference(reference)?;
+    }
+    Ok(reference)
+}
+
+/// Download the template called `name` from github/gitignore, looking in the `Global`
+/// directory too. Names are case-sensitive, as upstream. A pinned template (see [`reference`])
+/// comes from that commit.
+///
+/// If the network fails, the copy cached by an earlier download is used instead, with a warning
+/// saying how old it is; in offline mode, it is used without trying the network.
+pub fn fetch(name: &str) -> Result<String, Box<dyn Error>> {
+    fetch_at(name, reference()?.as_deref())
+}
+
+/// Download the template called `name` as [`fetch`] does, from the commit, tag or branch
+/// `reference` of github/gitignore, or `main` with `None`.
+pub fn fetch_at(name: &str, reference: Option<&str>) -> Result<String, Box<dyn Error>> {
+    check_name(name)?;
+    if let Some(reference) = reference {
+        check_reference(reference)?;
+    }
+
+    if is_offline()? {
+        return match cached(name, reference) {
+            Some((text, _)) => Ok(text),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                match reference {
+                    Some(reference) => format!(
+                        "Template '{}' from {} is not available offline (it is not cached)",
+                        name, reference
+                    ),
+                    None => format!(
+                        "Template '{}' is not available offline (it is neither embedded nor cached)",
+                        name
+                    ),
+                },
+            )
+            .into()),
+        };
+    }
+
+    match download(name, reference) {
+        Ok(Some(text)) => {
+            store(name, reference, &text);
+            Ok(text)
+        },
+        Ok(None) => Err(unknown(name)),
+        Err(error) => match cached(name, reference) {
+            Some((text, age)) => {
+                eprintln!(
+                    "Warning: could not download template '{}' ({}); using the copy cached {} ago",
+                    name,
+                    error,
+                    describe(age)
+                );
+                Ok(text)
+            },
+            None => Err(error),
+        },
+    }
+}
+
+// The template, or `None` if upstream doesn't have it
+fn download(name: &str, reference: Option<&str>) -> Result<Option<String>, Box<dyn Error>> {
+    let base = match reference {
+        Some(reference) => format!("{}/{}", REPOSITORY, reference),
+        None => UPSTREAM.to_string(),
+    };
+    for dir in ["", "Global/"] {
+        let url = format!("{}/{}{}.gitignore", base, dir, name);
+        if let Some(body) = http::get(&url)? {
+            return Ok(Some(String::from_utf8(body)?));
+        }
+    }
+
+    Ok(None)
+}
+
+// Pinned templates are kept apart, by commit
+fn cache_file(name: &str, reference: Option<&str>) -> Option<PathBuf> {
+    let mut dir = cache::dir().ok()?.join("templates");
+    if let Some(reference) = reference {
+        dir.push(format!("@{}", reference));
+    }
+    Some(dir.join(format!("{}.gitignore", name)))
+}
+
+// Failing to cache only loses the fallback
+fn store(name: &str, reference: Option<&str>, text: &str) {
+    if let Some(file) = cache_file(name, reference) {
+        if let Some(dir) = file.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        let _ = fs::write(file, text);
+    }
+}
+
+// The cached template and how old it is
+fn cached(name: &str, reference: Option<&str>) -> Option<(String, Duration)> {
+    let file = cache_file(name, reference)?;
+    let text = fs::read_to_string(&file).ok()?;
+    let age = fs::metadata(&file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|time| SystemTime::now().duration_since(time).ok())
+        .unwrap_or_default();
+
+    Some((text, age))
+}
+
+fn describe(age: Duration) -> String {
+    let minutes = age.as_secs() / 60;
+    match (minutes / (24 * 60), minutes / 60) {
+        (0, 0) => format!("{} minute(s)", minutes),
+        (0, hours) => format!("{} hour(s)", hours),
+        (days, _) => format!("{} day(s)", days),
+    }
+}
+
+// References end up in URLs and paths too; a tag with a '/' can go by its commit instead
+fn check_reference(reference: &str) -> io::Result<()> {
+    let valid = !reference.is_empty()
+        && !reference.starts_with('.')
+        && reference
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-._".contains(c));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid template reference '{}'", reference),
+        ))
+    }
+}
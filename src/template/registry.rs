@@ -0,0 +1,471 @@
+//! A registry of the templates an organization approves, as the `ignore.registry` config names
+//! it: a git repository of `NAME.gitignore` files (a URL ending in `.git`, or any other that a
+//! `git+` prefix marks), cloned into the cache and pulled once a run, or the URL of a directory
+//! of them, with an `index` file listing their names, a line each.
+//!
+//! A private registry gets credentials as [`http::get_authenticated`] says, or, for a git
+//! repository, as git gets them. What is downloaded is kept in the cache, and used when the
+//! network fails, or in offline mode.
+//!
+//! Templates are only used once they are verified: a `SHA256SUMS` file next to them, as
+//! `sha256sum` writes it, must have their checksums, and if the `ignore.registryKey` config
+//! names a key, that file must be signed by it, in `SHA256SUMS.minisig` for a minisign public
+//! key or in `SHA256SUMS.asc` for the fingerprint or long ID of a GPG one (which `minisign` or
+//! `gpg` checks). A key that is neither is an error.
+
+use super::{check_name, is_offline, unknown};
+use crate::{bytes, cache, http, repo, Error, Result};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Mutex,
+};
+
+// The checksums of the templates, and their signatures
+const SUMS: &str = "SHA256SUMS";
+const MINISIG: &str = "SHA256SUMS.minisig";
+const ASC: &str = "SHA256SUMS.asc";
+
+// The names of the templates and their checksums, found once a run
+static NAMES: Mutex<Option<Vec<String>>> = Mutex::new(None);
+static CHECKSUMS: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
+enum Registry {
+    Git(String),
+    Web(String),
+}
+
+/// The URL of the registry, if one is configured.
+pub fn registry() -> Result<Option<String>> {
+    Ok(repo::config("ignore.registry")?.filter(|url| !url.is_empty()))
+}
+
+/// The names of the templates in the registry, sorted; none without a registry.
+pub fn registered() -> Result<Vec<String>> {
+    let mut names = NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(names) = &*names {
+        return Ok(names.clone());
+    }
+
+    let found = match registry()?.as_deref().map(parse) {
+        None => vec![],
+        Some(Registry::Git(url)) => {
+            let dir = cache_dir(&url)?;
+            sync(&url, &dir)?;
+            clone_names(&dir)?
+        },
+        Some(Registry::Web(url)) => index(&url)?,
+    };
+    *names = Some(found.clone());
+    Ok(found)
+}
+
+/// The template called `name` from the registry, once it is verified; with `unverified`, even if
+/// it can't be, with a warning.
+pub fn fetch(name: &str, unverified: bool) -> Result<String> {
+    check_name(name)?;
+    if !registered()?.iter().any(|registered| registered == name) {
+        return Err(unknown(name));
+    }
+
+    // The file, as SHA256SUMS names it
+    let (file, text) = match registry()?.as_deref().map(parse) {
+        None => return Err(unknown(name)),
+        Some(Registry::Git(url)) => {
+            let dir = cache_dir(&url)?;
+            let file = [
+                format!("{}.gitignore", name),
+                format!("Global/{}.gitignore", name),
+            ]
+            .into_iter()
+            .find(|file| dir.join(file).is_file())
+            .ok_or_else(|| unknown(name))?;
+            let text = bytes::read(dir.join(&file))?;
+            (file, text)
+        },
+        Some(Registry::Web(url)) => {
+            let file = format!("{}.gitignore", name);
+            let text = download(&format!("{}/{}", url, file), &cache_dir(&url)?.join(&file))?;
+            (file, text)
+        },
+    };
+
+    match verify(name, &file, &text) {
+        Err(e) if unverified => {
+            eprintln!("Warning: {}; using it anyway", e);
+            Ok(text)
+        },
+        result => result.map(|_| text),
+    }
+}
+
+fn verify(name: &str, file: &str, text: &str) -> Result<()> {
+    let unverified = |reason: String| -> Box<dyn std::error::Error> {
+        Error::Unverified {
+            name: name.to_string(),
+            reason,
+        }
+        .into()
+    };
+
+    let checksums = checksums().map_err(|e| unverified(e.to_string()))?;
+    let expected = checksums
+        .get(file)
+        .ok_or_else(|| unverified(format!("{} has no checksum for {}", SUMS, file)))?;
+    let actual = format!("{:x}", Sha256::digest(bytes::encode(text)));
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(unverified(format!(
+            "its checksum is {}, not {} as {} says",
+            actual, expected, SUMS
+        )));
+    }
+    Ok(())
+}
+
+// The checksums SHA256SUMS gives, by file, once its signature is verified if a key is configured
+fn checksums() -> Result<HashMap<String, String>> {
+    let mut checksums = CHECKSUMS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(checksums) = &*checksums {
+        return Ok(checksums.clone());
+    }
+
+    let key = repo::config("ignore.registryKey")?
+        .filter(|key| !key.is_empty())
+        .map(|key| Key::parse(&key))
+        .transpose()?;
+    let dir = match registry()?.as_deref().map(parse) {
+        None => return Ok(HashMap::new()),
+        Some(Registry::Git(url)) => cache_dir(&url)?,
+        Some(Registry::Web(url)) => {
+            let dir = cache_dir(&url)?;
+            download(&format!("{}/{}", url, SUMS), &dir.join(SUMS))?;
+            if let Some(key) = &key {
+                let signature = key.signature();
+                download(&format!("{}/{}", url, signature), &dir.join(signature))?;
+            }
+            dir
+        },
+    };
+    // As downloaded, so that it is what the signature is checked against
+    let sums = fs::read_to_string(dir.join(SUMS))
+        .map_err(|e| format!("could not read {} ({})", SUMS, e))?;
+    if let Some(key) = &key {
+        check_signature(key, &dir)?;
+    }
+
+    let mut found = HashMap::new();
+    for line in sums.lines() {
+        // A '*' marks files read in binary mode
+        if let Some((checksum, file)) = line.split_once(' ') {
+            let file = file.trim_start_matches([' ', '*']);
+            let file = file.strip_prefix("./").unwrap_or(file);
+            found.insert(file.to_string(), checksum.to_string());
+        }
+    }
+    *checksums = Some(found.clone());
+    Ok(found)
+}
+
+// The key `ignore.registryKey` names
+enum Key {
+    // A minisign public key
+    Minisign(String),
+    // The fingerprint of a GPG key, or its 16-digit ID, in upper case
+    Gpg(String),
+}
+
+impl Key {
+    // A minisign public key is base64 starting with 'RW'; a GPG key is given by its fingerprint
+    // (40 hexadecimal digits, or 64 for a v5 key) or its long ID, with any spaces or '0x'
+    fn parse(key: &str) -> Result<Self> {
+        if key.starts_with("RW") {
+            let base64 = |c: char| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=');
+            if key.len() == 56 && key.chars().all(base64) {
+                return Ok(Self::Minisign(key.to_string()));
+            }
+            return Err(format!(
+                "ignore.registryKey '{}' is not a minisign public key (56 characters of base64)",
+                key
+            )
+            .into());
+        }
+
+        let hex = key.replace(' ', "").to_uppercase();
+        let hex = hex.strip_prefix("0X").unwrap_or(&hex);
+        if matches!(hex.len(), 16 | 40 | 64) && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Ok(Self::Gpg(hex.to_string()));
+        }
+        Err(format!(
+            "ignore.registryKey '{}' is neither a minisign public key nor the fingerprint or \
+             16-digit ID of a GPG key",
+            key
+        )
+        .into())
+    }
+
+    fn signature(&self) -> &'static str {
+        match self {
+            Self::Minisign(_) => MINISIG,
+            Self::Gpg(_) => ASC,
+        }
+    }
+}
+
+fn check_signature(key: &Key, dir: &Path) -> Result<()> {
+    let (tool, output) = match key {
+        Key::Minisign(key) => {
+            let output = Command::new("minisign")
+                .args(["-V", "-q", "-P", key, "-m"])
+                .arg(dir.join(SUMS))
+                .arg("-x")
+                .arg(dir.join(MINISIG))
+                .output();
+            ("minisign", output)
+        },
+        Key::Gpg(_) => {
+            let output = Command::new("gpg")
+                .args(["--batch", "--no-tty", "--status-fd", "1", "--verify"])
+                .arg(dir.join(ASC))
+                .arg(dir.join(SUMS))
+                .output();
+            ("gpg", output)
+        },
+    };
+    let output = output.map_err(|e| format!("could not run {} ({})", tool, e))?;
+
+    let (signed, key) = match key {
+        Key::Minisign(key) => (output.status.success(), key),
+        Key::Gpg(key) => (
+            output.status.success() && signed_by(&output.stdout, key),
+            key,
+        ),
+    };
+    if signed {
+        Ok(())
+    } else {
+        Err(format!("{} has no valid signature by {}", SUMS, key).into())
+    }
+}
+
+// Whether gpg's status output has a good signature by the key, or a subkey of it; its line is
+// 'VALIDSIG FINGERPRINT ... PRIMARY-FINGERPRINT'. A key ID is the end of a v4 fingerprint and the
+// start of a v5 one
+fn signed_by(status: &[u8], key: &str) -> bool {
+    let matches = |fingerprint: &str| match (key.len(), fingerprint.len()) {
+        (16, 40) => fingerprint.ends_with(key),
+        (16, 64) => fingerprint.starts_with(key),
+        _ => fingerprint == key,
+    };
+    String::from_utf8_lossy(status).lines().any(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        fields.get(..2) == Some(&["[GNUPG:]", "VALIDSIG"])
+            && [fields.get(2), fields.get(11)]
+                .into_iter()
+                .flatten()
+                .any(|fingerprint| matches(&fingerprint.to_uppercase()))
+    })
+}
+
+fn parse(url: &str) -> Registry {
+    match url.strip_prefix("git+") {
+        Some(url) => Registry::Git(url.to_string()),
+        None if url.ends_with(".git") => Registry::Git(url.to_string()),
+        None => Registry::Web(url.trim_end_matches('/').to_string()),
+    }
+}
+
+// Where the registry is kept in the cache
+fn cache_dir(url: &str) -> Result<PathBuf> {
+    let key: String = url
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '.' => c,
+            _ => '_',
+        })
+        .collect();
+    Ok(cache::dir()?.join("registry").join(key))
+}
+
+// Clone the repository into `dir`, or pull it if it is there already; failing that, or in
+// offline mode, an earlier clone will do
+fn sync(url: &str, dir: &Path) -> Result<()> {
+    let cloned = dir.join(".git").is_dir();
+    if is_offline()? {
+        return if cloned { Ok(()) } else { Err(offline(url)) };
+    }
+
+    let mut git = Command::new("git");
+    if cloned {
+        git.arg("-C")
+            .arg(dir)
+            .args(["pull", "--ff-only", "--quiet"]);
+    } else {
+        if let Some(parent) = dir.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        git.args(["clone", "--depth", "1", "--quiet", url]).arg(dir);
+    }
+    let status = git.status();
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        _ if cloned => {
+            eprintln!(
+                "Warning: could not update the template registry {}; using the copy cloned earlier",
+                url
+            );
+            Ok(())
+        },
+        Ok(status) => Err(Error::Network {
+            url: url.to_string(),
+            reason: format!("git clone failed ({})", status),
+        }
+        .into()),
+        Err(e) => Err(Error::Network {
+            url: url.to_string(),
+            reason: format!("could not run git ({})", e),
+        }
+        .into()),
+    }
+}
+
+fn clone_names(dir: &Path) -> Result<Vec<String>> {
+    let mut names = vec![];
+    for dir in [dir.to_path_buf(), dir.join("Global")] {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".gitignore"))
+                .filter(|name| check_name(name).is_ok() && path.is_file());
+            names.extend(name.map(str::to_string));
+        }
+    }
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+// The names the index lists, without any '.gitignore' extension; blank lines and comments
+// starting with '#' are skipped
+fn index(url: &str) -> Result<Vec<String>> {
+    let index = download(&format!("{}/index", url), &cache_dir(url)?.join("index"))?;
+    let mut names = vec![];
+    for line in index.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let name = line.strip_suffix(".gitignore").unwrap_or(line);
+        check_name(name)?;
+        names.push(name.to_string());
+    }
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+// Download `url`, keeping it in `file`; failing that, or in offline mode, what was kept before
+fn download(url: &str, file: &Path) -> Result<String> {
+    if is_offline()? {
+        return bytes::read(file).map_err(|_| offline(url));
+    }
+
+    match http::get_authenticated(url) {
+        Ok(Some(body)) => {
+            // Failing to cache only loses the fallback
+            if let Some(dir) = file.parent() {
+                let _ = fs::create_dir_all(dir);
+            }
+            let _ = fs::write(file, &body);
+            Ok(bytes::decode(&body))
+        },
+        Ok(None) => Err(Error::Network {
+            url: url.to_string(),
+            reason: String::from("not found"),
+        }
+        .into()),
+        Err(e) => match bytes::read(file) {
+            Ok(text) => {
+                // The message names the URL
+                eprintln!("Warning: {}; using the copy downloaded earlier", e);
+                Ok(text)
+            },
+            Err(_) => Err(e),
+        },
+    }
+}
+
+fn offline(url: &str) -> Box<dyn std::error::Error> {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} is not available offline (it is not cached)", url),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FINGERPRINT: &str = "0123456789ABCDEF0123456789ABCDEF01234567";
+    const SUBKEY: &str = "FEDCBA9876543210FEDCBA9876543210FEDCBA98";
+
+    fn status(fingerprint: &str, primary: &str) -> Vec<u8> {
+        format!(
+            "[GNUPG:] GOODSIG 76543210FEDCBA98 Someone\n\
+             [GNUPG:] VALIDSIG {} 2024-05-01 1714564800 0 4 0 1 10 00 {}\n",
+            fingerprint, primary
+        )
+        .into_bytes()
+    }
+
+    fn gpg(key: &str) -> String {
+        match Key::parse(key) {
+            Ok(Key::Gpg(key)) => key,
+            _ => panic!("{} is not a GPG key", key),
+        }
+    }
+
+    #[test]
+    fn keys() {
+        let minisign = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+        assert!(matches!(Key::parse(minisign), Ok(Key::Minisign(_))));
+        assert!(Key::parse(&minisign[..55]).is_err());
+        assert!(Key::parse(&format!("{}!", &minisign[..55])).is_err());
+
+        assert_eq!(gpg("0x0123456789abcdef"), "0123456789ABCDEF");
+        assert_eq!(
+            gpg("0123 4567 89AB CDEF 0123  4567 89AB CDEF 0123 4567"),
+            FINGERPRINT
+        );
+        assert!(Key::parse("89ABCDEF").is_err());
+        assert!(Key::parse("0123456789ABCDEG").is_err());
+        assert!(Key::parse(&FINGERPRINT[1..]).is_err());
+    }
+
+    #[test]
+    fn signatures() {
+        let signed = status(SUBKEY, FINGERPRINT);
+        assert!(signed_by(&signed, FINGERPRINT));
+        assert!(signed_by(&signed, SUBKEY));
+        assert!(signed_by(&signed, &FINGERPRINT[24..]));
+        assert!(!signed_by(&signed, &FINGERPRINT[..16]));
+        assert!(!signed_by(&signed, &FINGERPRINT[1..]));
+        assert!(!signed_by(&signed, "0000000000000000"));
+        // Too short to be a key ID, or not a fingerprint at all
+        assert!(!signed_by(&signed, &FINGERPRINT[32..]));
+        assert!(!signed_by(&signed, "1714564800"));
+        assert!(!signed_by(
+            b"[GNUPG:] GOODSIG 76543210FEDCBA98 Someone\n",
+            SUBKEY
+        ));
+    }
+}
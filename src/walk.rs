@@ -0,0 +1,100 @@
+//! Walking big working trees.
+
+use std::{
+    fs, io,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{Condvar, Mutex, PoisonError},
+    thread,
+};
+
+// The directories waiting to be read, how many are being read, and the first error
+struct Queue {
+    dirs: Vec<PathBuf>,
+    busy: usize,
+    error: Option<io::Error>,
+}
+
+/// Walk `root` and the directories below it on as many threads as there are CPUs, for big
+/// trees.
+///
+/// `visit` gets each directory with its entries, and returns what to keep for it, if anything,
+/// and the subdirectories to walk into. What was kept is returned by directory, sorted so that
+/// parents come before their children, as in a depth-first walk.
+pub fn walk<T, F>(root: &Path, visit: F) -> io::Result<Vec<(PathBuf, T)>>
+where
+    T: Send,
+    F: Fn(&Path, Vec<fs::DirEntry>) -> (Option<T>, Vec<PathBuf>) + Sync,
+{
+    let queue = Mutex::new(Queue {
+        dirs: vec![root.to_path_buf()],
+        busy: 0,
+        error: None,
+    });
+    let ready = Condvar::new();
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+
+    let worker = || {
+        let mut kept = vec![];
+        loop {
+            let dir = {
+                let mut queue = queue.lock().unwrap_or_else(PoisonError::into_inner);
+                loop {
+                    if queue.error.is_some() {
+                        return kept;
+                    }
+                    if let Some(dir) = queue.dirs.pop() {
+                        queue.busy += 1;
+                        break dir;
+                    }
+                    if queue.busy == 0 {
+                        return kept;
+                    }
+                    queue = ready.wait(queue).unwrap_or_else(PoisonError::into_inner);
+                }
+            };
+
+            // A panic stops the other threads before going on, so that they aren't left waiting
+            let entries = fs::read_dir(&dir).and_then(|entries| entries.collect());
+            let visited = entries
+                .map(|entries| panic::catch_unwind(AssertUnwindSafe(|| visit(&dir, entries))));
+
+            let mut queue = queue.lock().unwrap_or_else(PoisonError::into_inner);
+            queue.busy -= 1;
+            ready.notify_all();
+            match visited {
+                Ok(Ok((keep, subdirs))) => {
+                    kept.extend(keep.map(|keep| (dir, keep)));
+                    queue.dirs.extend(subdirs);
+                },
+                Ok(Err(panic)) => {
+                    queue.error = Some(io::Error::other("Walk panicked"));
+                    drop(queue);
+                    panic::resume_unwind(panic);
+                },
+                Err(e) => queue.error = queue.error.take().or(Some(e)),
+            }
+        }
+    };
+
+    let mut kept: Vec<(PathBuf, T)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads).map(|_| scope.spawn(worker)).collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| match worker.join() {
+                Ok(kept) => kept,
+                Err(panic) => panic::resume_unwind(panic),
+            })
+            .collect()
+    });
+
+    if let Some(e) = queue
+        .into_inner()
+        .unwrap_or_else(PoisonError::into_inner)
+        .error
+    {
+        return Err(e);
+    }
+    kept.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(kept)
+}
@@ -0,0 +1,49 @@
+//! JavaScript bindings for the core, for `wasm32-unknown-unknown` builds:
+//!
+//! ```sh
+//! cargo build --lib --target wasm32-unknown-unknown --no-default-features --features wasm
+//! ```
+
+use crate::{Comments, Dedup, MergeOptions, Negations};
+use wasm_bindgen::prelude::*;
+
+/// [`merge`](crate::merge()) with the default options.
+#[wasm_bindgen(js_name = merge)]
+pub fn merge(existing: &str, additions: Vec<String>) -> String {
+    crate::merge(existing, &additions, MergeOptions::default())
+}
+
+/// [`merge`](crate::merge()) with explicit options; `dedup` is one of `"all"`, `"additions"` or
+/// `"none"`.
+#[wasm_bindgen(js_name = mergeWith)]
+pub fn merge_with(
+    existing: &str,
+    additions: Vec<String>,
+    sort: bool,
+    keep_comments: bool,
+    dedup: &str,
+    negations_last: bool,
+) -> Result<String, JsError> {
+    let options = MergeOptions {
+        sort,
+        comments: if keep_comments {
+            Comments::Keep
+        } else {
+            Comments::Strip
+        },
+        dedup: match dedup {
+            "all" => Dedup::All,
+            "additions" => Dedup::Additions,
+            "none" => Dedup::None,
+            _ => return Err(JsError::new(&format!("invalid dedup strategy: {}", dedup))),
+        },
+        negations: if negations_last {
+            Negations::Last
+        } else {
+            Negations::InPlace
+        },
+        ..MergeOptions::default()
+    };
+
+    Ok(crate::merge(existing, &additions, options))
+}